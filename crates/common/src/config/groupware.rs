@@ -4,9 +4,14 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+use crate::Server;
+use ahash::AHashMap;
 use std::{str::FromStr, time::Duration};
 
-use utils::{config::Config, template::Template};
+use utils::{
+    config::{Config, utils::ParseValue},
+    template::Template,
+};
 
 #[derive(Debug, Clone, Default)]
 pub struct GroupwareConfig {
@@ -21,10 +26,15 @@ pub struct GroupwareConfig {
 
     // Calendar settings
     pub max_ical_size: usize,
+    pub size_limits: Vec<GroupwareSizeLimit>,
     pub max_ical_instances: usize,
+    pub max_ical_overrides: usize,
+    pub max_ical_horizon: i64,
     pub max_ical_attendees_per_instance: usize,
     pub default_calendar_name: Option<String>,
     pub default_calendar_display_name: Option<String>,
+    pub default_tasklist_name: Option<String>,
+    pub default_tasklist_display_name: Option<String>,
     pub alarms_enabled: bool,
     pub alarms_minimum_interval: i64,
     pub alarms_allow_external_recipients: bool,
@@ -33,12 +43,15 @@ pub struct GroupwareConfig {
     pub alarms_template: Template<CalendarTemplateVariable>,
     pub itip_enabled: bool,
     pub itip_auto_add: bool,
+    pub itip_validate_timezones: bool,
     pub itip_inbound_max_ical_size: usize,
     pub itip_outbound_max_recipients: usize,
     pub itip_http_rsvp_url: Option<String>,
     pub itip_http_rsvp_expiration: u64,
     pub itip_inbox_auto_expunge: Option<u64>,
+    pub itip_attendee_aliases: AHashMap<String, String>,
     pub itip_template: Template<CalendarTemplateVariable>,
+    pub free_busy_default_visibility: FreeBusyVisibility,
 
     // Addressbook settings
     pub max_vcard_size: usize,
@@ -47,12 +60,28 @@ pub struct GroupwareConfig {
 
     // File storage settings
     pub max_file_size: usize,
+    pub file_upload_quota: bool,
 
     // Sharing settings
     pub max_shares_per_item: usize,
     pub allow_directory_query: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub enum FreeBusyVisibility {
+    None,
+    #[default]
+    BusyOnly,
+    Full,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupwareSizeLimit {
+    pub accounts: Vec<String>,
+    pub max_ical_size: Option<usize>,
+    pub max_vcard_size: Option<usize>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
 pub enum CalendarTemplateVariable {
     #[default]
@@ -104,6 +133,12 @@ impl GroupwareConfig {
                     "Stalwart Calendar",
                 )
                 .unwrap_or_default(),
+            default_tasklist_name: config
+                .property::<Option<String>>("calendar.default-tasklist.href-name")
+                .unwrap_or_default(),
+            default_tasklist_display_name: config
+                .property::<Option<String>>("calendar.default-tasklist.display-name")
+                .unwrap_or_default(),
             default_addressbook_name: config
                 .property_or_default::<Option<String>>("contacts.default.href-name", "default")
                 .unwrap_or_default(),
@@ -114,9 +149,17 @@ impl GroupwareConfig {
                 )
                 .unwrap_or_default(),
             max_ical_size: config.property("calendar.max-size").unwrap_or(512 * 1024),
+            size_limits: parse_size_limits(config),
             max_ical_instances: config
                 .property("calendar.max-recurrence-expansions")
                 .unwrap_or(3000),
+            max_ical_overrides: config
+                .property("calendar.max-recurrence-overrides")
+                .unwrap_or(3000),
+            max_ical_horizon: config
+                .property_or_default::<Duration>("calendar.max-recurrence-horizon", "1825d")
+                .unwrap_or(Duration::from_secs(1825 * 24 * 60 * 60))
+                .as_secs() as i64,
             max_ical_attendees_per_instance: config
                 .property("calendar.max-attendees-per-instance")
                 .unwrap_or(20),
@@ -124,6 +167,9 @@ impl GroupwareConfig {
             max_file_size: config
                 .property("file-storage.max-size")
                 .unwrap_or(25 * 1024 * 1024),
+            file_upload_quota: config
+                .property("file-storage.upload.enforce-quota")
+                .unwrap_or(false),
             alarms_enabled: config.property("calendar.alarms.enabled").unwrap_or(true),
             alarms_minimum_interval: config
                 .property_or_default::<Duration>("calendar.alarms.minimum-interval", "1h")
@@ -150,6 +196,9 @@ impl GroupwareConfig {
             itip_auto_add: config
                 .property("calendar.scheduling.inbound.auto-add")
                 .unwrap_or(false),
+            itip_validate_timezones: config
+                .property("calendar.scheduling.validate-timezones")
+                .unwrap_or(false),
             itip_inbound_max_ical_size: config
                 .property("calendar.scheduling.inbound.max-size")
                 .unwrap_or(512 * 1024),
@@ -163,6 +212,16 @@ impl GroupwareConfig {
                 )
                 .map(|d| d.map(|d| d.as_secs()))
                 .unwrap_or(Some(30 * 24 * 60 * 60)),
+            itip_attendee_aliases: config
+                .values("calendar.scheduling.inbound.attendee-aliases")
+                .filter_map(|(_, value)| {
+                    value.rsplit_once('=').and_then(|(alias, email)| {
+                        let alias = alias.trim().to_lowercase();
+                        let email = email.trim().to_lowercase();
+                        (!alias.is_empty() && email.contains('@')).then_some((alias, email))
+                    })
+                })
+                .collect(),
             itip_http_rsvp_url: if config
                 .property("calendar.scheduling.http-rsvp.enable")
                 .unwrap_or(true)
@@ -195,6 +254,122 @@ impl GroupwareConfig {
                 "/../../resources/html-templates/calendar-invite.html.min"
             )))
             .expect("Failed to parse calendar template"),
+            free_busy_default_visibility: config
+                .property_or_default("calendar.freebusy.default-visibility", "busy-only")
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns what a free/busy query discloses, members of the account always
+    /// see the full details while everyone else gets the configured default.
+    pub fn free_busy_visibility(&self, is_member: bool) -> FreeBusyVisibility {
+        if is_member {
+            FreeBusyVisibility::Full
+        } else {
+            self.free_busy_default_visibility
+        }
+    }
+
+    /// Returns the maximum iCalendar object size for the account, falling back
+    /// to `calendar.max-size` when no size limit entry matches it.
+    pub fn ical_size_limit(&self, account_name: &str) -> usize {
+        self.size_limits
+            .iter()
+            .filter(|limit| limit.matches(account_name))
+            .find_map(|limit| limit.max_ical_size)
+            .unwrap_or(self.max_ical_size)
+    }
+
+    /// Returns the maximum vCard object size for the account, falling back
+    /// to `contacts.max-size` when no size limit entry matches it.
+    pub fn vcard_size_limit(&self, account_name: &str) -> usize {
+        self.size_limits
+            .iter()
+            .filter(|limit| limit.matches(account_name))
+            .find_map(|limit| limit.max_vcard_size)
+            .unwrap_or(self.max_vcard_size)
+    }
+}
+
+impl Server {
+    /// Returns the maximum iCalendar object size for objects owned by the account.
+    pub async fn ical_size_limit(&self, account_id: u32) -> trc::Result<usize> {
+        let groupware = &self.core.groupware;
+        if groupware.size_limits.is_empty() {
+            Ok(groupware.max_ical_size)
+        } else {
+            self.get_access_token(account_id)
+                .await
+                .map(|owner| groupware.ical_size_limit(&owner.name))
+        }
+    }
+
+    /// Returns the maximum vCard object size for objects owned by the account.
+    pub async fn vcard_size_limit(&self, account_id: u32) -> trc::Result<usize> {
+        let groupware = &self.core.groupware;
+        if groupware.size_limits.is_empty() {
+            Ok(groupware.max_vcard_size)
+        } else {
+            self.get_access_token(account_id)
+                .await
+                .map(|owner| groupware.vcard_size_limit(&owner.name))
+        }
+    }
+}
+
+impl GroupwareSizeLimit {
+    fn matches(&self, account_name: &str) -> bool {
+        self.accounts.iter().any(|pattern| {
+            if let Some(domain) = pattern.strip_prefix("*@") {
+                account_name
+                    .rsplit_once('@')
+                    .is_some_and(|(_, account_domain)| account_domain.eq_ignore_ascii_case(domain))
+            } else {
+                pattern.eq_ignore_ascii_case(account_name)
+            }
+        })
+    }
+}
+
+fn parse_size_limits(config: &mut Config) -> Vec<GroupwareSizeLimit> {
+    let mut limits = Vec::new();
+    for id in config.sub_keys("groupware.size-limit", "") {
+        let accounts = config
+            .values(("groupware.size-limit", id.as_str(), "accounts"))
+            .map(|(_, account)| account.trim().to_lowercase())
+            .filter(|account| !account.is_empty())
+            .collect::<Vec<_>>();
+        let max_ical_size = config.property(("groupware.size-limit", id.as_str(), "calendar"));
+        let max_vcard_size = config.property(("groupware.size-limit", id.as_str(), "contacts"));
+
+        if accounts.is_empty() {
+            config.new_parse_error(
+                ("groupware.size-limit", id.as_str(), "accounts"),
+                "At least one account must be specified.".to_string(),
+            );
+        } else if max_ical_size.is_none() && max_vcard_size.is_none() {
+            config.new_parse_error(
+                ("groupware.size-limit", id.as_str()),
+                "At least one of 'calendar' or 'contacts' must be specified.".to_string(),
+            );
+        } else {
+            limits.push(GroupwareSizeLimit {
+                accounts,
+                max_ical_size,
+                max_vcard_size,
+            });
+        }
+    }
+    limits
+}
+
+impl ParseValue for FreeBusyVisibility {
+    fn parse_value(value: &str) -> Result<Self, String> {
+        match value {
+            "none" | "disable" | "disabled" => Ok(FreeBusyVisibility::None),
+            "busy-only" | "busy" => Ok(FreeBusyVisibility::BusyOnly),
+            "full" => Ok(FreeBusyVisibility::Full),
+            _ => Err(format!("Invalid free/busy visibility value {:?}.", value)),
         }
     }
 }
@@ -20,6 +20,8 @@ pub struct ImapConfig {
 
     pub rate_requests: Option<Rate>,
     pub rate_concurrent: Option<u64>,
+
+    pub shared_read_only_hint: bool,
 }
 
 impl ImapConfig {
@@ -49,6 +51,9 @@ impl ImapConfig {
             allow_plain_auth: config
                 .property_or_default("imap.auth.allow-plain-text", "false")
                 .unwrap_or(false),
+            shared_read_only_hint: config
+                .property_or_default("imap.namespace.read-only-hint", "false")
+                .unwrap_or(false),
         }
     }
 }
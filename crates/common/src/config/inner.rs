@@ -11,7 +11,7 @@ use crate::{
     auth::{AccessToken, roles::RolePermissions},
     config::{
         smtp::resolver::{Policy, Tlsa},
-        spamfilter::SpamClassifier,
+        spamfilter::{DnsBlListing, SpamClassifier},
     },
     listener::blocked::BlockedIps,
     manager::webadmin::WebAdminManager,
@@ -29,6 +29,7 @@ use utils::{
     cache::{Cache, CacheWithTtl},
     config::Config,
     snowflake::SnowflakeIdGenerator,
+    suffixlist::PublicSuffix,
 };
 
 impl Data {
@@ -63,10 +64,15 @@ impl Data {
             .ok()
             .map(Arc::new),
             blocked_ips: RwLock::new(BlockedIps::parse(config).blocked_ip_addresses),
+            public_suffix: ArcSwap::from_pointee(PublicSuffix::embedded()),
+            dnsbl_metrics: Default::default(),
+            dnsbl_lookup: None,
+            dsn_text: Default::default(),
             jmap_id_gen: id_generator.clone(),
             queue_id_gen: id_generator.clone(),
             span_id_gen: id_generator,
             queue_status: true.into(),
+            queue_health: Default::default(),
             webadmin: config
                 .value("webadmin.path")
                 .map(|path| WebAdminManager::new(path.into()))
@@ -189,6 +195,18 @@ impl Caches {
                 MB_5,
                 ((std::mem::size_of::<Ipv4Addr>() + 255) * 2) as u64,
             ),
+            dns_rbl_listings: Cache::from_config(
+                config,
+                "dns.rbl-listing",
+                MB_1,
+                (std::mem::size_of::<DnsBlListing>() + 255) as u64,
+            ),
+            remote_max_size: CacheWithTtl::from_config(
+                config,
+                "smtp.remote-size",
+                MB_1,
+                (std::mem::size_of::<u64>() + 255) as u64,
+            ),
         }
     }
 
@@ -224,10 +242,15 @@ impl Default for Data {
             tls_certificates: Default::default(),
             tls_self_signed_cert: Default::default(),
             blocked_ips: Default::default(),
+            public_suffix: ArcSwap::from_pointee(PublicSuffix::embedded()),
+            dnsbl_metrics: Default::default(),
+            dnsbl_lookup: None,
+            dsn_text: Default::default(),
             jmap_id_gen: Default::default(),
             queue_id_gen: Default::default(),
             span_id_gen: Default::default(),
             queue_status: true.into(),
+            queue_health: Default::default(),
             webadmin: Default::default(),
             logos: Default::default(),
             smtp_connectors: Default::default(),
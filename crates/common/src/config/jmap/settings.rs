@@ -52,6 +52,7 @@ pub struct JmapConfig {
     pub calendar_parse_max_items: usize,
 
     pub sieve_max_script_name: usize,
+    pub sieve_max_versions: usize,
     pub max_objects: [u32; Collection::MAX],
 
     pub rate_authenticated: Option<Rate>,
@@ -289,6 +290,9 @@ impl JmapConfig {
             sieve_max_script_name: config
                 .property("sieve.untrusted.limits.name-length")
                 .unwrap_or(512),
+            sieve_max_versions: config
+                .property("sieve.untrusted.limits.max-versions")
+                .unwrap_or(5),
             max_objects: [u32::MAX; Collection::MAX],
             capabilities: BaseCapabilities::default(),
             rate_authenticated: config
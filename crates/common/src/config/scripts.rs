@@ -23,14 +23,20 @@ use super::{if_block::IfBlock, smtp::SMTP_RCPT_TO_VARS, tokenizer::TokenMap};
 
 pub struct Scripting {
     pub untrusted_compiler: Compiler,
+    pub trusted_compiler: Compiler,
     pub untrusted_runtime: Runtime,
     pub trusted_runtime: Runtime,
+    pub test_runtime: Runtime,
     pub from_addr: IfBlock,
     pub from_name: IfBlock,
     pub return_path: IfBlock,
     pub sign: IfBlock,
     pub trusted_scripts: AHashMap<String, Arc<Sieve>>,
+    pub trusted_scripts_access: AHashMap<String, Vec<String>>,
     pub untrusted_scripts: AHashMap<String, Arc<Sieve>>,
+    pub untrusted_max_nested_includes: usize,
+    pub test_timeout: Duration,
+    pub duplicate_retention: Duration,
 }
 
 impl Scripting {
@@ -91,13 +97,12 @@ impl Scripting {
             .register_functions(&mut fnc_map_untrusted);
 
         // Parse untrusted runtime
+        let untrusted_max_nested_includes = config
+            .property("sieve.untrusted.limits.nested-includes")
+            .unwrap_or(3);
         let untrusted_runtime = Runtime::new()
             .with_functions(&mut fnc_map_untrusted)
-            .with_max_nested_includes(
-                config
-                    .property("sieve.untrusted.limits.nested-includes")
-                    .unwrap_or(3),
-            )
+            .with_max_nested_includes(untrusted_max_nested_includes)
             .with_cpu_limit(
                 config
                     .property("sieve.untrusted.limits.cpu")
@@ -128,10 +133,11 @@ impl Scripting {
                     .property("sieve.untrusted.limits.outgoing-messages")
                     .unwrap_or(3),
             )
+            // Also the renotify interval of JMAP vacation responses, which omit :days
             .with_default_vacation_expiry(
                 config
                     .property::<Duration>("sieve.untrusted.default-expiry.vacation")
-                    .unwrap_or(Duration::from_secs(30 * 86400))
+                    .unwrap_or(Duration::from_secs(7 * 86400))
                     .as_secs(),
             )
             .with_default_duplicate_expiry(
@@ -210,6 +216,9 @@ impl Scripting {
             )
             .register_functions(&mut fnc_map_trusted);
 
+        let trusted_cpu_limit = config
+            .property_or_default("sieve.trusted.limits.cpu", "1048576")
+            .unwrap_or(1048576);
         let mut trusted_runtime = Runtime::new()
             .without_capabilities([
                 Capability::FileInto,
@@ -244,11 +253,7 @@ impl Scripting {
                     .property_or_default("sieve.trusted.limits.out-messages", "5")
                     .unwrap_or(5),
             )
-            .with_cpu_limit(
-                config
-                    .property_or_default("sieve.trusted.limits.cpu", "1048576")
-                    .unwrap_or(1048576),
-            )
+            .with_cpu_limit(trusted_cpu_limit)
             .with_max_nested_includes(
                 config
                     .property_or_default("sieve.trusted.limits.nested-includes", "5")
@@ -273,24 +278,12 @@ impl Scripting {
             .to_string();
         trusted_runtime.set_local_hostname(hostname.clone());
 
-        // Parse trusted scripts
-        let mut trusted_scripts = AHashMap::new();
-        for id in config.sub_keys("sieve.trusted.scripts", ".contents") {
-            match trusted_compiler.compile(
-                config
-                    .value(("sieve.trusted.scripts", id.as_str(), "contents"))
-                    .unwrap()
-                    .as_bytes(),
-            ) {
-                Ok(compiled) => {
-                    trusted_scripts.insert(id, compiled.into());
-                }
-                Err(err) => config.new_build_error(
-                    ("sieve.trusted.scripts", id.as_str(), "contents"),
-                    format!("Failed to compile trusted Sieve script: {err}"),
-                ),
-            }
-        }
+        // Scripts run from the management API get their own instruction budget
+        let test_runtime = trusted_runtime.clone().with_cpu_limit(
+            config
+                .property("sieve.trusted.limits.test-cpu")
+                .unwrap_or(trusted_cpu_limit),
+        );
 
         // Parse untrusted scripts
         let mut untrusted_scripts = AHashMap::new();
@@ -313,10 +306,12 @@ impl Scripting {
 
         let token_map = TokenMap::default().with_variables(SMTP_RCPT_TO_VARS);
 
-        Scripting {
+        let mut scripting = Scripting {
             untrusted_compiler,
+            trusted_compiler,
             untrusted_runtime,
             trusted_runtime,
+            test_runtime,
             from_addr: IfBlock::try_parse(config, "sieve.trusted.from-addr", &token_map)
                 .unwrap_or_else(|| {
                     IfBlock::new::<()>(
@@ -344,7 +339,50 @@ impl Scripting {
                 },
             ),
             untrusted_scripts,
-            trusted_scripts,
+            untrusted_max_nested_includes,
+            trusted_scripts: AHashMap::new(),
+            trusted_scripts_access: AHashMap::new(),
+            test_timeout: config
+                .property_or_default::<Duration>("sieve.trusted.limits.test-timeout", "30s")
+                .unwrap_or_else(|| Duration::from_secs(30)),
+            duplicate_retention: config
+                .property_or_default::<Duration>(
+                    "sieve.untrusted.limits.duplicate-retention",
+                    "30d",
+                )
+                .unwrap_or_else(|| Duration::from_secs(30 * 86400)),
+        };
+        scripting.parse_trusted_scripts(config);
+        scripting
+    }
+
+    pub fn parse_trusted_scripts(&mut self, config: &mut Config) {
+        self.trusted_scripts.clear();
+        self.trusted_scripts_access.clear();
+
+        for id in config.sub_keys("sieve.trusted.scripts", ".contents") {
+            let access = config
+                .values(("sieve.trusted.scripts", id.as_str(), "test-access"))
+                .map(|(_, name)| name.to_lowercase())
+                .collect::<Vec<_>>();
+            if !access.is_empty() {
+                self.trusted_scripts_access.insert(id.clone(), access);
+            }
+
+            match self.trusted_compiler.compile(
+                config
+                    .value(("sieve.trusted.scripts", id.as_str(), "contents"))
+                    .unwrap()
+                    .as_bytes(),
+            ) {
+                Ok(compiled) => {
+                    self.trusted_scripts.insert(id, compiled.into());
+                }
+                Err(err) => config.new_build_error(
+                    ("sieve.trusted.scripts", id.as_str(), "contents"),
+                    format!("Failed to compile trusted Sieve script: {err}"),
+                ),
+            }
         }
     }
 }
@@ -353,8 +391,10 @@ impl Default for Scripting {
     fn default() -> Self {
         Scripting {
             untrusted_compiler: Compiler::new(),
+            trusted_compiler: Compiler::new(),
             untrusted_runtime: Runtime::new(),
             trusted_runtime: Runtime::new(),
+            test_runtime: Runtime::new(),
             from_addr: IfBlock::new::<()>(
                 "sieve.trusted.from-addr",
                 [],
@@ -371,7 +411,11 @@ impl Default for Scripting {
                 ),
             ),
             untrusted_scripts: AHashMap::new(),
+            untrusted_max_nested_includes: 3,
             trusted_scripts: AHashMap::new(),
+            trusted_scripts_access: AHashMap::new(),
+            test_timeout: Duration::from_secs(30),
+            duplicate_retention: Duration::from_secs(30 * 86400),
         }
     }
 }
@@ -380,14 +424,20 @@ impl Clone for Scripting {
     fn clone(&self) -> Self {
         Self {
             untrusted_compiler: self.untrusted_compiler.clone(),
+            trusted_compiler: self.trusted_compiler.clone(),
             untrusted_runtime: self.untrusted_runtime.clone(),
             trusted_runtime: self.trusted_runtime.clone(),
+            test_runtime: self.test_runtime.clone(),
             from_addr: self.from_addr.clone(),
             from_name: self.from_name.clone(),
             return_path: self.return_path.clone(),
             sign: self.sign.clone(),
             trusted_scripts: self.trusted_scripts.clone(),
+            trusted_scripts_access: self.trusted_scripts_access.clone(),
             untrusted_scripts: self.untrusted_scripts.clone(),
+            untrusted_max_nested_includes: self.untrusted_max_nested_includes,
+            test_timeout: self.test_timeout,
+            duplicate_retention: self.duplicate_retention,
         }
     }
 }
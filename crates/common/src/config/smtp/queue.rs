@@ -7,6 +7,7 @@
 use self::throttle::parse_queue_rate_limiter;
 use super::*;
 use crate::{
+    Server,
     config::server::ServerProtocol,
     expr::{if_block::IfBlock, *},
 };
@@ -15,8 +16,10 @@ use mail_auth::IpLookupStrategy;
 use mail_send::Credentials;
 use std::{
     fmt::Display,
+    future::Future,
     hash::{Hash, Hasher},
     net::IpAddr,
+    pin::Pin,
     time::Duration,
 };
 use throttle::parse_queue_rate_limiter_key;
@@ -62,6 +65,20 @@ pub struct QueueConfig {
     pub routing_strategy: AHashMap<String, RoutingStrategy>,
     pub tls_strategy: AHashMap<String, TlsStrategy>,
     pub virtual_queues: AHashMap<QueueName, VirtualQueue>,
+    pub domain_retry: Vec<DomainRetrySchedule>,
+
+    // Fallback route used after repeated temporary failures
+    pub fallback_route: Option<String>,
+    pub fallback_attempts: u32,
+
+    // Limits
+    pub max_in_memory: Option<usize>,
+    pub max_per_tenant: Option<usize>,
+    pub max_idle: Duration,
+    pub remote_size_ttl: Duration,
+
+    // Shutdown
+    pub drain_timeout: Option<Duration>,
 }
 
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
@@ -85,11 +102,49 @@ pub struct Dsn {
     pub sign: IfBlock,
 }
 
+#[derive(Debug)]
+pub struct DsnText {
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsnStatus {
+    Delivered,
+    Delayed,
+    Failed,
+}
+
+#[derive(Debug)]
+pub struct DsnRecipient<'x> {
+    pub address: &'x str,
+    pub status: DsnStatus,
+    pub details: String,
+}
+
+/// Builds the subject and human readable part of a DSN sent to `return_path`
+/// for the recipients included in it, `details` holds the status line the
+/// default text uses for each recipient. Builders are installed on `Data::dsn_text`.
+pub trait DsnTextBuilder: Sync + Send {
+    fn build<'x>(
+        &'x self,
+        server: &'x Server,
+        return_path: &'x str,
+        recipients: &'x [DsnRecipient<'x>],
+    ) -> Pin<Box<dyn Future<Output = DsnText> + Send + 'x>>;
+}
+
 #[derive(Clone, Debug)]
 pub struct VirtualQueue {
     pub threads: usize,
 }
 
+#[derive(Clone, Debug)]
+pub struct DomainRetrySchedule {
+    pub domains: Vec<String>,
+    pub retry: Vec<u64>,
+}
+
 #[derive(Clone, Debug)]
 pub struct QueueStrategy {
     pub retry: Vec<u64>,
@@ -239,9 +294,17 @@ impl Default for QueueConfig {
             quota: QueueQuotas::default(),
             queue_strategy: Default::default(),
             virtual_queues: Default::default(),
+            domain_retry: Default::default(),
             connection_strategy: Default::default(),
             routing_strategy: Default::default(),
             tls_strategy: Default::default(),
+            max_in_memory: None,
+            max_per_tenant: None,
+            max_idle: Duration::from_secs(5 * 60),
+            remote_size_ttl: Duration::from_secs(24 * 60 * 60),
+            fallback_route: None,
+            fallback_attempts: 3,
+            drain_timeout: None,
         }
     }
 }
@@ -278,16 +341,94 @@ impl QueueConfig {
         // Parse strategies
         queue.virtual_queues = parse_virtual_queues(config);
         queue.queue_strategy = parse_queue_strategies(config, &queue.virtual_queues);
+        queue.domain_retry = parse_domain_retry_schedules(config);
         queue.connection_strategy = parse_connection_strategies(config);
         queue.routing_strategy = parse_routing_strategies(config);
         queue.tls_strategy = parse_tls_strategies(config);
+        queue.fallback_route = config
+            .value("queue.fallback.route")
+            .filter(|route| !route.is_empty())
+            .map(|route| route.to_string());
+        if let Some(attempts) = config.property::<u32>("queue.fallback.attempts") {
+            queue.fallback_attempts = std::cmp::max(attempts, 1);
+        }
 
         // Parse rate limiters
         queue.inbound_limiters = parse_inbound_rate_limiters(config);
         queue.outbound_limiters = parse_outbound_rate_limiters(config);
         queue.quota = parse_queue_quota(config);
+
+        // Parse limits
+        if let Some(max_in_memory) = config.property::<usize>("queue.limits.max-in-memory") {
+            queue.max_in_memory = Some(std::cmp::max(max_in_memory, 1));
+        }
+        queue.max_per_tenant = config
+            .property::<usize>("queue.limits.max-per-tenant")
+            .filter(|max| *max > 0);
+        if let Some(max_idle) = config.property::<Duration>("queue.limits.max-idle") {
+            queue.max_idle = std::cmp::max(max_idle, Duration::from_secs(1));
+        }
+        if let Some(remote_size_ttl) = config.property::<Duration>("queue.limits.remote-size-ttl") {
+            queue.remote_size_ttl = remote_size_ttl;
+        }
+
+        // Parse shutdown settings
+        queue.drain_timeout = config.property::<Duration>("queue.shutdown.drain-timeout");
         queue
     }
+
+    /// Returns the retry schedule of the most specific entry matching the domain,
+    /// exact names take precedence over the longest matching `*.` wildcard.
+    pub fn domain_retry_schedule(&self, domain: &str) -> Option<&[u64]> {
+        let mut best_match: Option<(usize, &[u64])> = None;
+        for schedule in &self.domain_retry {
+            for pattern in &schedule.domains {
+                let specificity = if pattern.eq_ignore_ascii_case(domain) {
+                    usize::MAX
+                } else if let Some(suffix) = pattern.strip_prefix("*.")
+                    && domain.len() > suffix.len()
+                    && domain[domain.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+                    && domain.as_bytes()[domain.len() - suffix.len() - 1] == b'.'
+                {
+                    suffix.len()
+                } else {
+                    continue;
+                };
+
+                if best_match.is_none_or(|(best, _)| specificity > best) {
+                    best_match = Some((specificity, &schedule.retry));
+                }
+            }
+        }
+
+        best_match.map(|(_, retry)| retry)
+    }
+}
+
+fn parse_domain_retry_schedules(config: &mut Config) -> Vec<DomainRetrySchedule> {
+    let mut entries = Vec::new();
+    for id in config.sub_keys("queue.domain-schedule", "") {
+        let domains = config
+            .values(("queue.domain-schedule", id.as_str(), "domains"))
+            .map(|(_, domain)| domain.trim().to_lowercase())
+            .filter(|domain| !domain.is_empty())
+            .collect::<Vec<_>>();
+        let retry = config
+            .properties::<Duration>(("queue.domain-schedule", id.as_str(), "retry"))
+            .into_iter()
+            .map(|(_, d)| d.as_secs())
+            .collect::<Vec<_>>();
+
+        if domains.is_empty() {
+            config.new_parse_error(
+                ("queue.domain-schedule", id.as_str(), "domains"),
+                "At least one domain must be specified.".to_string(),
+            );
+        } else if !retry.is_empty() {
+            entries.push(DomainRetrySchedule { domains, retry });
+        }
+    }
+    entries
 }
 
 fn parse_queue_strategies(
@@ -4,18 +4,28 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use super::{Variable, functions::ResolveVariable, if_block::IfBlock, tokenizer::TokenMap};
-use ahash::AHashSet;
+use super::{
+    Variable,
+    functions::ResolveVariable,
+    if_block::IfBlock,
+    parser::ExpressionParser,
+    tokenizer::{TokenMap, Tokenizer},
+};
+use ahash::{AHashMap, AHashSet};
 use mail_auth::common::resolver::ToReverseName;
 use nlp::classifier::model::{CcfhClassifier, FhClassifier};
+use parking_lot::Mutex;
 use std::{
+    collections::VecDeque,
+    future::Future,
     net::{IpAddr, SocketAddr},
-    time::Duration,
+    pin::Pin,
+    time::{Duration, Instant},
 };
 use tokio::net::lookup_host;
 use utils::{
     cache::CacheItemWeight,
-    config::{Config, utils::ParseValue},
+    config::{Config, ipmask::IpAddrMask, utils::ParseValue},
     glob::GlobMap,
 };
 
@@ -61,6 +71,10 @@ pub struct DnsBlConfig {
     pub max_domain_checks: usize,
     pub max_email_checks: usize,
     pub max_url_checks: usize,
+    pub timeout: Duration,
+    pub min_ttl: Duration,
+    pub max_ttl: Duration,
+    pub error_ttl: Duration,
     pub servers: Vec<DnsBlServer>,
 }
 
@@ -167,6 +181,63 @@ pub struct DnsBlServer {
     pub zone: IfBlock,
     pub scope: Element,
     pub tags: IfBlock,
+    pub score: Option<IfBlock>,
+    pub result_mask: Vec<IpAddrMask>,
+    pub lookup_reason: bool,
+    pub grace_period: Option<DnsBlGracePeriod>,
+    pub negative_ttl: Duration,
+    pub terminal: Option<DnsBlTerminal>,
+}
+
+// Outcome of a terminal DNSBL hit, no further checks are performed after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DnsBlTerminal {
+    Allow,
+    Block,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsBlGracePeriod {
+    pub min_age: u64,
+    pub min_checks: u32,
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DnsBlListing {
+    pub first_seen: u64,
+    pub checks: u32,
+}
+
+pub const DNSBL_METRICS_BUCKET: u64 = 3600;
+pub const DNSBL_METRICS_MAX_BUCKETS: usize = 24;
+pub const DNSBL_LATENCY_BOUNDS: [u64; 9] = [10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+/// Query counters for each blocklist, kept in hourly buckets for the last day.
+#[derive(Debug, Default)]
+pub struct DnsBlMetrics {
+    servers: Mutex<AHashMap<String, VecDeque<DnsBlBucket>>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsBlBucket {
+    pub start: u64,
+    pub queries: u64,
+    pub hits: u64,
+    pub nxdomain: u64,
+    pub errors: u64,
+    pub elapsed_total: u64,
+    pub elapsed_max: u64,
+    // Query counts by latency, the last slot holds queries above the highest bound
+    pub latency: [u64; DNSBL_LATENCY_BOUNDS.len() + 1],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DnsBlOutcome {
+    Hit,
+    NxDomain,
+    Error,
 }
 
 impl SpamFilterConfig {
@@ -280,9 +351,25 @@ impl DnsBlConfig {
             max_url_checks: config
                 .property_or_default("spam-filter.dnsbl.max-check.url", "50")
                 .unwrap_or(20),
+            timeout: config
+                .property_or_default("spam-filter.dnsbl.timeout", "10s")
+                .unwrap_or(Duration::from_secs(10)),
+            min_ttl: config
+                .property_or_default("spam-filter.dnsbl.ttl.min", "1m")
+                .unwrap_or(Duration::from_secs(60)),
+            max_ttl: config
+                .property_or_default("spam-filter.dnsbl.ttl.max", "1d")
+                .unwrap_or(Duration::from_secs(86400)),
+            error_ttl: config
+                .property_or_default("spam-filter.dnsbl.ttl.error", "1m")
+                .unwrap_or(Duration::from_secs(60)),
             servers,
         }
     }
+
+    pub fn clamp_ttl(&self, ttl: Duration) -> Duration {
+        ttl.max(self.min_ttl).min(self.max_ttl)
+    }
 }
 
 impl DnsBlServer {
@@ -299,22 +386,237 @@ impl DnsBlServer {
         let scope =
             config.property_require::<Element>(("spam-filter.dnsbl.server", id_, "scope"))?;
 
-        DnsBlServer {
-            zone: IfBlock::try_parse(
+        let zone = if let Some(suffix) = config
+            .value(("spam-filter.dnsbl.server", id_, "suffix"))
+            .filter(|_| !config.contains_key(("spam-filter.dnsbl.server", id_, "zone")))
+            .map(|suffix| suffix.to_string())
+        {
+            DnsBlServer::parse_suffix(config, id_, scope, suffix)?
+        } else {
+            IfBlock::try_parse(
                 config,
                 ("spam-filter.dnsbl.server", id_, "zone"),
                 &scope.token_map(),
-            )?,
+            )?
+        };
+
+        DnsBlServer {
+            zone,
             scope,
             tags: IfBlock::try_parse(
                 config,
                 ("spam-filter.dnsbl.server", id_, "tag"),
-                &Element::Ip.token_map(),
+                &Element::Ip
+                    .token_map()
+                    .with_variables_map([("reason", V_DNSBL_REASON)]),
             )?,
+            score: IfBlock::try_parse(
+                config,
+                ("spam-filter.dnsbl.server", id_, "score"),
+                &Element::Ip
+                    .token_map()
+                    .with_variables_map([("reason", V_DNSBL_REASON)]),
+            ),
+            result_mask: config
+                .properties::<IpAddrMask>(("spam-filter.dnsbl.server", id_, "result-mask"))
+                .into_iter()
+                .map(|(_, mask)| mask)
+                .collect(),
+            lookup_reason: config
+                .property_or_default(("spam-filter.dnsbl.server", id_, "reason"), "false")
+                .unwrap_or(false),
+            grace_period: DnsBlGracePeriod::parse(config, id_),
+            negative_ttl: config
+                .property_or_default(("spam-filter.dnsbl.server", id_, "negative-ttl"), "1d")
+                .unwrap_or(Duration::from_secs(86400)),
+            terminal: config.property(("spam-filter.dnsbl.server", id_, "terminal")),
             id,
         }
         .into()
     }
+
+    /// Returns whether a return code falls within the configured result masks,
+    /// any return code is accepted when no masks are configured.
+    pub fn accepts_result(&self, result: &IpResolver) -> bool {
+        self.result_mask.is_empty() || self.result_mask.iter().any(|mask| mask.matches(&result.ip))
+    }
+
+    fn parse_suffix(
+        config: &mut Config,
+        id: &str,
+        scope: Element,
+        suffix: String,
+    ) -> Option<IfBlock> {
+        let key = format!("spam-filter.dnsbl.server.{id}.suffix");
+        let suffix = suffix.trim().trim_matches('.').to_ascii_lowercase();
+        if suffix.is_empty()
+            || !suffix
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-' | '_'))
+        {
+            config.new_parse_error(key, format!("Invalid DNSBL zone suffix {suffix:?}"));
+            return None;
+        }
+
+        // IP lists are queried by reversed octets, RHSBLs by the domain name
+        let query = match scope {
+            Element::Ip => "ip_reverse",
+            Element::Domain => "value",
+            Element::Email => "domain",
+            Element::Url => "sld",
+            Element::Header | Element::Body | Element::Any => {
+                config.new_parse_error(key, "Zone suffixes are not supported for this scope");
+                return None;
+            }
+        };
+
+        match ExpressionParser::new(Tokenizer::new(
+            &format!("{query} + '.{suffix}'"),
+            &scope.token_map(),
+        ))
+        .parse()
+        {
+            Ok(default) => Some(IfBlock {
+                key,
+                if_then: Default::default(),
+                default,
+            }),
+            Err(err) => {
+                config.new_parse_error(key, err);
+                None
+            }
+        }
+    }
+}
+
+impl DnsBlGracePeriod {
+    pub fn parse(config: &mut Config, id: &str) -> Option<Self> {
+        let min_age = config
+            .property::<Duration>(("spam-filter.dnsbl.server", id, "grace-period.duration"))
+            .map(|d| d.as_secs());
+        let min_checks =
+            config.property::<u32>(("spam-filter.dnsbl.server", id, "grace-period.min-checks"));
+
+        if min_age.is_some() || min_checks.is_some() {
+            Some(DnsBlGracePeriod {
+                min_age: min_age.unwrap_or_default(),
+                min_checks: min_checks.unwrap_or_default(),
+                tag: config
+                    .value(("spam-filter.dnsbl.server", id, "grace-period.tag"))
+                    .map(|tag| tag.to_string()),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn is_established(&self, listing: &DnsBlListing, now: u64) -> bool {
+        listing.checks >= self.min_checks && now.saturating_sub(listing.first_seen) >= self.min_age
+    }
+}
+
+impl DnsBlListing {
+    pub fn new(now: u64) -> Self {
+        DnsBlListing {
+            first_seen: now,
+            checks: 0,
+        }
+    }
+}
+
+impl DnsBlMetrics {
+    pub fn record(&self, id: &str, outcome: DnsBlOutcome, elapsed: Duration, now: u64) {
+        let start = now - (now % DNSBL_METRICS_BUCKET);
+        let mut servers = self.servers.lock();
+        let buckets = servers
+            .entry(id.to_string())
+            .or_insert_with(|| VecDeque::with_capacity(DNSBL_METRICS_MAX_BUCKETS));
+        if buckets.back().is_none_or(|bucket| bucket.start != start) {
+            if buckets.len() == DNSBL_METRICS_MAX_BUCKETS {
+                buckets.pop_front();
+            }
+            buckets.push_back(DnsBlBucket {
+                start,
+                ..Default::default()
+            });
+        }
+        buckets.back_mut().unwrap().record(outcome, elapsed);
+    }
+
+    /// Returns the buckets of each blocklist that started at or after `since`.
+    pub fn snapshot(&self, since: u64) -> AHashMap<String, Vec<DnsBlBucket>> {
+        self.servers
+            .lock()
+            .iter()
+            .map(|(id, buckets)| {
+                (
+                    id.clone(),
+                    buckets
+                        .iter()
+                        .filter(|bucket| bucket.start + DNSBL_METRICS_BUCKET > since)
+                        .copied()
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+impl DnsBlBucket {
+    pub fn record(&mut self, outcome: DnsBlOutcome, elapsed: Duration) {
+        let elapsed = elapsed.as_millis() as u64;
+        self.queries += 1;
+        match outcome {
+            DnsBlOutcome::Hit => self.hits += 1,
+            DnsBlOutcome::NxDomain => self.nxdomain += 1,
+            DnsBlOutcome::Error => self.errors += 1,
+        }
+        self.elapsed_total += elapsed;
+        self.elapsed_max = self.elapsed_max.max(elapsed);
+        self.latency[DNSBL_LATENCY_BOUNDS.partition_point(|bound| *bound < elapsed)] += 1;
+    }
+
+    pub fn merge(&mut self, other: &DnsBlBucket) {
+        self.start = if self.queries == 0 {
+            other.start
+        } else {
+            self.start.min(other.start)
+        };
+        self.queries += other.queries;
+        self.hits += other.hits;
+        self.nxdomain += other.nxdomain;
+        self.errors += other.errors;
+        self.elapsed_total += other.elapsed_total;
+        self.elapsed_max = self.elapsed_max.max(other.elapsed_max);
+        for (total, count) in self.latency.iter_mut().zip(other.latency) {
+            *total += count;
+        }
+    }
+
+    /// Returns the upper latency bound, in milliseconds, below which the given
+    /// fraction of the queries completed.
+    pub fn latency_percentile(&self, percentile: f64) -> Option<u64> {
+        let target = (self.queries as f64 * percentile).ceil() as u64;
+        let mut count = 0;
+        for (idx, slot) in self.latency.iter().enumerate() {
+            count += slot;
+            if count >= target && count > 0 {
+                return Some(
+                    DNSBL_LATENCY_BOUNDS
+                        .get(idx)
+                        .copied()
+                        .unwrap_or(self.elapsed_max),
+                );
+            }
+        }
+        None
+    }
+}
+
+impl CacheItemWeight for DnsBlListing {
+    fn weight(&self) -> u64 {
+        std::mem::size_of::<DnsBlListing>() as u64
+    }
 }
 
 impl SpamFilterLists {
@@ -572,6 +874,16 @@ impl ParseValue for Element {
     }
 }
 
+impl ParseValue for DnsBlTerminal {
+    fn parse_value(value: &str) -> utils::config::Result<Self> {
+        match value {
+            "allow" => Ok(DnsBlTerminal::Allow),
+            "block" => Ok(DnsBlTerminal::Block),
+            other => Err(format!("Invalid terminal action {other:?}.",)),
+        }
+    }
+}
+
 impl Location {
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -665,6 +977,7 @@ pub const V_IP_REVERSE: u32 = 1;
 pub const V_IP_OCTETS: u32 = 2;
 pub const V_IP_IS_V4: u32 = 3;
 pub const V_IP_IS_V6: u32 = 4;
+pub const V_DNSBL_REASON: u32 = 5;
 
 impl Element {
     pub fn token_map(&self) -> TokenMap {
@@ -819,6 +1132,64 @@ impl IpResolver {
     }
 }
 
+pub struct DnsBlResult {
+    pub ips: Vec<IpResolver>,
+    pub reason: Option<String>,
+}
+
+pub struct DnsBlResolver<'x> {
+    pub ip: &'x IpResolver,
+    pub reason: Option<&'x str>,
+}
+
+pub enum DnsBlLookup {
+    Listed { ips: Vec<IpAddr>, expires: Instant },
+    NotListed { negative_ttl: Option<Duration> },
+}
+
+/// Answers DNSBL queries instead of the configured DNS resolver, used to serve
+/// deterministic responses in tests.
+pub trait DnsBlZoneLookup: Sync + Send {
+    fn lookup_zone<'x>(
+        &'x self,
+        zone: &'x str,
+    ) -> Pin<Box<dyn Future<Output = mail_auth::Result<DnsBlLookup>> + Send + 'x>>;
+
+    fn lookup_txt<'x>(
+        &'x self,
+        zone: &'x str,
+    ) -> Pin<Box<dyn Future<Output = mail_auth::Result<String>> + Send + 'x>>;
+}
+
+impl DnsBlResult {
+    pub fn resolvers(&self) -> impl Iterator<Item = DnsBlResolver<'_>> {
+        self.ips.iter().map(|ip| DnsBlResolver {
+            ip,
+            reason: self.reason.as_deref(),
+        })
+    }
+}
+
+impl ResolveVariable for DnsBlResolver<'_> {
+    fn resolve_variable(&self, variable: u32) -> Variable<'_> {
+        match variable {
+            V_DNSBL_REASON => self.reason.unwrap_or_default().into(),
+            _ => self.ip.resolve_variable(variable),
+        }
+    }
+
+    fn resolve_global(&self, _: &str) -> Variable<'_> {
+        Variable::Integer(0)
+    }
+}
+
+impl CacheItemWeight for DnsBlResult {
+    fn weight(&self) -> u64 {
+        self.ips.iter().map(|ip| ip.weight()).sum::<u64>()
+            + self.reason.as_ref().map_or(0, |r| r.len() as u64)
+    }
+}
+
 impl CacheItemWeight for IpResolver {
     fn weight(&self) -> u64 {
         (std::mem::size_of::<IpResolver>() + self.ip_string.len() + self.reverse.len()) as u64
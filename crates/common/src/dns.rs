@@ -95,4 +95,16 @@ impl Server {
             Err(err) => Err(err.into()),
         }
     }
+
+    pub fn psl_domain<'x>(&self, domain: &'x str) -> Option<&'x str> {
+        self.inner.data.public_suffix.load().domain_str(domain)
+    }
+
+    pub fn psl_listed_domain<'x>(&self, domain: &'x str) -> Option<&'x str> {
+        self.inner
+            .data
+            .public_suffix
+            .load()
+            .listed_domain_str(domain)
+    }
 }
@@ -20,6 +20,7 @@ use utils::{
         cron::SimpleCron,
         utils::{AsKey, ParseValue},
     },
+    suffixlist::PublicSuffix,
     template::Template,
 };
 
@@ -44,15 +45,23 @@ impl Enterprise {
             .value("server.hostname")
             .or_else(|| config.value("lookup.default.hostname"))?;
         let mut update_license = None;
+        let license_key = config.value("enterprise.license-key");
+        let api_key = config.value("enterprise.api-key");
+        if license_key.is_none() && api_key.is_none() {
+            return None;
+        }
 
-        let license_result = match (
-            config.value("enterprise.license-key"),
-            config.value("enterprise.api-key"),
-        ) {
+        // Data, which holds the reloadable public suffix list, is built after
+        // the core, so license domains are checked against the bundled list
+        let public_suffix = PublicSuffix::embedded();
+        let license_result = match (license_key, api_key) {
             (Some(license_key), maybe_api_key) => {
-                match (LicenseKey::new(license_key, server_hostname), maybe_api_key) {
+                match (
+                    LicenseKey::new(license_key, server_hostname, &public_suffix),
+                    maybe_api_key,
+                ) {
                     (Ok(license), Some(api_key)) if license.is_near_expiration() => Ok(license
-                        .try_renew(api_key)
+                        .try_renew(api_key, &public_suffix)
                         .await
                         .map(|result| {
                             update_license = Some(result.encoded_key);
@@ -60,8 +69,8 @@ impl Enterprise {
                         })
                         .unwrap_or(license)),
                     (Ok(license), None) => Ok(license),
-                    (Err(_), Some(api_key)) => LicenseKey::invalid(server_hostname)
-                        .try_renew(api_key)
+                    (Err(_), Some(api_key)) => LicenseKey::invalid(server_hostname, &public_suffix)
+                        .try_renew(api_key, &public_suffix)
                         .await
                         .map(|result| {
                             update_license = Some(result.encoded_key);
@@ -70,8 +79,8 @@ impl Enterprise {
                     (maybe_license, _) => maybe_license,
                 }
             }
-            (None, Some(api_key)) => LicenseKey::invalid(server_hostname)
-                .try_renew(api_key)
+            (None, Some(api_key)) => LicenseKey::invalid(server_hostname, &public_suffix)
+                .try_renew(api_key, &public_suffix)
                 .await
                 .map(|result| {
                     update_license = Some(result.encoded_key);
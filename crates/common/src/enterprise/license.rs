@@ -30,6 +30,7 @@ use ring::signature::{ED25519, UnparsedPublicKey};
 use base64::{Engine, engine::general_purpose::STANDARD};
 use store::write::now;
 use trc::ServerEvent;
+use utils::suffixlist::PublicSuffix;
 
 use crate::manager::fetch_resource;
 
@@ -157,12 +158,13 @@ impl LicenseKey {
     pub fn new(
         license_key: impl AsRef<str>,
         hostname: impl AsRef<str>,
+        public_suffix: &PublicSuffix,
     ) -> Result<Self, LicenseError> {
         LicenseValidator::new()
             .try_parse(license_key)
             .and_then(|key| {
-                let local_domain = Self::base_domain(hostname)?;
-                let license_domain = Self::base_domain(&key.domain)?;
+                let local_domain = Self::base_domain(hostname, public_suffix)?;
+                let license_domain = Self::base_domain(&key.domain, public_suffix)?;
                 if local_domain == license_domain {
                     Ok(key)
                 } else {
@@ -174,16 +176,20 @@ impl LicenseKey {
             })
     }
 
-    pub fn invalid(domain: impl AsRef<str>) -> Self {
+    pub fn invalid(domain: impl AsRef<str>, public_suffix: &PublicSuffix) -> Self {
         LicenseKey {
             valid_from: 0,
             valid_to: 0,
-            domain: Self::base_domain(domain).unwrap_or_default(),
+            domain: Self::base_domain(domain, public_suffix).unwrap_or_default(),
             accounts: 0,
         }
     }
 
-    pub async fn try_renew(&self, api_key: &str) -> Result<RenewedLicense, LicenseError> {
+    pub async fn try_renew(
+        &self,
+        api_key: &str,
+        public_suffix: &PublicSuffix,
+    ) -> Result<RenewedLicense, LicenseError> {
         let mut headers = HeaderMap::new();
         headers.insert(
             AUTHORIZATION,
@@ -208,7 +214,7 @@ impl LicenseKey {
             String::from_utf8(bytes)
                 .map_err(|_| String::from("Failed to UTF-8 decode server response"))
         }) {
-            Ok(encoded_key) => match LicenseKey::new(&encoded_key, &self.domain) {
+            Ok(encoded_key) => match LicenseKey::new(&encoded_key, &self.domain, public_suffix) {
                 Ok(key) => Ok(RenewedLicense { key, encoded_key }),
                 Err(err) => {
                     trc::event!(
@@ -248,9 +254,13 @@ impl LicenseKey {
         now >= self.valid_to || now < self.valid_from
     }
 
-    pub fn base_domain(domain: impl AsRef<str>) -> Result<String, LicenseError> {
+    pub fn base_domain(
+        domain: impl AsRef<str>,
+        public_suffix: &PublicSuffix,
+    ) -> Result<String, LicenseError> {
         let domain = domain.as_ref();
-        psl::domain_str(domain)
+        public_suffix
+            .domain_str(domain)
             .map(|d| d.to_string())
             .ok_or(LicenseError::InvalidDomain {
                 domain: domain.to_string(),
@@ -154,7 +154,7 @@ impl Server {
         const MAX_IMAGE_SIZE: usize = 1024 * 1024;
 
         if self.is_enterprise_edition() {
-            let domain = psl::domain_str(domain).unwrap_or(domain);
+            let domain = self.psl_domain(domain).unwrap_or(domain);
             let logo = { self.inner.data.logos.lock().get(domain).cloned() };
 
             if let Some(logo) = logo {
@@ -553,6 +553,15 @@ impl<'x> Variable<'x> {
         }
     }
 
+    pub fn to_float(&self) -> Option<f64> {
+        match self {
+            Variable::Integer(n) => Some(*n as f64),
+            Variable::Float(n) => Some(*n),
+            Variable::String(s) if !s.is_empty() => s.as_str().parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+
     pub fn to_usize(&self) -> Option<usize> {
         match self {
             Variable::Integer(n) => Some(*n as usize),
@@ -738,6 +747,14 @@ impl<'x> TryFrom<Variable<'x>> for u64 {
     }
 }
 
+impl<'x> TryFrom<Variable<'x>> for f64 {
+    type Error = ();
+
+    fn try_from(value: Variable<'x>) -> Result<Self, Self::Error> {
+        value.to_float().ok_or(())
+    }
+}
+
 impl<'x> TryFrom<Variable<'x>> for usize {
     type Error = ();
 
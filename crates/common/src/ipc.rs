@@ -4,10 +4,13 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use crate::config::smtp::{
-    queue::QueueName,
-    report::AggregateFrequency,
-    resolver::{Policy, Tlsa},
+use crate::{
+    Server,
+    config::smtp::{
+        queue::QueueName,
+        report::AggregateFrequency,
+        resolver::{Policy, Tlsa},
+    },
 };
 use ahash::RandomState;
 use mail_auth::{
@@ -23,7 +26,7 @@ use std::{
     time::Instant,
 };
 use store::{BlobStore, InMemoryStore, Store};
-use tokio::sync::{Semaphore, SemaphorePermit, mpsc};
+use tokio::sync::{Semaphore, SemaphorePermit, broadcast, mpsc, oneshot};
 use types::type_state::{DataType, StateChange};
 use utils::map::bitmap::Bitmap;
 
@@ -107,6 +110,8 @@ pub enum BroadcastEvent {
     ReloadSettings,
     ReloadBlockedIps,
     ReloadSpamFilter,
+    ReloadPublicSuffix,
+    ReloadSieveScripts,
 }
 
 #[derive(Debug)]
@@ -119,6 +124,19 @@ pub enum QueueEvent {
     },
     Paused(bool),
     ReloadSettings,
+    FlushOnHold {
+        filter: Option<String>,
+    },
+    HoldUntil {
+        queue_id: u64,
+        until: u64,
+    },
+    Retry(u64),
+    LimiterStats(oneshot::Sender<Vec<QueueLimiterStats>>),
+    Drain {
+        deadline: Instant,
+        tx: oneshot::Sender<()>,
+    },
     Stop,
 }
 
@@ -129,6 +147,23 @@ pub enum QueueEventStatus {
     Deferred,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueLimiterStats {
+    pub queue_name: String,
+    pub in_flight: usize,
+    pub max_in_flight: usize,
+}
+
+/// Queue state transitions published to external subscribers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueStateEvent {
+    Queued { queue_id: u64 },
+    Delivered { queue_id: u64, recipient: String },
+    Deferred { queue_id: u64, next_retry: u64 },
+    Bounced { queue_id: u64, recipient: String },
+}
+
 #[derive(Debug)]
 pub enum ReportingEvent {
     Dmarc(Box<DmarcEvent>),
@@ -196,6 +231,23 @@ impl TrainTaskController {
     }
 }
 
+impl Server {
+    pub fn notify_queue_state(&self, event: QueueStateEvent) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.inner.ipc.queue_state_tx.send(event);
+    }
+
+    pub fn notify_queue_states(&self, events: impl IntoIterator<Item = QueueStateEvent>) {
+        for event in events {
+            self.notify_queue_state(event);
+        }
+    }
+
+    pub fn subscribe_queue_state(&self) -> broadcast::Receiver<QueueStateEvent> {
+        self.inner.ipc.queue_state_tx.subscribe()
+    }
+}
+
 pub trait ToHash {
     fn to_hash(&self) -> u64;
 }
@@ -7,7 +7,7 @@
 #![warn(clippy::large_futures)]
 
 use ahash::{AHashMap, AHashSet};
-use arc_swap::ArcSwap;
+use arc_swap::{ArcSwap, ArcSwapOption};
 use auth::{AccessToken, oauth::config::OAuthConfig, roles::RolePermissions};
 use calcard::common::timezone::Tz;
 use config::{
@@ -18,13 +18,16 @@ use config::{
     scripts::Scripting,
     smtp::{
         SmtpConfig,
+        queue::DsnTextBuilder,
         resolver::{Policy, Tlsa},
     },
-    spamfilter::{IpResolver, SpamFilterConfig},
+    spamfilter::{DnsBlListing, DnsBlMetrics, DnsBlResult, DnsBlZoneLookup, SpamFilterConfig},
     storage::Storage,
     telemetry::Metrics,
 };
-use ipc::{BroadcastEvent, HousekeeperEvent, PushEvent, QueueEvent, ReportingEvent};
+use ipc::{
+    BroadcastEvent, HousekeeperEvent, PushEvent, QueueEvent, QueueStateEvent, ReportingEvent,
+};
 use listener::{asn::AsnGeoLookupData, blocked::Security, tls::AcmeProviders};
 use mail_auth::{MX, Txt};
 use manager::webadmin::{Resource, WebAdminManager};
@@ -33,17 +36,21 @@ use rustls::sign::CertifiedKey;
 use std::{
     hash::{BuildHasher, Hash, Hasher},
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
-    sync::{Arc, atomic::AtomicBool},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize},
+    },
     time::{Duration, Instant},
 };
 use store::rand::{Rng, distr::Alphanumeric};
 use tinyvec::TinyVec;
-use tokio::sync::{Notify, Semaphore, mpsc};
+use tokio::sync::{Notify, Semaphore, broadcast, mpsc};
 use tokio_rustls::TlsConnector;
 use types::{acl::AclGrant, special_use::SpecialUse};
 use utils::{
     cache::{Cache, CacheItemWeight, CacheWithTtl},
     snowflake::SnowflakeIdGenerator,
+    suffixlist::PublicSuffix,
 };
 
 pub mod addresses;
@@ -70,8 +77,6 @@ pub mod enterprise;
 
 // SPDX-SnippetEnd
 
-pub use psl;
-
 use crate::{config::spamfilter::SpamClassifier, ipc::TrainTaskController};
 
 pub static VERSION_PRIVATE: &str = env!("CARGO_PKG_VERSION");
@@ -93,7 +98,7 @@ Schema history:
 
 */
 
-pub const DATABASE_SCHEMA_VERSION: u32 = 5;
+pub const DATABASE_SCHEMA_VERSION: u32 = 6;
 
 pub const LONG_1D_SLUMBER: Duration = Duration::from_secs(60 * 60 * 24);
 pub const LONG_1Y_SLUMBER: Duration = Duration::from_secs(60 * 60 * 24 * 365);
@@ -140,6 +145,10 @@ pub struct Data {
     pub tls_self_signed_cert: Option<Arc<CertifiedKey>>,
 
     pub blocked_ips: RwLock<AHashSet<IpAddr>>,
+    pub public_suffix: ArcSwap<PublicSuffix>,
+    pub dnsbl_metrics: DnsBlMetrics,
+    pub dnsbl_lookup: Option<Arc<dyn DnsBlZoneLookup>>,
+    pub dsn_text: ArcSwapOption<Arc<dyn DsnTextBuilder>>,
 
     pub asn_geo_data: AsnGeoLookupData,
 
@@ -147,6 +156,7 @@ pub struct Data {
     pub queue_id_gen: SnowflakeIdGenerator,
     pub span_id_gen: SnowflakeIdGenerator,
     pub queue_status: AtomicBool,
+    pub queue_health: QueueHealth,
 
     pub webadmin: WebAdminManager,
     pub logos: Mutex<AHashMap<String, Option<Resource<Vec<u8>>>>>,
@@ -154,6 +164,15 @@ pub struct Data {
     pub smtp_connectors: TlsConnectors,
 }
 
+#[derive(Debug, Default)]
+pub struct QueueHealth {
+    pub next_refresh: AtomicU64,
+    pub last_processed: AtomicU64,
+    pub due_messages: AtomicUsize,
+    pub on_hold: AtomicUsize,
+    pub locked: AtomicUsize,
+}
+
 pub struct Caches {
     pub access_tokens: Cache<u32, Arc<AccessToken>>,
     pub http_auth: Cache<String, HttpAuthCache>,
@@ -172,7 +191,10 @@ pub struct Caches {
     pub dns_ipv6: CacheWithTtl<String, Arc<Vec<Ipv6Addr>>>,
     pub dns_tlsa: CacheWithTtl<String, Arc<Tlsa>>,
     pub dbs_mta_sts: CacheWithTtl<String, Arc<Policy>>,
-    pub dns_rbl: CacheWithTtl<String, Option<Arc<IpResolver>>>,
+    pub dns_rbl: CacheWithTtl<String, Option<Arc<DnsBlResult>>>,
+    pub dns_rbl_listings: Cache<String, DnsBlListing>,
+
+    pub remote_max_size: CacheWithTtl<String, u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -247,6 +269,7 @@ pub struct Ipc {
     pub queue_tx: mpsc::Sender<QueueEvent>,
     pub report_tx: mpsc::Sender<ReportingEvent>,
     pub broadcast_tx: Option<mpsc::Sender<BroadcastEvent>>,
+    pub queue_state_tx: broadcast::Sender<QueueStateEvent>,
     pub train_task_controller: Arc<TrainTaskController>,
 }
 
@@ -485,6 +508,7 @@ impl Default for Caches {
             events: Cache::new(1024, 10 * 1024 * 1024),
             scheduling: Cache::new(1024, 10 * 1024 * 1024),
             dns_rbl: CacheWithTtl::new(1024, 10 * 1024 * 1024),
+            dns_rbl_listings: Cache::new(1024, 10 * 1024 * 1024),
             dns_txt: CacheWithTtl::new(1024, 10 * 1024 * 1024),
             dns_mx: CacheWithTtl::new(1024, 10 * 1024 * 1024),
             dns_ptr: CacheWithTtl::new(1024, 10 * 1024 * 1024),
@@ -492,6 +516,7 @@ impl Default for Caches {
             dns_ipv6: CacheWithTtl::new(1024, 10 * 1024 * 1024),
             dns_tlsa: CacheWithTtl::new(1024, 10 * 1024 * 1024),
             dbs_mta_sts: CacheWithTtl::new(1024, 10 * 1024 * 1024),
+            remote_max_size: CacheWithTtl::new(1024, 10 * 1024 * 1024),
         }
     }
 }
@@ -506,6 +531,7 @@ impl Default for Ipc {
             queue_tx: mpsc::channel(IPC_CHANNEL_BUFFER).0,
             report_tx: mpsc::channel(IPC_CHANNEL_BUFFER).0,
             broadcast_tx: None,
+            queue_state_tx: broadcast::channel(IPC_CHANNEL_BUFFER).0,
             train_task_controller: Arc::new(TrainTaskController::default()),
         }
     }
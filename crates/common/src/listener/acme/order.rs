@@ -247,7 +247,7 @@ impl Server {
                         let name = format!("_acme-challenge.{}", domain);
                         let origin = origin
                             .as_deref()
-                            .or_else(|| psl::domain_str(domain))
+                            .or_else(|| self.psl_domain(domain))
                             .unwrap_or(domain)
                             .to_string();
 
@@ -30,7 +30,7 @@ use store::{
     Stores,
     rand::{Rng, distr::Alphanumeric, rng},
 };
-use tokio::sync::{Notify, mpsc};
+use tokio::sync::{Notify, broadcast, mpsc};
 use utils::{
     UnwrapFailure,
     config::{Config, ConfigKey},
@@ -495,6 +495,18 @@ impl BootManager {
                     );
                 }
 
+                // Fetch the public suffix list without delaying startup,
+                // the bundled list is used until it is loaded
+                let server = inner.build_server();
+                tokio::spawn(async move {
+                    if let Err(err) = server.reload_public_suffix().await {
+                        trc::error!(
+                            err.details("Failed to load public suffix list")
+                                .caused_by(trc::location!())
+                        );
+                    }
+                });
+
                 // Fetch ASN database
                 if has_remote_asn {
                     inner
@@ -564,6 +576,7 @@ pub fn build_ipc(has_pubsub: bool) -> (Ipc, IpcReceivers) {
             queue_tx,
             report_tx,
             broadcast_tx: has_pubsub.then_some(broadcast_tx),
+            queue_state_tx: broadcast::channel(IPC_CHANNEL_BUFFER).0,
             task_tx: Arc::new(Notify::new()),
             train_task_controller: Arc::new(TrainTaskController::default()),
         },
@@ -7,7 +7,7 @@
 use ahash::AHashMap;
 use arc_swap::ArcSwap;
 use store::Stores;
-use utils::config::Config;
+use utils::{config::Config, suffixlist::PublicSuffix};
 
 use crate::{
     Core, Server,
@@ -20,6 +20,8 @@ use crate::{
 
 use super::config::{ConfigManager, Patterns};
 
+pub const PUBLIC_SUFFIX_KEY: &str = "public-suffix";
+
 pub struct ReloadResult {
     pub config: Config,
     pub new_core: Option<Core>,
@@ -39,6 +41,34 @@ impl Server {
         Ok(config.into())
     }
 
+    pub async fn reload_public_suffix(&self) -> trc::Result<ReloadResult> {
+        let mut config = self
+            .core
+            .storage
+            .config
+            .build_config(PUBLIC_SUFFIX_KEY)
+            .await?;
+        let list = PublicSuffix::parse(&mut config, PUBLIC_SUFFIX_KEY).await;
+
+        // Keep the current list rather than falling back to the embedded copy
+        // when none of the configured sources could be loaded
+        let is_fallback = list.sources.iter().all(|source| source == "embedded");
+        if !is_fallback
+            || self
+                .inner
+                .data
+                .public_suffix
+                .load()
+                .sources
+                .iter()
+                .all(|source| source == "embedded")
+        {
+            self.inner.data.public_suffix.store(list.into());
+        }
+
+        Ok(config.into())
+    }
+
     pub async fn reload_certificates(&self) -> trc::Result<ReloadResult> {
         let mut config = self.core.storage.config.build_config("certificate").await?;
         let mut certificates = self.inner.data.tls_certificates.load().as_ref().clone();
@@ -67,6 +97,28 @@ impl Server {
         })
     }
 
+    pub async fn reload_sieve_scripts(&self) -> trc::Result<ReloadResult> {
+        let mut config = self
+            .core
+            .storage
+            .config
+            .build_config("sieve.trusted.scripts")
+            .await?;
+        let mut core = self.core.as_ref().clone();
+        core.sieve.parse_trusted_scripts(&mut config);
+
+        // Keep the current scripts if any of them failed to compile
+        if !config.errors.is_empty() {
+            return Ok(config.into());
+        }
+
+        Ok(ReloadResult {
+            config,
+            new_core: core.into(),
+            tracers: None,
+        })
+    }
+
     pub async fn reload(&self) -> trc::Result<ReloadResult> {
         let mut config = self.core.storage.config.build_config("").await?;
 
@@ -11,12 +11,12 @@ use sieve::{FunctionMap, runtime::Variable};
 
 use super::PluginContext;
 
-pub fn register(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("dns_query", plugin_id, 2);
+pub fn register(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 2);
 }
 
-pub fn register_exists(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("dns_exists", plugin_id, 2);
+pub fn register_exists(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 2);
 }
 
 pub async fn exec(ctx: PluginContext<'_>) -> trc::Result<Variable> {
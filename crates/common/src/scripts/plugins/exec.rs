@@ -10,8 +10,8 @@ use sieve::{FunctionMap, runtime::Variable};
 
 use super::PluginContext;
 
-pub fn register(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("exec", plugin_id, 2);
+pub fn register(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 2);
 }
 
 pub async fn exec(ctx: PluginContext<'_>) -> trc::Result<Variable> {
@@ -10,8 +10,8 @@ use crate::scripts::ScriptModification;
 
 use super::PluginContext;
 
-pub fn register(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("add_header", plugin_id, 2);
+pub fn register(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 2);
 }
 
 pub fn exec(ctx: PluginContext<'_>) -> trc::Result<Variable> {
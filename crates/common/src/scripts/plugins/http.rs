@@ -11,8 +11,8 @@ use sieve::{FunctionMap, runtime::Variable};
 
 use super::PluginContext;
 
-pub fn register_header(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("http_header", plugin_id, 4);
+pub fn register_header(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 4);
 }
 
 pub async fn exec_header(ctx: PluginContext<'_>) -> trc::Result<Variable> {
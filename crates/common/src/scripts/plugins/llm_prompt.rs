@@ -12,8 +12,8 @@ use trc::{AiEvent, SecurityEvent};
 
 use super::PluginContext;
 
-pub fn register(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("llm_prompt", plugin_id, 3);
+pub fn register(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 3);
 }
 
 pub async fn exec(ctx: PluginContext<'_>) -> trc::Result<Variable> {
@@ -9,20 +9,20 @@ use crate::scripts::into_sieve_value;
 use sieve::{FunctionMap, runtime::Variable};
 use store::{Deserialize, Value, dispatch::lookup::KeyValue};
 
-pub fn register(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("key_exists", plugin_id, 2);
+pub fn register(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 2);
 }
 
-pub fn register_get(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("key_get", plugin_id, 2);
+pub fn register_get(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 2);
 }
 
-pub fn register_set(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("key_set", plugin_id, 4);
+pub fn register_set(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 4);
 }
 
-pub fn register_local_domain(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("is_local_domain", plugin_id, 2);
+pub fn register_local_domain(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 2);
 }
 
 pub async fn exec(ctx: PluginContext<'_>) -> trc::Result<Variable> {
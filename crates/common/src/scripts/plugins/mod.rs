@@ -20,7 +20,7 @@ use crate::{Core, Server, auth::AccessToken};
 
 use super::ScriptModification;
 
-type RegisterPluginFnc = fn(u32, &mut FunctionMap) -> ();
+type RegisterPluginFnc = fn(&'static str, u32, &mut FunctionMap) -> ();
 
 pub struct PluginContext<'x> {
     pub session_id: u64,
@@ -31,22 +31,26 @@ pub struct PluginContext<'x> {
     pub arguments: Vec<Variable>,
 }
 
-const PLUGINS_REGISTER: [RegisterPluginFnc; 13] = [
-    query::register,
-    exec::register,
-    lookup::register,
-    lookup::register_get,
-    lookup::register_set,
-    lookup::register_local_domain,
-    dns::register,
-    dns::register_exists,
-    http::register_header,
-    headers::register,
-    text::register_tokenize,
-    text::register_domain_part,
-    llm_prompt::register,
+// Plugin ids are the positions in this table
+const PLUGINS_REGISTER: [(&str, RegisterPluginFnc); 13] = [
+    ("query", query::register),
+    ("exec", exec::register),
+    ("key_exists", lookup::register),
+    ("key_get", lookup::register_get),
+    ("key_set", lookup::register_set),
+    ("is_local_domain", lookup::register_local_domain),
+    ("dns_query", dns::register),
+    ("dns_exists", dns::register_exists),
+    ("http_header", http::register_header),
+    ("add_header", headers::register),
+    ("tokenize", text::register_tokenize),
+    ("domain_part", text::register_domain_part),
+    ("llm_prompt", llm_prompt::register),
 ];
 
+// Plugins available to user scripts
+const PLUGINS_UNTRUSTED: &[&str] = &["llm_prompt"];
+
 pub trait RegisterSievePlugins {
     fn register_plugins_trusted(self) -> Self;
     fn register_plugins_untrusted(self) -> Self;
@@ -59,19 +63,32 @@ impl RegisterSievePlugins for FunctionMap {
             self.set_external_function("print", PLUGINS_REGISTER.len() as u32, 1)
         }
 
-        for (i, fnc) in PLUGINS_REGISTER.iter().enumerate() {
-            fnc(i as u32, &mut self);
+        for (i, (name, fnc)) in PLUGINS_REGISTER.iter().enumerate() {
+            fnc(name, i as u32, &mut self);
         }
         self
     }
 
     fn register_plugins_untrusted(mut self) -> Self {
-        llm_prompt::register(12, &mut self);
+        for (i, (name, fnc)) in PLUGINS_REGISTER.iter().enumerate() {
+            if PLUGINS_UNTRUSTED.contains(name) {
+                fnc(name, i as u32, &mut self);
+            }
+        }
         self
     }
 }
 
 impl Core {
+    pub fn plugin_name(&self, id: u32) -> &'static str {
+        match PLUGINS_REGISTER.get(id as usize) {
+            Some((name, _)) => name,
+            #[cfg(feature = "test_mode")]
+            None if id == PLUGINS_REGISTER.len() as u32 => "print",
+            None => "unknown",
+        }
+    }
+
     pub async fn run_plugin(&self, id: u32, ctx: PluginContext<'_>) -> Input {
         #[cfg(feature = "test_mode")]
         if id == PLUGINS_REGISTER.len() as u32 {
@@ -11,12 +11,12 @@ use crate::scripts::functions::{ApplyString, text::tokenize_words};
 
 use super::PluginContext;
 
-pub fn register_tokenize(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("tokenize", plugin_id, 2);
+pub fn register_tokenize(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 2);
 }
 
-pub fn register_domain_part(plugin_id: u32, fnc_map: &mut FunctionMap) {
-    fnc_map.set_external_function("domain_part", plugin_id, 2);
+pub fn register_domain_part(name: &'static str, plugin_id: u32, fnc_map: &mut FunctionMap) {
+    fnc_map.set_external_function(name, plugin_id, 2);
 }
 
 pub fn exec_tokenize(ctx: PluginContext<'_>) -> trc::Result<Variable> {
@@ -68,7 +68,7 @@ pub fn exec_domain_part(ctx: PluginContext<'_>) -> trc::Result<Variable> {
 
     Ok(v[0].transform(|domain| {
         match part {
-            DomainPart::Sld => psl::domain_str(domain),
+            DomainPart::Sld => ctx.server.psl_domain(domain),
             DomainPart::Tld => domain.rsplit_once('.').map(|(_, tld)| tld),
             DomainPart::Host => domain.split_once('.').map(|(host, _)| host),
         }
@@ -16,7 +16,10 @@ use calcard::{
         ICalendarProperty, ICalendarTransparency, ICalendarValue,
     },
 };
-use common::{DavResourcePath, DavResources, PROD_ID, Server, auth::AccessToken};
+use common::{
+    DavResourcePath, DavResources, PROD_ID, Server, auth::AccessToken,
+    config::groupware::FreeBusyVisibility,
+};
 use dav_proto::{RequestHeaders, schema::request::FreeBusyQuery};
 use groupware::{cache::GroupwareCache, calendar::CalendarEvent};
 use http_proto::HttpResponse;
@@ -101,7 +104,9 @@ impl CalendarFreebusyRequestHandler for Server {
         resource: DavResourcePath<'_>,
     ) -> crate::Result<ICalendar> {
         // Obtain shared ids
-        let shared_ids = if !access_token.is_member(account_id) {
+        let is_member = access_token.is_member(account_id);
+        let visibility = self.core.groupware.free_busy_visibility(is_member);
+        let shared_ids = if !is_member {
             resources
                 .shared_containers(
                     access_token,
@@ -143,17 +148,19 @@ impl CalendarFreebusyRequestHandler for Server {
                 ))],
             });
 
+            let is_visible = visibility != FreeBusyVisibility::None
+                && shared_ids
+                    .as_ref()
+                    .is_none_or(|ids| ids.contains(resource.document_id()));
             let document_ids = resources
                 .children(resource.document_id())
                 .filter(|resource| {
-                    shared_ids
-                        .as_ref()
-                        .is_none_or(|ids| ids.contains(resource.document_id()))
-                        && is_resource_in_time_range(resource.resource, &range)
+                    is_visible && is_resource_in_time_range(resource.resource, &range)
                 })
                 .map(|resource| resource.document_id())
                 .collect::<Vec<_>>();
 
+            let max_horizon = self.core.groupware.max_ical_horizon;
             let mut fb_entries: AHashMap<ICalendarFreeBusyType, Vec<(i64, i64)>> =
                 AHashMap::with_capacity(document_ids.len());
 
@@ -201,8 +208,8 @@ impl CalendarFreebusyRequestHandler for Server {
                     continue;
                 }
 
-                let events =
-                    CalendarQueryHandler::new(event, Some(range), default_tz).into_expanded_times();
+                let events = CalendarQueryHandler::new(event, Some(range), max_horizon, default_tz)
+                    .into_expanded_times();
 
                 if events.is_empty() {
                     continue;
@@ -268,6 +275,18 @@ impl CalendarFreebusyRequestHandler for Server {
                 }
             }
 
+            // Only disclose busy time, without telling apart tentative or unavailable periods
+            if visibility == FreeBusyVisibility::BusyOnly {
+                let busy = fb_entries
+                    .drain()
+                    .filter(|(fbtype, _)| *fbtype != ICalendarFreeBusyType::Free)
+                    .flat_map(|(_, events_in_range)| events_in_range)
+                    .collect::<Vec<_>>();
+                if !busy.is_empty() {
+                    fb_entries.insert(ICalendarFreeBusyType::Busy, busy);
+                }
+            }
+
             for (fbtype, events_in_range) in fb_entries {
                 entries.push(ICalendarEntry {
                     name: ICalendarProperty::Freebusy,
@@ -103,6 +103,9 @@ impl CalendarMkColRequestHandler for Server {
                 access_token,
                 &mut calendar,
                 false,
+                self.ical_size_limit(account_id)
+                    .await
+                    .caused_by(trc::location!())?,
                 mkcol.props,
                 &mut prop_stat,
             ) {
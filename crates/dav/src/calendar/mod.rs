@@ -127,7 +127,7 @@ pub(crate) trait ItipPrecondition {
 impl ItipPrecondition for ItipError {
     fn failed_precondition(&self) -> Option<CalCondition> {
         match self {
-            ItipError::MultipleOrganizer => Some(CalCondition::SameOrganizerInAllComponents),
+            ItipError::MultipleOrganizer { .. } => Some(CalCondition::SameOrganizerInAllComponents),
             ItipError::OrganizerIsLocalAddress
             | ItipError::SenderIsNotParticipant(_)
             | ItipError::OrganizerMismatch => Some(CalCondition::ValidOrganizer),
@@ -135,7 +135,7 @@ impl ItipPrecondition for ItipError {
             | ItipError::CannotModifyInstance
             | ItipError::CannotModifyAddress => Some(CalCondition::AllowedAttendeeObjectChange),
             ItipError::MissingUid
-            | ItipError::MultipleUid
+            | ItipError::MultipleUid { .. }
             | ItipError::MultipleObjectTypes
             | ItipError::MultipleObjectInstances
             | ItipError::MissingMethod
@@ -143,6 +143,8 @@ impl ItipPrecondition for ItipError {
             | ItipError::OutOfSequence
             | ItipError::UnknownParticipant(_)
             | ItipError::UnsupportedMethod(_) => Some(CalCondition::ValidSchedulingMessage),
+            ItipError::TooManyAttendees => Some(CalCondition::MaxAttendeesPerInstance),
+            ItipError::TooManyOverrides => Some(CalCondition::MaxInstances),
             _ => None,
         }
     }
@@ -55,6 +55,7 @@ pub(crate) trait CalendarPropPatchRequestHandler: Sync + Send {
         access_token: &AccessToken,
         calendar: &mut Calendar,
         is_update: bool,
+        max_ical_size: usize,
         properties: Vec<DavPropertyValue>,
         items: &mut PropStatBuilder,
     ) -> bool;
@@ -171,6 +172,9 @@ impl CalendarPropPatchRequestHandler for Server {
                 access_token,
                 &mut new_calendar,
                 true,
+                self.ical_size_limit(account_id)
+                    .await
+                    .caused_by(trc::location!())?,
                 request.set,
                 &mut items,
             );
@@ -251,6 +255,7 @@ impl CalendarPropPatchRequestHandler for Server {
         access_token: &AccessToken,
         calendar: &mut Calendar,
         is_update: bool,
+        max_ical_size: usize,
         properties: Vec<DavPropertyValue>,
         items: &mut PropStatBuilder,
     ) -> bool {
@@ -263,10 +268,9 @@ impl CalendarPropPatchRequestHandler for Server {
                         calendar.preferences_mut(access_token).name = name;
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.live_property_size,
                         );
                         has_errors = true;
                     }
@@ -279,10 +283,9 @@ impl CalendarPropPatchRequestHandler for Server {
                         calendar.preferences_mut(access_token).description = Some(name);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.live_property_size,
                         );
 
                         has_errors = true;
@@ -292,12 +295,8 @@ impl CalendarPropPatchRequestHandler for Server {
                     DavProperty::CalDav(CalDavProperty::CalendarTimezone),
                     DavValue::ICalendar(ical),
                 ) => {
-                    if ical.size() > self.core.groupware.max_ical_size {
-                        items.insert_error_with_description(
-                            property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
-                        );
+                    if ical.size() > max_ical_size {
+                        items.insert_too_large(property.property, max_ical_size);
                         has_errors = true;
                     } else if !ical.is_timezone() {
                         items.insert_precondition_failed_with_description(
@@ -391,15 +390,14 @@ impl CalendarPropPatchRequestHandler for Server {
                     }
 
                     if calendar.dead_properties.size() + values.size() + dead.size()
-                        < self.core.groupware.dead_property_size.unwrap()
+                        < self.core.groupware.dead_property_size.unwrap_or_default()
                     {
                         calendar.dead_properties.add_element(dead.clone(), values.0);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.dead_property_size.unwrap_or_default(),
                         );
 
                         has_errors = true;
@@ -438,10 +436,9 @@ impl CalendarPropPatchRequestHandler for Server {
                         event.display_name = Some(name);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.live_property_size,
                         );
                         has_errors = true;
                     }
@@ -458,15 +455,14 @@ impl CalendarPropPatchRequestHandler for Server {
                     }
 
                     if event.dead_properties.size() + values.size() + dead.size()
-                        < self.core.groupware.dead_property_size.unwrap()
+                        < self.core.groupware.dead_property_size.unwrap_or_default()
                     {
                         event.dead_properties.add_element(dead.clone(), values.0);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.dead_property_size.unwrap_or_default(),
                         );
                         has_errors = true;
                     }
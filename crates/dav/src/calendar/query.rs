@@ -217,6 +217,7 @@ impl CalendarQueryHandler {
     pub fn new(
         event: &ArchivedCalendarEvent,
         max_time_range: Option<TimeRange>,
+        max_horizon: i64,
         default_tz: Tz,
     ) -> Self {
         Self {
@@ -225,7 +226,7 @@ impl CalendarQueryHandler {
                 .map(|max_time_range| {
                     event
                         .data
-                        .expand(default_tz, max_time_range)
+                        .expand(default_tz, max_time_range, max_horizon)
                         .unwrap_or_else(|| {
                             trc::event!(
                                 Calendar(trc::CalendarEvent::RuleExpansionError),
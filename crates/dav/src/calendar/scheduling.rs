@@ -244,10 +244,14 @@ impl CalendarEventNotificationHandler for Server {
         }
 
         // Parse iTIP message
-        if bytes.len() > self.core.groupware.max_ical_size {
+        let max_ical_size = self
+            .ical_size_limit(resource.account_id)
+            .await
+            .caused_by(trc::location!())?;
+        if bytes.len() > max_ical_size {
             return Err(DavError::Condition(DavErrorCondition::new(
                 StatusCode::PRECONDITION_FAILED,
-                CalCondition::MaxResourceSize(self.core.groupware.max_ical_size as u32),
+                CalCondition::MaxResourceSize(max_ical_size as u32),
             )));
         }
         let itip_raw = std::str::from_utf8(&bytes).map_err(|_| {
@@ -30,7 +30,7 @@ use directory::Permission;
 use groupware::{
     cache::GroupwareCache,
     calendar::{CalendarEvent, CalendarEventData},
-    scheduling::{ItipMessages, event_create::itip_create, event_update::itip_update},
+    scheduling::{ItipLimits, ItipMessages, event_create::itip_create, event_update::itip_update},
 };
 use http_proto::HttpResponse;
 use hyper::StatusCode;
@@ -80,10 +80,14 @@ impl CalendarUpdateRequestHandler for Server {
                 .ok_or(DavError::Code(StatusCode::CONFLICT))?,
         );
 
-        if bytes.len() > self.core.groupware.max_ical_size {
+        let max_ical_size = self
+            .ical_size_limit(account_id)
+            .await
+            .caused_by(trc::location!())?;
+        if bytes.len() > max_ical_size {
             return Err(DavError::Condition(DavErrorCondition::new(
                 StatusCode::PRECONDITION_FAILED,
-                CalCondition::MaxResourceSize(self.core.groupware.max_ical_size as u32),
+                CalCondition::MaxResourceSize(max_ical_size as u32),
             )));
         }
         let ical_raw = std::str::from_utf8(&bytes).map_err(|_| {
@@ -208,6 +212,7 @@ impl CalendarUpdateRequestHandler for Server {
                 ical,
                 Tz::Floating,
                 self.core.groupware.max_ical_instances,
+                self.core.groupware.max_ical_horizon,
                 &mut next_email_alarm,
             );
 
@@ -223,9 +228,14 @@ impl CalendarUpdateRequestHandler for Server {
                         &mut new_event.data.event,
                         &old_ical,
                         access_token.emails.as_slice(),
+                        ItipLimits::from(&self.core.groupware),
                     )
                 } else {
-                    itip_create(&mut new_event.data.event, access_token.emails.as_slice())
+                    itip_create(
+                        &mut new_event.data.event,
+                        access_token.emails.as_slice(),
+                        ItipLimits::from(&self.core.groupware),
+                    )
                 };
 
                 match result {
@@ -365,6 +375,7 @@ impl CalendarUpdateRequestHandler for Server {
                     ical,
                     Tz::Floating,
                     self.core.groupware.max_ical_instances,
+                    self.core.groupware.max_ical_horizon,
                     &mut next_email_alarm,
                 ),
                 size: bytes.len() as u32,
@@ -378,7 +389,11 @@ impl CalendarUpdateRequestHandler for Server {
                 && access_token.has_permission(Permission::CalendarSchedulingSend)
                 && event.data.event_range_end() > now() as i64
             {
-                match itip_create(&mut event.data.event, access_token.emails.as_slice()) {
+                match itip_create(
+                    &mut event.data.event,
+                    access_token.emails.as_slice(),
+                    ItipLimits::from(&self.core.groupware),
+                ) {
                     Ok(messages) => {
                         if messages.iter().map(|r| r.to.len()).sum::<usize>()
                             < self.core.groupware.itip_outbound_max_recipients
@@ -260,10 +260,9 @@ impl CardPropPatchRequestHandler for Server {
                         address_book.preferences_mut(access_token).name = name;
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.live_property_size,
                         );
                         has_errors = true;
                     }
@@ -276,10 +275,9 @@ impl CardPropPatchRequestHandler for Server {
                         address_book.preferences_mut(access_token).description = Some(name);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.live_property_size,
                         );
 
                         has_errors = true;
@@ -314,17 +312,16 @@ impl CardPropPatchRequestHandler for Server {
                     }
 
                     if address_book.dead_properties.size() + values.size() + dead.size()
-                        < self.core.groupware.dead_property_size.unwrap()
+                        < self.core.groupware.dead_property_size.unwrap_or_default()
                     {
                         address_book
                             .dead_properties
                             .add_element(dead.clone(), values.0);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.dead_property_size.unwrap_or_default(),
                         );
                         has_errors = true;
                     }
@@ -362,10 +359,9 @@ impl CardPropPatchRequestHandler for Server {
                         card.display_name = Some(name);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.live_property_size,
                         );
                         has_errors = true;
                     }
@@ -382,15 +378,14 @@ impl CardPropPatchRequestHandler for Server {
                     }
 
                     if card.dead_properties.size() + values.size() + dead.size()
-                        < self.core.groupware.dead_property_size.unwrap()
+                        < self.core.groupware.dead_property_size.unwrap_or_default()
                     {
                         card.dead_properties.add_element(dead.clone(), values.0);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.dead_property_size.unwrap_or_default(),
                         );
                         has_errors = true;
                     }
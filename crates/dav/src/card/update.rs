@@ -69,10 +69,14 @@ impl CardUpdateRequestHandler for Server {
                 .ok_or(DavError::Code(StatusCode::CONFLICT))?,
         );
 
-        if bytes.len() > self.core.groupware.max_vcard_size {
+        let max_vcard_size = self
+            .vcard_size_limit(account_id)
+            .await
+            .caused_by(trc::location!())?;
+        if bytes.len() > max_vcard_size {
             return Err(DavError::Condition(DavErrorCondition::new(
                 StatusCode::PRECONDITION_FAILED,
-                CardCondition::MaxResourceSize(self.core.groupware.max_vcard_size as u32),
+                CardCondition::MaxResourceSize(max_vcard_size as u32),
             )));
         }
         let vcard_raw = std::str::from_utf8(&bytes).map_err(|_| {
@@ -478,8 +478,12 @@ impl PropFindRequestHandler for Server {
                         } else {
                             Tz::UTC
                         };
-                        let mut query_handler =
-                            CalendarQueryHandler::new(event.inner, *max_time_range, default_tz);
+                        let mut query_handler = CalendarQueryHandler::new(
+                            event.inner,
+                            *max_time_range,
+                            self.core.groupware.max_ical_horizon,
+                            default_tz,
+                        );
                         if !query_handler.filter(event.inner, filter) {
                             continue;
                         }
@@ -835,7 +839,10 @@ impl PropFindRequestHandler for Server {
                         (CardDavProperty::MaxResourceSize, ArchivedResource::AddressBook(_)) => {
                             fields.push(DavPropertyValue::new(
                                 property.clone(),
-                                self.core.groupware.max_vcard_size as u64,
+                                self.vcard_size_limit(account_id)
+                                    .await
+                                    .caused_by(trc::location!())?
+                                    as u64,
                             ));
                         }
                         (
@@ -950,7 +957,10 @@ impl PropFindRequestHandler for Server {
                         (CalDavProperty::MaxResourceSize, ArchivedResource::Calendar(_)) => {
                             fields.push(DavPropertyValue::new(
                                 property.clone(),
-                                self.core.groupware.max_ical_size as u64,
+                                self.ical_size_limit(account_id)
+                                    .await
+                                    .caused_by(trc::location!())?
+                                    as u64,
                             ));
                         }
                         (CalDavProperty::MinDateTime, ArchivedResource::Calendar(_)) => {
@@ -987,7 +997,12 @@ impl PropFindRequestHandler for Server {
                             if calendar_filter.is_some() || !data.properties.is_empty() {
                                 if let Some(ical) = calendar_filter
                                     .get_or_insert_with(|| {
-                                        CalendarQueryHandler::new(event.inner, None, Tz::UTC)
+                                        CalendarQueryHandler::new(
+                                            event.inner,
+                                            None,
+                                            self.core.groupware.max_ical_horizon,
+                                            Tz::UTC,
+                                        )
                                     })
                                     .serialize_ical(event.inner, data, &mut ical_instances_limit)
                                 {
@@ -186,10 +186,9 @@ impl FilePropPatchRequestHandler for Server {
                         file.display_name = Some(name);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.live_property_size,
                         );
 
                         has_errors = true;
@@ -206,10 +205,9 @@ impl FilePropPatchRequestHandler for Server {
                         file.file.as_mut().unwrap().media_type = Some(name);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.live_property_size,
                         );
                         has_errors = true;
                     }
@@ -237,15 +235,14 @@ impl FilePropPatchRequestHandler for Server {
                     }
 
                     if file.dead_properties.size() + values.size() + dead.size()
-                        < self.core.groupware.dead_property_size.unwrap()
+                        < self.core.groupware.dead_property_size.unwrap_or_default()
                     {
                         file.dead_properties.add_element(dead.clone(), values.0);
                         items.insert_ok(property.property);
                     } else {
-                        items.insert_error_with_description(
+                        items.insert_too_large(
                             property.property,
-                            StatusCode::INSUFFICIENT_STORAGE,
-                            "Property value is too long",
+                            self.core.groupware.dead_property_size.unwrap_or_default(),
                         );
                         has_errors = true;
                     }
@@ -14,7 +14,7 @@ pub mod request;
 
 use dav_proto::schema::{
     request::DavPropertyValue,
-    response::{Condition, List, Prop, PropStat, ResponseDescription, Status},
+    response::{BaseCondition, Condition, List, Prop, PropStat, ResponseDescription, Status},
 };
 use groupware::{DavResourceName, RFC_3986};
 use hyper::{Method, StatusCode};
@@ -194,6 +194,19 @@ impl PropStatBuilder {
         self
     }
 
+    pub fn insert_too_large(
+        &mut self,
+        prop: impl Into<DavPropertyValue>,
+        max_size: usize,
+    ) -> &mut Self {
+        self.insert_precondition_failed_with_description(
+            prop,
+            StatusCode::INSUFFICIENT_STORAGE,
+            BaseCondition::QuotaNotExceeded,
+            format!("Property value exceeds the maximum size of {max_size} bytes"),
+        )
+    }
+
     pub fn insert_precondition_failed(
         &mut self,
         prop: impl Into<DavPropertyValue>,
@@ -586,17 +586,67 @@ impl DavRequestHandler for Server {
                 .and_then(|v| v.parse::<u64>().ok())
                 .is_some_and(|len| len > 0)
         {
-            if let Some(body) = fetch_body(
-                &mut request,
-                if !access_token.has_permission(Permission::UnlimitedUploads) {
-                    self.core.groupware.max_request_size
-                } else {
-                    0
-                },
-                session.session_id,
-            )
-            .await
+            let max_size = if access_token.has_permission(Permission::UnlimitedUploads) {
+                0
+            } else if resource == DavResourceName::File && matches!(method, DavMethod::PUT) {
+                // File uploads are capped while streaming, before they are buffered in full
+                let mut max_size = self.core.groupware.max_file_size;
+                if self.core.groupware.file_upload_quota
+                    && let Some(account_id) = self
+                        .validate_uri(&access_token, request.uri().path())
+                        .await
+                        .ok()
+                        .and_then(|uri| uri.account_id)
+                {
+                    // Uploads count against the quota of the account owning the target collection
+                    let resource_token =
+                        match self.get_resource_token(&access_token, account_id).await {
+                            Ok(resource_token) => resource_token,
+                            Err(err) => {
+                                trc::error!(err.span_id(session.session_id));
+                                return HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR);
+                            }
+                        };
+                    if resource_token.quota != 0 {
+                        let available_quota = match self.get_used_quota(account_id).await {
+                            Ok(used_quota) => {
+                                resource_token.quota.saturating_sub(used_quota as u64)
+                            }
+                            Err(err) => {
+                                trc::error!(err.span_id(session.session_id));
+                                return HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR);
+                            }
+                        };
+                        if available_quota == 0 {
+                            return HttpResponse::new(StatusCode::INSUFFICIENT_STORAGE);
+                        }
+                        max_size = max_size.min(available_quota as usize);
+                    }
+                }
+                max_size
+            } else {
+                self.core.groupware.max_request_size
+            };
+
+            if max_size != 0
+                && request
+                    .headers()
+                    .get(header::CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .is_some_and(|len| len > max_size as u64)
             {
+                trc::event!(
+                    Limit(trc::LimitEvent::SizeRequest),
+                    SpanId = session.session_id,
+                    Contents = "Request body too large",
+                    Limit = max_size,
+                );
+
+                return HttpResponse::new(StatusCode::PAYLOAD_TOO_LARGE);
+            }
+
+            if let Some(body) = fetch_body(&mut request, max_size, session.session_id).await {
                 body
             } else {
                 trc::event!(
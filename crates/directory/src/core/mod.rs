@@ -319,6 +319,7 @@ impl Permission {
             Permission::JmapParticipantIdentityChanges => {
                 "Track participant identity changes via JMAP"
             }
+            Permission::ImapSharedNamespace => "Discover shared mailboxes via IMAP NAMESPACE",
         }
     }
 }
@@ -1665,6 +1665,7 @@ impl Permission {
                 | Permission::JmapParticipantIdentityGet
                 | Permission::JmapParticipantIdentitySet
                 | Permission::JmapParticipantIdentityChanges
+                | Permission::ImapSharedNamespace
         )
     }
 
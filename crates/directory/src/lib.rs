@@ -423,6 +423,8 @@ pub enum Permission {
     JmapParticipantIdentityGet,
     JmapParticipantIdentitySet,
     JmapParticipantIdentityChanges,
+
+    ImapSharedNamespace,
     // TODO: Reuse _ suffixes for new permissions
     // WARNING: add new ids at the end (TODO: use static ids)
 }
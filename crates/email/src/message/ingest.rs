@@ -397,7 +397,8 @@ impl EmailIngest for Server {
                                         Err(ItipIngestError::Message(itip_error)) => {
                                             match itip_error {
                                                 ItipError::NothingToSend
-                                                | ItipError::OtherSchedulingAgent => (),
+                                                | ItipError::OtherSchedulingAgent
+                                                | ItipError::SchedulingDisabled => (),
                                                 err => {
                                                     trc::event!(
                                                         Calendar(
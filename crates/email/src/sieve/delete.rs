@@ -4,12 +4,12 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use super::SieveScript;
+use super::{SieveScript, versions::SieveScriptVersioning};
 use common::{Server, auth::AccessToken, storage::index::ObjectIndexBuilder};
 use store::write::BatchBuilder;
 use store::{
     ValueKey,
-    write::{AlignedBytes, Archive},
+    write::{AlignedBytes, Archive, BlobLink, BlobOp},
 };
 use trc::AddContext;
 use types::{collection::Collection, field::SieveField};
@@ -42,18 +42,30 @@ impl SieveScriptDelete for Server {
             ))
             .await?
         {
+            let current = obj_
+                .to_unarchived::<SieveScript>()
+                .caused_by(trc::location!())?;
+            let versions = self.sieve_script_versions(account_id, document_id).await?;
+
             // Delete record
             batch
                 .with_account_id(account_id)
                 .with_collection(Collection::SieveScript)
                 .with_document(document_id)
                 .clear(SieveField::Ids)
+                .clear(SieveField::Versions);
+            for version in versions.versions {
+                if version.blob_hash.0 != current.inner.blob_hash.0 {
+                    batch.clear(BlobOp::Link {
+                        hash: version.blob_hash,
+                        to: BlobLink::Document,
+                    });
+                }
+            }
+            batch
                 .custom(
                     ObjectIndexBuilder::<_, ()>::new()
-                        .with_current(
-                            obj_.to_unarchived::<SieveScript>()
-                                .caused_by(trc::location!())?,
-                        )
+                        .with_current(current)
                         .with_access_token(access_token),
                 )
                 .caused_by(trc::location!())?
@@ -0,0 +1,130 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! Personal `include` commands are resolved when a script is compiled: the names
+//! are read from the compiled script and `sieve_script_compile` embeds the scripts
+//! they reference, so the runtime never looks them up. Scripts are compiled every
+//! time they are loaded, changes to an included script take effect without saving
+//! the scripts that include it. Cycles and the nesting depth are checked when a
+//! script is saved, names built from variables are not known until run time and
+//! cannot be included.
+
+use super::ingest::SieveScriptIngest;
+use common::Server;
+use sieve::Sieve;
+use std::sync::Arc;
+use store::ahash::AHashMap;
+use trc::AddContext;
+use types::{collection::Collection, field::SieveField};
+
+pub type ResolvedIncludes = AHashMap<String, Arc<Sieve>>;
+
+pub trait SieveScriptIncludes: Sync + Send {
+    fn sieve_script_resolve_includes(
+        &self,
+        account_id: u32,
+        name: &str,
+        script: &Sieve,
+    ) -> impl Future<Output = trc::Result<Result<ResolvedIncludes, String>>> + Send;
+}
+
+impl SieveScriptIncludes for Server {
+    async fn sieve_script_resolve_includes(
+        &self,
+        account_id: u32,
+        name: &str,
+        script: &Sieve,
+    ) -> trc::Result<Result<ResolvedIncludes, String>> {
+        let max_nested_includes = self.core.sieve.untrusted_max_nested_includes;
+        let mut resolved = ResolvedIncludes::default();
+        let mut path = vec![name.to_string()];
+        let mut stack = match personal_includes(script) {
+            Ok(includes) => vec![includes.into_iter()],
+            Err(reason) => return Ok(Err(reason)),
+        };
+
+        while let Some(includes) = stack.last_mut() {
+            let Some(include) = includes.next() else {
+                stack.pop();
+                path.pop();
+                continue;
+            };
+
+            if path.contains(&include) {
+                return Ok(Err(format!(
+                    "Script '{}' includes itself through '{include}'.",
+                    path.join("' -> '")
+                )));
+            } else if resolved.contains_key(&include) {
+                continue;
+            } else if stack.len() > max_nested_includes {
+                return Ok(Err(format!(
+                    "Including '{include}' exceeds the maximum nesting depth of {max_nested_includes}."
+                )));
+            }
+
+            // Missing scripts are not embedded, including them fails unless the include is optional
+            if let Some(document_id) = self
+                .document_ids_matching(
+                    account_id,
+                    Collection::SieveScript,
+                    SieveField::Name,
+                    include.as_bytes(),
+                )
+                .await
+                .caused_by(trc::location!())?
+                .min()
+                && let Some(compiled) = self
+                    .sieve_script_compile_without_includes(account_id, document_id)
+                    .await?
+            {
+                match personal_includes(&compiled.script) {
+                    Ok(names) => stack.push(names.into_iter()),
+                    Err(reason) => return Ok(Err(reason)),
+                }
+                path.push(include.clone());
+                resolved.insert(include, Arc::new(compiled.script));
+            }
+        }
+
+        Ok(Ok(resolved))
+    }
+}
+
+/// Returns the names of the personal scripts referenced by the `include` commands
+/// of a compiled script. The compiler does not expose its instructions, so they are
+/// read from the serialized script and a layout that is not understood is reported
+/// as an error instead of skipping the include checks.
+pub fn personal_includes(script: &Sieve) -> Result<Vec<String>, String> {
+    let unsupported = || "Unsupported compiled Sieve script layout.".to_string();
+    let script = serde_json::to_value(script).map_err(|_| unsupported())?;
+    let mut includes = Vec::new();
+
+    for include in script
+        .get("instructions")
+        .and_then(|instructions| instructions.as_array())
+        .ok_or_else(unsupported)?
+        .iter()
+        .filter_map(|instruction| instruction.get("Include"))
+    {
+        let value = include.get("value").ok_or_else(unsupported)?;
+        match include
+            .get("location")
+            .and_then(|location| location.as_str())
+        {
+            Some("Personal") => {
+                // Names built from variables are only known at run time
+                if let Some(name) = value.get("Text") {
+                    includes.push(name.as_str().ok_or_else(unsupported)?.to_string());
+                }
+            }
+            Some("Global") => {}
+            _ => return Err(unsupported()),
+        }
+    }
+
+    Ok(includes)
+}
@@ -4,7 +4,11 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use super::{ActiveScript, SeenIdHash, SieveScript};
+use super::{
+    ActiveScript, SeenIdHash, SieveScript,
+    include::{ResolvedIncludes, SieveScriptIncludes},
+    versions::SieveScriptVersioning,
+};
 use crate::{
     cache::{MessageCacheFetch, mailbox::MailboxCacheAccess},
     mailbox::{INBOX_ID, TRASH_ID, manage::MailboxFnc},
@@ -24,7 +28,8 @@ use store::{
     ahash::AHashMap,
     dispatch::lookup::KeyValue,
     write::{
-        AlignedBytes, Archive, ArchiveVersion, Archiver, BatchBuilder, BlobLink, BlobOp, ValueClass,
+        AlignedBytes, Archive, ArchiveVersion, Archiver, BatchBuilder, BlobLink, BlobOp,
+        ValueClass, now,
     },
 };
 use trc::{AddContext, SieveEvent};
@@ -81,6 +86,20 @@ pub trait SieveScriptIngest: Sync + Send {
         account_id: u32,
         document_id: u32,
     ) -> impl Future<Output = trc::Result<Option<CompiledScript>>> + Send;
+
+    fn sieve_script_compile_without_includes(
+        &self,
+        account_id: u32,
+        document_id: u32,
+    ) -> impl Future<Output = trc::Result<Option<CompiledScript>>> + Send;
+
+    fn sieve_duplicate_id(
+        &self,
+        id_hash: &SeenIdHash,
+        expiry: u64,
+        last: bool,
+        now: i64,
+    ) -> impl Future<Output = trc::Result<bool>> + Send;
 }
 
 impl SieveScriptIngest for Server {
@@ -169,10 +188,8 @@ impl SieveScriptIngest for Server {
                 Ok(event) => match event {
                     Event::IncludeScript { name, .. } => match &name {
                         sieve::Script::Personal(name_) => {
-                            if let Ok(Some(script)) =
-                                self.sieve_script_get_by_name(account_id, name_).await
-                            {
-                                input = Input::script(name, script);
+                            if let Some(script) = active_script.includes.get(name_.as_str()) {
+                                input = Input::script(name, script.clone());
                             } else {
                                 input = false.into();
                             }
@@ -258,27 +275,14 @@ impl SieveScriptIngest for Server {
                         }
                     }
                     Event::DuplicateId { id, expiry, last } => {
-                        let id_hash = SeenIdHash::new(
-                            account_id,
-                            active_script.version.hash().unwrap_or_default(),
-                            &id,
-                        );
+                        let id_hash = SeenIdHash::new(account_id, active_script.seen_ids_seed, &id);
                         if let Some(result) = checked_ids.get(&id_hash) {
                             input = (*result).into();
                         } else {
                             let exists = self
-                                .in_memory_store()
-                                .key_get::<()>(id_hash.key())
+                                .sieve_duplicate_id(&id_hash, expiry, last, now() as i64)
                                 .await
-                                .caused_by(trc::location!())?
-                                .is_some();
-
-                            if !exists || last {
-                                self.in_memory_store()
-                                    .key_set(KeyValue::new(id_hash.key(), vec![]).expires(expiry))
-                                    .await
-                                    .caused_by(trc::location!())?;
-                            }
+                                .caused_by(trc::location!())?;
 
                             checked_ids.insert(id_hash, exists);
                             input = exists.into();
@@ -589,11 +593,18 @@ impl SieveScriptIngest for Server {
             .caused_by(trc::location!())?
         {
             if let Some(script) = self.sieve_script_compile(account_id, document_id).await? {
+                // Seen ids survive rollbacks, any other change to the script resets them
+                let seen_ids_seed = self
+                    .sieve_script_versions(account_id, document_id)
+                    .await?
+                    .seen_ids_seed(&script.version);
+
                 Ok(Some(ActiveScript {
                     document_id,
+                    seen_ids_seed,
                     script: Arc::new(script.script),
                     script_name: script.name,
-                    version: script.version,
+                    includes: script.includes,
                 }))
             } else {
                 Ok(None)
@@ -628,11 +639,44 @@ impl SieveScriptIngest for Server {
         }
     }
 
-    #[allow(clippy::blocks_in_conditions)]
     async fn sieve_script_compile(
         &self,
         account_id: u32,
         document_id: u32,
+    ) -> trc::Result<Option<CompiledScript>> {
+        let Some(mut compiled) = self
+            .sieve_script_compile_without_includes(account_id, document_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        // Embed the personal scripts referenced by include commands
+        match self
+            .sieve_script_resolve_includes(account_id, &compiled.name, &compiled.script)
+            .await?
+        {
+            Ok(includes) => {
+                compiled.includes = includes;
+            }
+            Err(reason) => {
+                trc::event!(
+                    Sieve(SieveEvent::RuntimeError),
+                    AccountId = account_id,
+                    DocumentId = document_id,
+                    Reason = reason,
+                );
+            }
+        }
+
+        Ok(Some(compiled))
+    }
+
+    #[allow(clippy::blocks_in_conditions)]
+    async fn sieve_script_compile_without_includes(
+        &self,
+        account_id: u32,
+        document_id: u32,
     ) -> trc::Result<Option<CompiledScript>> {
         // Obtain script object
         let Some(script_object) = self
@@ -680,6 +724,7 @@ impl SieveScriptIngest for Server {
                 script,
                 name: unarchived_script.name.as_str().into(),
                 version,
+                includes: ResolvedIncludes::default(),
             }))
         } else {
             // Deserialization failed, probably because the script compiler version changed
@@ -742,6 +787,7 @@ impl SieveScriptIngest for Server {
                         script: sieve.into_inner(),
                         name: new_archive.into_inner().name,
                         version,
+                        includes: ResolvedIncludes::default(),
                     }))
                 }
                 Err(error) => Err(trc::StoreEvent::UnexpectedError
@@ -751,10 +797,44 @@ impl SieveScriptIngest for Server {
             }
         }
     }
+
+    async fn sieve_duplicate_id(
+        &self,
+        id_hash: &SeenIdHash,
+        expiry: u64,
+        last: bool,
+        now: i64,
+    ) -> trc::Result<bool> {
+        // Never keep ids for longer than the configured retention,
+        // older entries are treated as unseen and left to expire.
+        let expiry = expiry.min(self.core.sieve.duplicate_retention.as_secs());
+
+        // Ids are stored along with the time they were last seen,
+        // entries without a timestamp are considered current.
+        let last_seen = match self.in_memory_store().key_get::<i64>(id_hash.key()).await {
+            Ok(last_seen) => last_seen,
+            Err(err) if err.matches(trc::EventType::Store(trc::StoreEvent::DataCorruption)) => {
+                Some(now)
+            }
+            Err(err) => return Err(err.caused_by(trc::location!())),
+        };
+        let exists =
+            last_seen.is_some_and(|last_seen| now.saturating_sub(last_seen) < expiry as i64);
+
+        if !exists || last {
+            self.in_memory_store()
+                .key_set(KeyValue::new(id_hash.key(), now.to_be_bytes().to_vec()).expires(expiry))
+                .await
+                .caused_by(trc::location!())?;
+        }
+
+        Ok(exists)
+    }
 }
 
 pub struct CompiledScript {
     pub script: Sieve,
     pub name: String,
     pub version: ArchiveVersion,
+    pub includes: ResolvedIncludes,
 }
@@ -7,19 +7,22 @@
 use common::KV_SIEVE_ID;
 use sieve::Sieve;
 use std::sync::Arc;
-use store::{blake3, write::ArchiveVersion};
+use store::blake3;
 use types::blob_hash::BlobHash;
 
 pub mod delete;
+pub mod include;
 pub mod index;
 pub mod ingest;
+pub mod versions;
 
 #[derive(Debug, Clone)]
 pub struct ActiveScript {
     pub document_id: u32,
-    pub version: ArchiveVersion,
+    pub seen_ids_seed: u32,
     pub script_name: String,
     pub script: Arc<Sieve>,
+    pub includes: include::ResolvedIncludes,
 }
 
 #[derive(
@@ -33,6 +36,36 @@ pub struct SieveScript {
     pub vacation_response: Option<VacationResponse>,
 }
 
+#[derive(
+    rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Default, Clone, PartialEq, Eq,
+)]
+#[rkyv(derive(Debug))]
+pub struct SieveScriptVersions {
+    // Previous versions, most recent first
+    pub versions: Vec<SieveScriptVersion>,
+    // Seen ids seed carried over by the last rollback
+    pub seen_ids: Option<SeenIdsSeed>,
+}
+
+#[derive(
+    rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Default, Clone, PartialEq, Eq,
+)]
+#[rkyv(derive(Debug))]
+pub struct SeenIdsSeed {
+    pub archive_hash: u32,
+    pub seed: u32,
+}
+
+#[derive(
+    rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Default, Clone, PartialEq, Eq,
+)]
+#[rkyv(derive(Debug))]
+pub struct SieveScriptVersion {
+    pub blob_hash: BlobHash,
+    pub size: u32,
+    pub replaced_at: u64,
+}
+
 #[derive(
     rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Default, Clone, PartialEq, Eq,
 )]
@@ -43,6 +76,7 @@ pub struct VacationResponse {
     pub subject: Option<String>,
     pub text_body: Option<String>,
     pub html_body: Option<String>,
+    pub reply_once_per_list: bool,
 }
 
 impl SieveScript {
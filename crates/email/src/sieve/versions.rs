@@ -0,0 +1,139 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::{SieveScriptVersion, SieveScriptVersions};
+use common::Server;
+use store::{
+    Serialize, ValueKey,
+    write::{AlignedBytes, Archive, ArchiveVersion, Archiver, BatchBuilder, BlobLink, BlobOp, now},
+};
+use trc::AddContext;
+use types::{blob_hash::BlobHash, collection::Collection, field::SieveField};
+
+pub trait SieveScriptVersioning: Sync + Send {
+    fn sieve_script_versions(
+        &self,
+        account_id: u32,
+        document_id: u32,
+    ) -> impl Future<Output = trc::Result<SieveScriptVersions>> + Send;
+
+    fn sieve_script_keep_version(
+        &self,
+        account_id: u32,
+        document_id: u32,
+        previous: (BlobHash, u32),
+        current_hash: &BlobHash,
+        batch: &mut BatchBuilder,
+    ) -> impl Future<Output = trc::Result<()>> + Send;
+}
+
+impl SieveScriptVersioning for Server {
+    async fn sieve_script_versions(
+        &self,
+        account_id: u32,
+        document_id: u32,
+    ) -> trc::Result<SieveScriptVersions> {
+        if let Some(versions) = self
+            .store()
+            .get_value::<Archive<AlignedBytes>>(ValueKey::property(
+                account_id,
+                Collection::SieveScript,
+                document_id,
+                SieveField::Versions,
+            ))
+            .await
+            .caused_by(trc::location!())?
+        {
+            versions
+                .deserialize::<SieveScriptVersions>()
+                .caused_by(trc::location!())
+        } else {
+            Ok(SieveScriptVersions::default())
+        }
+    }
+
+    async fn sieve_script_keep_version(
+        &self,
+        account_id: u32,
+        document_id: u32,
+        previous: (BlobHash, u32),
+        current_hash: &BlobHash,
+        batch: &mut BatchBuilder,
+    ) -> trc::Result<()> {
+        let max_versions = self.core.jmap.sieve_max_versions;
+        if max_versions == 0 || &previous.0 == current_hash {
+            return Ok(());
+        }
+
+        let mut versions = self.sieve_script_versions(account_id, document_id).await?;
+        let evicted = versions.push(
+            SieveScriptVersion {
+                blob_hash: previous.0.clone(),
+                size: previous.1,
+                replaced_at: now(),
+            },
+            max_versions,
+        );
+
+        // The index builder unlinks the replaced blob, link it again so it is not purged
+        batch
+            .with_account_id(account_id)
+            .with_collection(Collection::SieveScript)
+            .with_document(document_id)
+            .set(
+                BlobOp::Link {
+                    hash: previous.0,
+                    to: BlobLink::Document,
+                },
+                Vec::new(),
+            )
+            .set(
+                SieveField::Versions,
+                Archiver::new(versions.clone())
+                    .serialize()
+                    .caused_by(trc::location!())?,
+            );
+        for version in evicted {
+            if &version.blob_hash != current_hash && !versions.contains(&version.blob_hash) {
+                batch.clear(BlobOp::Link {
+                    hash: version.blob_hash,
+                    to: BlobLink::Document,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SieveScriptVersions {
+    pub fn push(
+        &mut self,
+        version: SieveScriptVersion,
+        max_versions: usize,
+    ) -> Vec<SieveScriptVersion> {
+        self.versions.insert(0, version);
+        if self.versions.len() > max_versions {
+            self.versions.split_off(max_versions)
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn contains(&self, blob_hash: &BlobHash) -> bool {
+        self.versions.iter().any(|v| &v.blob_hash == blob_hash)
+    }
+
+    /// Returns the seed used to hash the seen ids of the script, which is the
+    /// archive hash unless a rollback carried the previous seed forward.
+    pub fn seen_ids_seed(&self, version: &ArchiveVersion) -> u32 {
+        let archive_hash = version.hash().unwrap_or_default();
+        self.seen_ids
+            .as_ref()
+            .filter(|seen_ids| seen_ids.archive_hash == archive_hash)
+            .map_or(archive_hash, |seen_ids| seen_ids.seed)
+    }
+}
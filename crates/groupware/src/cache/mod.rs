@@ -6,7 +6,7 @@
 
 use crate::{
     cache::calcard::{build_scheduling_resources, path_from_scheduling, resource_from_scheduling},
-    calendar::{Calendar, CalendarEvent, CalendarPreferences},
+    calendar::{Calendar, CalendarEvent, CalendarPreferences, SupportedComponent},
     contact::{AddressBook, AddressBookPreferences, ContactCard},
     file::FileNode,
 };
@@ -30,6 +30,7 @@ use types::{
     collection::{Collection, SyncCollection},
     field::PrincipalField,
 };
+use utils::map::bitmap::Bitmap;
 
 pub mod calcard;
 pub mod file;
@@ -390,6 +391,42 @@ impl GroupwareCache for Server {
                 .with_document(0)
                 .set(PrincipalField::DefaultCalendarId, document_id.serialize());
 
+            // Create default task list
+            if let Some(tasklist_name) = self
+                .core
+                .groupware
+                .default_tasklist_name
+                .as_ref()
+                .filter(|tasklist_name| *tasklist_name != name)
+            {
+                let document_id = self
+                    .store()
+                    .assign_document_ids(account_id, Collection::Calendar, 1)
+                    .await?;
+                Calendar {
+                    name: tasklist_name.clone(),
+                    preferences: vec![CalendarPreferences {
+                        account_id,
+                        name: format!(
+                            "{} ({})",
+                            self.core
+                                .groupware
+                                .default_tasklist_display_name
+                                .as_ref()
+                                .unwrap_or(tasklist_name),
+                            account_name
+                        ),
+                        ..Default::default()
+                    }],
+                    supported_components: Bitmap::<SupportedComponent>::from_iter([
+                        SupportedComponent::VTodo,
+                    ])
+                    .into_inner(),
+                    ..Default::default()
+                }
+                .insert(access_token, account_id, document_id, &mut batch)?;
+            }
+
             self.commit_batch(batch).await?;
             Ok(Some(document_id))
         } else {
@@ -11,7 +11,10 @@ use super::{
 use crate::calendar::{ComponentTimeRange, alarm::CalendarAlarmType};
 use calcard::{
     common::timezone::Tz,
-    icalendar::{ICalendar, ICalendarComponentType, dates::TimeOrDelta},
+    icalendar::{
+        ICalendar, ICalendarComponentType, ICalendarFrequency, ICalendarProperty,
+        ICalendarRecurrenceRule, ICalendarValue, dates::TimeOrDelta,
+    },
 };
 use compact_str::ToCompactString;
 use store::{
@@ -24,19 +27,37 @@ impl CalendarEventData {
         ical: ICalendar,
         default_tz: Tz,
         max_expansions: usize,
+        max_horizon: i64,
         next_email_alarm: &mut Option<CalendarAlarm>,
     ) -> Self {
         let mut ranges = TimeRanges::default();
         let now = now() as i64;
 
-        let expanded = ical.expand_dates(default_tz, max_expansions);
+        let expanded = ical.expand_dates(
+            default_tz,
+            horizon_expansions(&ical, max_expansions, max_horizon),
+        );
         let mut groups: AHashMap<(u16, u16, u16, i32), Vec<i64>> = AHashMap::with_capacity(16);
         let mut alarms = AHashMap::with_capacity(16);
 
+        // Instances starting past the horizon are discarded, counting from
+        // today or the first instance, whichever is later
+        let horizon = horizon_cutoff(
+            expanded
+                .events
+                .iter()
+                .map(|event| event.start.timestamp())
+                .min(),
+            max_horizon,
+        );
+
         for event in expanded.events {
+            let start_timestamp_utc = event.start.timestamp();
+            if start_timestamp_utc > horizon {
+                continue;
+            }
             let start_naive = event.start.naive_local();
             let start_tz = event.start.timezone().as_id();
-            let start_timestamp_utc = event.start.timestamp();
             let start_timestamp_naive = start_naive.and_utc().timestamp();
             let (end_timestamp_utc, end_timestamp_naive, end_tz) = match event.end {
                 TimeOrDelta::Time(time) => {
@@ -284,3 +305,103 @@ impl ArchivedTimezone {
         }
     }
 }
+
+/// Returns the latest start time an instance can have, `max_horizon` seconds
+/// after today or the first instance, whichever is later
+pub fn horizon_cutoff(first_start: Option<i64>, max_horizon: i64) -> i64 {
+    let now = now() as i64;
+    first_start
+        .map_or(now, |first| first.max(now))
+        .saturating_add(max_horizon)
+}
+
+/// Caps `max_expansions` to the number of instances that can start before the
+/// horizon, so rules that recur often are not expanded past it
+pub fn horizon_expansions(ical: &ICalendar, max_expansions: usize, max_horizon: i64) -> usize {
+    let mut first_start: Option<i64> = None;
+    let mut num_instances = 0usize;
+    let mut rules = Vec::new();
+
+    for comp in ical
+        .components
+        .iter()
+        .filter(|comp| comp.component_type.has_time_ranges())
+    {
+        for entry in &comp.entries {
+            match (&entry.name, entry.values.first()) {
+                (ICalendarProperty::Dtstart, Some(ICalendarValue::PartialDateTime(date))) => {
+                    if let Some(start) = date.to_timestamp() {
+                        first_start = Some(first_start.map_or(start, |first| first.min(start)));
+                        num_instances += 1;
+                    }
+                }
+                (ICalendarProperty::Rrule, Some(ICalendarValue::RecurrenceRule(rule))) => {
+                    rules.push(rule);
+                }
+                (ICalendarProperty::Rdate, _) => {
+                    num_instances += entry.values.len();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let Some(first_start) = first_start else {
+        return max_expansions;
+    };
+
+    // Leave a day of slack for start times in local time
+    let span = first_start
+        .max(now() as i64)
+        .saturating_add(max_horizon)
+        .saturating_sub(first_start)
+        .saturating_add(86400);
+    for rule in rules {
+        num_instances = num_instances.saturating_add(rule_max_instances(rule, span));
+        if num_instances >= max_expansions {
+            return max_expansions;
+        }
+    }
+
+    num_instances.min(max_expansions)
+}
+
+// Upper bound of the instances a rule produces within `span` seconds
+fn rule_max_instances(rule: &ICalendarRecurrenceRule, span: i64) -> usize {
+    let period = match rule.freq {
+        ICalendarFrequency::Secondly => 1,
+        ICalendarFrequency::Minutely => 60,
+        ICalendarFrequency::Hourly => 3600,
+        ICalendarFrequency::Daily => 86400,
+        ICalendarFrequency::Weekly => 7 * 86400,
+        ICalendarFrequency::Monthly => 28 * 86400,
+        ICalendarFrequency::Yearly => 365 * 86400,
+    } * (rule.interval.unwrap_or(1).max(1) as i64);
+
+    // BYxxx rules can place several instances within a period, no closer
+    // than their own granularity
+    let min_gap = if !rule.bysecond.is_empty() {
+        1
+    } else if !rule.byminute.is_empty() {
+        60
+    } else if !rule.byhour.is_empty() {
+        3600
+    } else if !rule.byday.is_empty()
+        || !rule.bymonthday.is_empty()
+        || !rule.byyearday.is_empty()
+        || !rule.byweekno.is_empty()
+    {
+        86400
+    } else if !rule.bymonth.is_empty() {
+        28 * 86400
+    } else {
+        period
+    }
+    .min(period);
+
+    let max_instances = usize::try_from(span / min_gap)
+        .unwrap_or(usize::MAX)
+        .saturating_add(1);
+    rule.count
+        .map_or(max_instances, |count| max_instances.min(count as usize))
+}
@@ -5,7 +5,7 @@
  */
 
 use super::ArchivedCalendarEventData;
-use crate::calendar::CalendarEventData;
+use crate::calendar::{CalendarEventData, dates::horizon_cutoff};
 use ahash::AHashSet;
 use calcard::common::timezone::Tz;
 use chrono::{DateTime, TimeZone};
@@ -22,9 +22,15 @@ pub struct CalendarEventExpansion {
 }
 
 impl ArchivedCalendarEventData {
-    pub fn expand(&self, default_tz: Tz, limit: TimeRange) -> Option<Vec<CalendarEventExpansion>> {
+    pub fn expand(
+        &self,
+        default_tz: Tz,
+        limit: TimeRange,
+        max_horizon: i64,
+    ) -> Option<Vec<CalendarEventExpansion>> {
         let mut expansion = Vec::with_capacity(self.time_ranges.len());
         let base_offset = self.base_offset.to_native();
+        let horizon = horizon_cutoff(Some(self.event_range_start()), max_horizon);
         let mut base_expansion_id = 0;
 
         'outer: for range in self.time_ranges.iter() {
@@ -68,7 +74,9 @@ impl ArchivedCalendarEventData {
                         .single()?
                         .timestamp();
 
-                    if limit.is_in_range(is_todo, start, end) {
+                    if start > horizon {
+                        continue 'outer;
+                    } else if limit.is_in_range(is_todo, start, end) {
                         expansion.push(CalendarEventExpansion {
                             comp_id,
                             expansion_id,
@@ -12,13 +12,14 @@ use crate::{
         EVENT_NOTIFICATION_IS_CHANGE,
     },
     scheduling::{
-        ItipError, ItipMessage,
+        ItipError, ItipLimits, ItipMessage,
         inbound::{
             MergeResult, itip_import_message, itip_merge_changes, itip_method, itip_process_message,
         },
         snapshot::itip_snapshot,
     },
 };
+use ahash::AHashSet;
 use calcard::{
     common::{IanaString, timezone::Tz},
     icalendar::{
@@ -33,6 +34,7 @@ use common::{
     config::groupware::CalendarTemplateVariable,
     i18n,
 };
+use std::borrow::Cow;
 use store::{
     ValueKey, rand,
     write::{AlignedBytes, Archive, BatchBuilder, now},
@@ -134,7 +136,41 @@ impl ItipIngest for Server {
             }
         }
 
-        let itip_snapshots = itip_snapshot(&itip, access_token.emails.as_slice(), false)?;
+        let account_id = access_token.primary_id;
+        let mut local_addresses = Cow::Borrowed(access_token.emails.as_slice());
+        let itip_snapshots = match itip_snapshot(
+            &itip,
+            &local_addresses,
+            ItipLimits::from(&self.core.groupware),
+            Some(&self.core.groupware.itip_attendee_aliases),
+            false,
+        ) {
+            Err(ItipError::NotOrganizerNorAttendee) => {
+                // The limits have been checked at this point, look for aliases of this
+                // account in the directory (catch-all addresses are not considered)
+                for address in calendar_addresses(&itip) {
+                    if !local_addresses.contains(&address)
+                        && self
+                            .directory()
+                            .email_to_id(&address)
+                            .await
+                            .caused_by(trc::location!())?
+                            == Some(account_id)
+                    {
+                        local_addresses.to_mut().push(address);
+                    }
+                }
+
+                itip_snapshot(
+                    &itip,
+                    &local_addresses,
+                    ItipLimits::from(&self.core.groupware),
+                    Some(&self.core.groupware.itip_attendee_aliases),
+                    false,
+                )
+            }
+            result => result,
+        }?;
         if !itip_snapshots.sender_is_organizer_or_attendee(sender) {
             return Err(ItipIngestError::Message(
                 ItipError::SenderIsNotOrganizerNorAttendee,
@@ -149,7 +185,6 @@ impl ItipIngest for Server {
         };
 
         // Find event by UID
-        let account_id = access_token.primary_id;
         let document_id = self
             .document_ids_matching(
                 account_id,
@@ -181,8 +216,13 @@ impl ItipIngest for Server {
                     .caused_by(trc::location!())?;
 
                 // Process the iTIP message
-                let snapshots =
-                    itip_snapshot(&event.data.event, access_token.emails.as_slice(), false)?;
+                let snapshots = itip_snapshot(
+                    &event.data.event,
+                    &local_addresses,
+                    ItipLimits::UNLIMITED,
+                    Some(&self.core.groupware.itip_attendee_aliases),
+                    false,
+                )?;
                 let is_organizer_update = !itip_snapshots.organizer.email.is_local;
                 match itip_process_message(
                     &event.data.event,
@@ -197,7 +237,12 @@ impl ItipIngest for Server {
 
                         // Calculate the new ical size
                         event.size = event.data.event.to_string().len() as u32;
-                        if event.size > self.core.groupware.max_ical_size as u32 {
+                        if event.size
+                            > self
+                                .ical_size_limit(account_id)
+                                .await
+                                .caused_by(trc::location!())? as u32
+                        {
                             return Err(ItipIngestError::Message(ItipError::EventTooLarge));
                         }
 
@@ -221,6 +266,7 @@ impl ItipIngest for Server {
                             event.data.event,
                             Tz::Floating,
                             self.core.groupware.max_ical_instances,
+                            self.core.groupware.max_ical_horizon,
                             &mut next_email_alarm,
                         );
                         if is_organizer_update {
@@ -329,6 +375,7 @@ impl ItipIngest for Server {
                     ical,
                     Tz::Floating,
                     self.core.groupware.max_ical_instances,
+                    self.core.groupware.max_ical_horizon,
                     &mut next_email_alarm,
                 ),
                 size: itip_message.len() as u32,
@@ -571,6 +618,29 @@ enum Response {
     NoLongerParticipant,
 }
 
+fn calendar_addresses(ical: &ICalendar) -> AHashSet<String> {
+    let mut addresses = AHashSet::new();
+    for comp in &ical.components {
+        if comp.component_type.is_scheduling_object() {
+            for entry in &comp.entries {
+                if matches!(
+                    entry.name,
+                    ICalendarProperty::Organizer | ICalendarProperty::Attendee
+                ) {
+                    for value in entry.values.iter().filter_map(|v| v.as_text()) {
+                        let address = value.trim().to_lowercase();
+                        let address = address.strip_prefix("mailto:").unwrap_or(&address);
+                        if address.contains('@') {
+                            addresses.insert(address.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    addresses
+}
+
 fn render_response(server: &Server, response: Response, language: &str) -> String {
     // SPDX-SnippetBegin
     // SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
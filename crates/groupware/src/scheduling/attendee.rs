@@ -98,10 +98,18 @@ pub(crate) fn attendee_handle_update(
                         }
                     }
 
-                    // Send participation status update
+                    // Send participation status update, organizers never reply to themselves
                     if local_attendee.is_server_scheduling
+                        && !local_attendee.is_organizer
                         && ((local_attendee.part_stat != old_local_attendee.part_stat)
-                            || local_attendee.force_send.is_some()
+                            || instance.should_force_send(
+                                &new_itip.organizer,
+                                &local_attendee.email.email,
+                            )
+                            || instance.should_force_send(
+                                &new_itip.organizer,
+                                &new_itip.organizer.email.email,
+                            )
                             || send_update)
                     {
                         // Build the attendee list
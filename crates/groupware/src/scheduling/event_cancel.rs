@@ -5,7 +5,7 @@
  */
 
 use crate::scheduling::{
-    InstanceId, ItipError, ItipMessage, ItipSummary,
+    InstanceId, ItipError, ItipLimits, ItipMessage, ItipSummary,
     attendee::attendee_decline,
     itip::{itip_add_tz, itip_build_envelope},
     snapshot::itip_snapshot,
@@ -24,8 +24,8 @@ pub fn itip_cancel(
     account_emails: &[String],
     is_deletion: bool,
 ) -> Result<ItipMessage<ICalendar>, ItipError> {
-    // Prepare iTIP message
-    let itip = itip_snapshot(ical, account_emails, false)?;
+    // Prepare iTIP message, stored events were already validated against the limits
+    let itip = itip_snapshot(ical, account_emails, ItipLimits::UNLIMITED, None, false)?;
     let dt_stamp = PartialDateTime::now();
     let mut message = ICalendar {
         components: Vec::with_capacity(2),
@@ -5,7 +5,7 @@
  */
 
 use crate::scheduling::{
-    ItipError, ItipMessage, itip::itip_finalize, organizer::organizer_request_full,
+    ItipError, ItipLimits, ItipMessage, itip::itip_finalize, organizer::organizer_request_full,
     snapshot::itip_snapshot,
 };
 use calcard::icalendar::ICalendar;
@@ -13,8 +13,9 @@ use calcard::icalendar::ICalendar;
 pub fn itip_create(
     ical: &mut ICalendar,
     account_emails: &[String],
+    limits: ItipLimits,
 ) -> Result<Vec<ItipMessage<ICalendar>>, ItipError> {
-    let itip = itip_snapshot(ical, account_emails, false)?;
+    let itip = itip_snapshot(ical, account_emails, limits, None, false)?;
     if !itip.organizer.is_server_scheduling {
         Err(ItipError::OtherSchedulingAgent)
     } else if !itip.organizer.email.is_local {
@@ -5,8 +5,9 @@
  */
 
 use crate::scheduling::{
-    ItipError, ItipMessage, attendee::attendee_handle_update, event_cancel::itip_cancel,
-    itip::itip_finalize, organizer::organizer_handle_update, snapshot::itip_snapshot,
+    ItipError, ItipLimits, ItipMessage, attendee::attendee_handle_update,
+    event_cancel::itip_cancel, itip::itip_finalize, organizer::organizer_handle_update,
+    snapshot::itip_snapshot,
 };
 use calcard::icalendar::ICalendar;
 
@@ -14,9 +15,10 @@ pub fn itip_update(
     ical: &mut ICalendar,
     old_ical: &ICalendar,
     account_emails: &[String],
+    limits: ItipLimits,
 ) -> Result<Vec<ItipMessage<ICalendar>>, ItipError> {
-    let old_itip = itip_snapshot(old_ical, account_emails, false)?;
-    match itip_snapshot(ical, account_emails, false) {
+    let old_itip = itip_snapshot(old_ical, account_emails, ItipLimits::UNLIMITED, None, false)?;
+    match itip_snapshot(ical, account_emails, limits, None, false) {
         Ok(new_itip) => {
             let mut sequences = Vec::new();
             if old_itip.organizer.email != new_itip.organizer.email {
@@ -36,7 +38,8 @@ pub fn itip_update(
                 ItipError::NoSchedulingInfo
                 | ItipError::NotOrganizer
                 | ItipError::NotOrganizerNorAttendee
-                | ItipError::OtherSchedulingAgent => {
+                | ItipError::OtherSchedulingAgent
+                | ItipError::SchedulingDisabled => {
                     if old_itip.organizer.email.is_local {
                         // RFC 6638 does not support replacing the organizer, so we cancel the event
                         itip_cancel(old_ical, account_emails, false).map(|message| vec![message])
@@ -398,7 +398,12 @@ fn handle_reply(
                     if has_request_status {
                         remove_parameters.push(ICalendarParameterName::ScheduleStatus);
                         add_parameters.push(ICalendarParameter::schedule_status(
-                            itip_snapshot.request_status.join(","),
+                            itip_snapshot
+                                .request_status
+                                .iter()
+                                .map(|status| status.code.as_str())
+                                .collect::<Vec<_>>()
+                                .join(","),
                         ));
                     }
 
@@ -14,7 +14,8 @@ use calcard::{
         ICalendarStatus, ICalendarUserTypes, ICalendarValue, Uri,
     },
 };
-use std::{fmt::Display, hash::Hash};
+use common::config::groupware::GroupwareConfig;
+use std::{collections::BTreeSet, fmt::Display, hash::Hash};
 
 pub mod attendee;
 pub mod event_cancel;
@@ -32,6 +33,13 @@ pub struct ItipSnapshots<'x> {
     pub components: AHashMap<InstanceId, ItipSnapshot<'x>>,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct ItipLimits {
+    pub max_attendees_per_instance: usize,
+    pub max_overrides: usize,
+    pub validate_timezones: bool,
+}
+
 #[derive(Debug)]
 pub struct ItipSnapshot<'x> {
     pub comp_id: u16,
@@ -40,7 +48,56 @@ pub struct ItipSnapshot<'x> {
     pub dtstamp: Option<&'x PartialDateTime>,
     pub entries: AHashSet<ItipEntry<'x>>,
     pub sequence: Option<i64>,
-    pub request_status: Vec<&'x str>,
+    pub status: Option<ICalendarStatus>,
+    pub end: Option<i64>,
+    pub request_status: Vec<RequestStatus>,
+    pub unresolved_attendees: Vec<UnresolvedAttendee<'x>>,
+    pub alarms: Vec<ItipAlarm<'x>>,
+    pub tz_warnings: Vec<ItipTzWarning<'x>>,
+}
+
+// An embedded VTIMEZONE that resolves a date differently than the IANA zone with the same TZID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItipTzWarning<'x> {
+    pub name: &'x ICalendarProperty,
+    pub tz_id: &'x str,
+    pub timestamp: i64,
+    pub iana_timestamp: i64,
+}
+
+#[derive(Debug)]
+pub struct ItipAlarm<'x> {
+    pub comp_id: u16,
+    pub trigger: ItipAlarmTrigger<'x>,
+    pub action: Option<&'x str>,
+}
+
+#[derive(Debug)]
+pub enum ItipAlarmTrigger<'x> {
+    Relative {
+        duration: &'x ICalendarDuration,
+        related_end: bool,
+    },
+    Absolute(ItipDateTime<'x>),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RecurrenceSet {
+    pub dates: BTreeSet<i64>,
+}
+
+#[derive(Debug)]
+pub struct UnresolvedAttendee<'x> {
+    pub entry_id: u16,
+    pub name: Option<&'x str>,
+    pub value: Option<&'x str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestStatus {
+    pub code: String,
+    pub description: String,
+    pub extra: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -57,6 +114,7 @@ pub enum ItipEntryValue<'x> {
     Status(&'x ICalendarStatus),
     RRule(&'x ICalendarRecurrenceRule),
     Text(&'x str),
+    Uri(&'x str),
     Integer(i64),
 }
 
@@ -68,13 +126,13 @@ pub struct ItipDateTime<'x> {
     pub timestamp: i64,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum InstanceId {
     Main,
     Recurrence(RecurrenceId),
 }
 
-#[derive(Debug, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialOrd, Ord)]
 pub struct RecurrenceId {
     pub entry_id: u16,
     pub date: i64,
@@ -94,14 +152,26 @@ pub struct Attendee<'x> {
     pub sent_by: Option<Email>,
     pub rsvp: Option<bool>,
     pub is_server_scheduling: bool,
+    pub is_organizer: bool,
     pub force_send: Option<&'x ICalendarScheduleForceSendValue>,
 }
 
+// Attendee counts by participation status, other statuses are counted under `other`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PartStatCounts {
+    pub accepted: usize,
+    pub declined: usize,
+    pub tentative: usize,
+    pub needs_action: usize,
+    pub other: usize,
+}
+
 #[derive(Debug)]
 pub struct Organizer<'x> {
     pub entry_id: u16,
     pub email: Email,
     pub name: Option<&'x str>,
+    pub sent_by: Option<Email>,
     pub is_server_scheduling: bool,
     pub force_send: Option<&'x ICalendarScheduleForceSendValue>,
 }
@@ -116,12 +186,13 @@ pub struct Email {
 pub enum ItipError {
     NoSchedulingInfo,
     OtherSchedulingAgent,
+    SchedulingDisabled,
     NotOrganizer,
     NotOrganizerNorAttendee,
     NothingToSend,
     MissingUid,
-    MultipleUid,
-    MultipleOrganizer,
+    MultipleUid { found: String, existing: String },
+    MultipleOrganizer { found: String, existing: String },
     MultipleObjectTypes,
     MultipleObjectInstances,
     CannotModifyProperty(ICalendarProperty),
@@ -142,6 +213,8 @@ pub enum ItipError {
     QuotaExceeded,
     NoDefaultCalendar,
     AutoAddDisabled,
+    TooManyAttendees,
+    TooManyOverrides,
 }
 
 #[derive(Debug, rkyv::Archive, rkyv::Deserialize, rkyv::Serialize)]
@@ -234,10 +307,32 @@ impl Attendee<'_> {
     }
 }
 
+impl RequestStatus {
+    pub fn class(&self) -> Option<u8> {
+        self.code
+            .split_once('.')
+            .map_or(self.code.as_str(), |(class, _)| class)
+            .parse()
+            .ok()
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.class() == Some(2)
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(self.class(), Some(3..=5))
+    }
+}
+
 impl Email {
     pub fn new(email: &str, local_addresses: &[String]) -> Option<Self> {
         email.contains('@').then(|| {
-            let email = email.trim().trim_start_matches("mailto:").to_lowercase();
+            let email = email.trim().to_lowercase();
+            let email = match email.strip_prefix("mailto:") {
+                Some(email) => email.to_string(),
+                None => email,
+            };
             let is_local = local_addresses.contains(&email);
             Email { email, is_local }
         })
@@ -343,7 +438,7 @@ impl ItipError {
     pub fn is_jmap_error(&self) -> bool {
         matches!(
             self,
-            ItipError::MultipleOrganizer
+            ItipError::MultipleOrganizer { .. }
                 | ItipError::OrganizerIsLocalAddress
                 | ItipError::SenderIsNotParticipant(_)
                 | ItipError::OrganizerMismatch
@@ -351,7 +446,7 @@ impl ItipError {
                 | ItipError::CannotModifyInstance
                 | ItipError::CannotModifyAddress
                 //| ItipError::MissingUid
-                | ItipError::MultipleUid
+                | ItipError::MultipleUid { .. }
                 | ItipError::MultipleObjectTypes
                 | ItipError::MultipleObjectInstances
                 | ItipError::MissingMethod
@@ -359,23 +454,66 @@ impl ItipError {
                 | ItipError::OutOfSequence
                 | ItipError::UnknownParticipant(_)
                 | ItipError::UnsupportedMethod(_)
+                | ItipError::TooManyAttendees
+                | ItipError::TooManyOverrides
         )
     }
 }
 
+impl RecurrenceSet {
+    pub fn contains(&self, date: i64) -> bool {
+        self.dates.contains(&date)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        self.dates.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+}
+
+impl ItipLimits {
+    pub const UNLIMITED: ItipLimits = ItipLimits {
+        max_attendees_per_instance: usize::MAX,
+        max_overrides: usize::MAX,
+        validate_timezones: false,
+    };
+}
+
+impl From<&GroupwareConfig> for ItipLimits {
+    fn from(config: &GroupwareConfig) -> Self {
+        ItipLimits {
+            max_attendees_per_instance: config.max_ical_attendees_per_instance,
+            max_overrides: config.max_ical_overrides,
+            validate_timezones: config.itip_validate_timezones,
+        }
+    }
+}
+
 impl Display for ItipError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ItipError::NoSchedulingInfo => write!(f, "No scheduling information found"),
             ItipError::OtherSchedulingAgent => write!(f, "Other scheduling agent"),
+            ItipError::SchedulingDisabled => write!(f, "Scheduling is disabled for this object"),
             ItipError::NotOrganizer => write!(f, "Not the organizer of the event"),
             ItipError::NotOrganizerNorAttendee => write!(f, "Not an organizer or attendee"),
             ItipError::NothingToSend => write!(f, "No iTIP messages to send"),
             ItipError::MissingUid => write!(f, "Missing UID in iCalendar object"),
-            ItipError::MultipleUid => write!(f, "Multiple UIDs found in iCalendar object"),
-            ItipError::MultipleOrganizer => {
-                write!(f, "Multiple organizers found in iCalendar object")
-            }
+            ItipError::MultipleUid { found, existing } => write!(
+                f,
+                "Multiple UIDs found in iCalendar object: {found:?} conflicts with {existing:?}"
+            ),
+            ItipError::MultipleOrganizer { found, existing } => write!(
+                f,
+                "Multiple organizers found in iCalendar object: {found:?} conflicts with {existing:?}"
+            ),
             ItipError::MultipleObjectTypes => {
                 write!(f, "Multiple object types found in iCalendar object")
             }
@@ -422,6 +560,14 @@ impl Display for ItipError {
             ItipError::AutoAddDisabled => {
                 write!(f, "Auto-adding events is disabled for this account")
             }
+            ItipError::TooManyAttendees => {
+                write!(f, "Too many attendees in a single event instance")
+            }
+            ItipError::TooManyOverrides => {
+                write!(f, "Too many recurrence overrides in iCalendar object")
+            }
         }
     }
 }
+
+impl std::error::Error for ItipError {}
@@ -14,7 +14,7 @@ use calcard::{
     common::PartialDateTime,
     icalendar::{
         ICalendar, ICalendarComponent, ICalendarComponentType, ICalendarMethod,
-        ICalendarParticipationStatus, ICalendarProperty, ICalendarStatus,
+        ICalendarParticipationStatus, ICalendarProperty,
     },
 };
 use std::collections::hash_map::Entry;
@@ -88,7 +88,7 @@ pub(crate) fn organizer_handle_update(
             }
         } else if instance_id != &InstanceId::Main {
             changed_properties.insert(&ICalendarProperty::Exdate);
-            let method = if matches!(instance.comp.status(), Some(ICalendarStatus::Cancelled)) {
+            let method = if instance.is_cancelled() {
                 &ICalendarMethod::Cancel
             } else {
                 &ICalendarMethod::Add
@@ -4,19 +4,48 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use crate::scheduling::{
-    Attendee, Email, InstanceId, ItipDateTime, ItipEntry, ItipEntryValue, ItipError, ItipField,
-    ItipParticipant, ItipSnapshot, ItipSnapshots, ItipTime, ItipValue, Organizer, RecurrenceId,
+use crate::{
+    calendar::dates::{horizon_cutoff, horizon_expansions},
+    scheduling::{
+        Attendee, Email, InstanceId, ItipAlarm, ItipAlarmTrigger, ItipDateTime, ItipEntry,
+        ItipEntryValue, ItipError, ItipField, ItipLimits, ItipParticipant, ItipSnapshot,
+        ItipSnapshots, ItipTime, ItipTzWarning, ItipValue, Organizer, PartStatCounts, RecurrenceId,
+        RecurrenceSet, RequestStatus, UnresolvedAttendee,
+    },
 };
 use ahash::AHashMap;
-use calcard::icalendar::{
-    ICalendar, ICalendarParameterName, ICalendarParameterValue, ICalendarProperty,
-    ICalendarScheduleAgentValue, ICalendarValue, Uri,
+use calcard::{
+    common::timezone::Tz,
+    icalendar::{
+        ICalendar, ICalendarComponentType, ICalendarEntry, ICalendarParameterName,
+        ICalendarParameterValue, ICalendarParticipationStatus, ICalendarProperty, ICalendarRelated,
+        ICalendarScheduleAgentValue, ICalendarStatus, ICalendarValue, Uri,
+    },
 };
+use std::{collections::BTreeMap, str::FromStr};
+
+// Assumed length of instances without an end or a duration to inherit
+const DEFAULT_EVENT_DURATION: i64 = 3600;
+const DEFAULT_ALL_DAY_DURATION: i64 = 86400;
+
+impl<'x> ItipSnapshots<'x> {
+    // The parsed calendar is stored in `ical` as the snapshots borrow from it
+    pub fn from_ics(
+        data: &str,
+        ical: &'x mut Option<ICalendar>,
+        account_emails: &[String],
+        limits: ItipLimits,
+    ) -> Result<Self, ItipError> {
+        let ical = ical.insert(ICalendar::parse(data).map_err(|_| ItipError::ICalendarParseError)?);
+        itip_snapshot(ical, account_emails, limits, None, false)
+    }
+}
 
 pub fn itip_snapshot<'x, 'y>(
     ical: &'x ICalendar,
     account_emails: &'y [String],
+    limits: ItipLimits,
+    aliases: Option<&AHashMap<String, String>>,
     force_add_client_scheduling: bool,
 ) -> Result<ItipSnapshots<'x>, ItipError> {
     if !ical.components.iter().any(|comp| {
@@ -55,7 +84,12 @@ pub fn itip_snapshot<'x, 'y>(
                 dtstamp: Default::default(),
                 entries: Default::default(),
                 sequence: Default::default(),
+                status: Default::default(),
+                end: Default::default(),
                 request_status: Default::default(),
+                unresolved_attendees: Default::default(),
+                alarms: Default::default(),
+                tz_warnings: Default::default(),
             };
             let mut instance_id = InstanceId::Main;
 
@@ -73,20 +107,20 @@ pub fn itip_snapshot<'x, 'y>(
                                 email,
                                 is_server_scheduling: true,
                                 name: None,
+                                sent_by: None,
                                 force_send: None,
                             };
-                            has_local_emails |= part.email.is_local;
+                            let mut is_scheduling_disabled = false;
 
                             for param in &entry.params {
                                 match (&param.name, &param.value) {
                                     (
                                         ICalendarParameterName::ScheduleAgent,
-                                        ICalendarParameterValue::ScheduleAgent(
-                                            ICalendarScheduleAgentValue::Client
-                                            | ICalendarScheduleAgentValue::None,
-                                        ),
-                                    ) => {
+                                        ICalendarParameterValue::ScheduleAgent(agent),
+                                    ) if agent != &ICalendarScheduleAgentValue::Server => {
                                         part.is_server_scheduling = false;
+                                        is_scheduling_disabled =
+                                            agent == &ICalendarScheduleAgentValue::None;
                                     }
                                     (
                                         ICalendarParameterName::ScheduleForceSend,
@@ -100,19 +134,46 @@ pub fn itip_snapshot<'x, 'y>(
                                     ) => {
                                         part.name = Some(name.as_str());
                                     }
+                                    (
+                                        ICalendarParameterName::SentBy,
+                                        ICalendarParameterValue::Uri(value),
+                                    ) => {
+                                        part.sent_by = Email::from_uri(value, account_emails);
+                                    }
                                     _ => {}
                                 }
                             }
 
+                            has_local_emails |= part.email.is_local
+                                || part.sent_by.as_ref().is_some_and(|email| email.is_local);
+
                             if !part.is_server_scheduling && !force_add_client_scheduling {
-                                return Err(ItipError::OtherSchedulingAgent);
+                                return Err(if is_scheduling_disabled {
+                                    ItipError::SchedulingDisabled
+                                } else {
+                                    ItipError::OtherSchedulingAgent
+                                });
                             }
 
-                            match organizer {
+                            // A delegate acting through SENT-BY is the same logical organizer
+                            let sent_by_matches = |org: &Organizer<'_>, other: &Organizer<'_>| {
+                                org.sent_by
+                                    .as_ref()
+                                    .is_some_and(|sent_by| sent_by.email == other.email.email)
+                            };
+                            match &organizer {
                                 Some(existing_organizer)
                                     if existing_organizer.email.email != part.email.email =>
                                 {
-                                    return Err(ItipError::MultipleOrganizer);
+                                    if sent_by_matches(&part, existing_organizer) {
+                                        // Prefer the mailbox being represented by the delegate
+                                        organizer = Some(part);
+                                    } else if !sent_by_matches(existing_organizer, &part) {
+                                        return Err(ItipError::MultipleOrganizer {
+                                            found: part.email.email,
+                                            existing: existing_organizer.email.email.clone(),
+                                        });
+                                    }
                                 }
                                 None => {
                                     organizer = Some(part);
@@ -122,18 +183,14 @@ pub fn itip_snapshot<'x, 'y>(
                         }
                     }
                     ICalendarProperty::Attendee => {
-                        if let Some(email) = entry
-                            .values
-                            .first()
-                            .and_then(|v| v.as_text())
-                            .and_then(|v| Email::new(v, account_emails))
-                        {
+                        if let Some(email) = attendee_email(entry, account_emails, aliases) {
                             let mut part = Attendee {
                                 entry_id: entry_id as u16,
                                 email,
                                 name: None,
                                 rsvp: None,
                                 is_server_scheduling: true,
+                                is_organizer: false,
                                 force_send: None,
                                 part_stat: None,
                                 delegated_from: vec![],
@@ -218,6 +275,15 @@ pub fn itip_snapshot<'x, 'y>(
                                 && (force_add_client_scheduling || part.is_server_scheduling);
 
                             sched_comp.attendees.insert(part);
+                            if sched_comp.attendees.len() > limits.max_attendees_per_instance {
+                                return Err(ItipError::TooManyAttendees);
+                            }
+                        } else {
+                            sched_comp.unresolved_attendees.push(UnresolvedAttendee {
+                                entry_id: entry_id as u16,
+                                name: attendee_name(entry),
+                                value: entry.values.first().and_then(|v| v.as_text()),
+                            });
                         }
                     }
                     ICalendarProperty::Uid => {
@@ -230,7 +296,10 @@ pub fn itip_snapshot<'x, 'y>(
                         {
                             match uid {
                                 Some(existing_uid) if existing_uid != uid_ => {
-                                    return Err(ItipError::MultipleUid);
+                                    return Err(ItipError::MultipleUid {
+                                        found: uid_.to_string(),
+                                        existing: existing_uid.to_string(),
+                                    });
                                 }
                                 None => {
                                     uid = Some(uid_);
@@ -281,14 +350,33 @@ pub fn itip_snapshot<'x, 'y>(
                         }
                     }
                     ICalendarProperty::RequestStatus => {
-                        if let Some(value) = entry.values.first().and_then(|v| v.as_text()) {
-                            sched_comp.request_status.push(value);
+                        let mut values = entry.values.iter().filter_map(|v| v.as_text());
+                        if let Some(code) =
+                            values.next().map(|v| v.trim()).filter(|v| !v.is_empty())
+                        {
+                            sched_comp.request_status.push(RequestStatus {
+                                code: code.to_string(),
+                                description: values.next().unwrap_or_default().to_string(),
+                                extra: values.next().map(|v| v.to_string()),
+                            });
                         }
                     }
                     ICalendarProperty::Dtstamp => {
                         sched_comp.dtstamp =
                             entry.values.first().and_then(|v| v.as_partial_date_time());
                     }
+                    ICalendarProperty::Status => {
+                        // Also added to the entries so status changes are detected
+                        for value in &entry.values {
+                            if let ICalendarValue::Status(status) = value {
+                                sched_comp.status = Some(status.clone());
+                                sched_comp.entries.insert(ItipEntry {
+                                    name: &entry.name,
+                                    value: ItipEntryValue::Status(status),
+                                });
+                            }
+                        }
+                    }
                     ICalendarProperty::Dtstart
                     | ICalendarProperty::Dtend
                     | ICalendarProperty::Duration
@@ -296,16 +384,24 @@ pub fn itip_snapshot<'x, 'y>(
                     | ICalendarProperty::Rrule
                     | ICalendarProperty::Rdate
                     | ICalendarProperty::Exdate
-                    | ICalendarProperty::Status
                     | ICalendarProperty::Location
                     | ICalendarProperty::Summary
                     | ICalendarProperty::Description
+                    | ICalendarProperty::Contact
+                    | ICalendarProperty::Attach
                     | ICalendarProperty::Priority
                     | ICalendarProperty::PercentComplete
                     | ICalendarProperty::Completed => {
                         let tz_id = entry.tz_id();
                         for value in &entry.values {
+                            // Inline ATTACH data is not part of the snapshot
                             let value = match value {
+                                ICalendarValue::Uri(Uri::Location(v))
+                                    if entry.name == ICalendarProperty::Attach =>
+                                {
+                                    ItipEntryValue::Uri(v.as_str())
+                                }
+                                _ if entry.name == ICalendarProperty::Attach => continue,
                                 ICalendarValue::Uri(Uri::Location(v)) => {
                                     ItipEntryValue::Text(v.as_str())
                                 }
@@ -313,16 +409,34 @@ pub fn itip_snapshot<'x, 'y>(
                                     let tz = tz_resolver
                                         .get_or_insert_with(|| ical.build_tz_resolver())
                                         .resolve_or_default(tz_id);
+                                    let tz_code = tz.as_id();
+                                    let timestamp = date
+                                        .to_date_time_with_tz(tz)
+                                        .map(|dt| dt.timestamp())
+                                        .unwrap_or_else(|| date.to_timestamp().unwrap_or_default());
+
+                                    if limits.validate_timezones
+                                        && let Some(tz_id) = tz_id
+                                        && let Some(iana_timestamp) = Tz::from_str(tz_id)
+                                            .ok()
+                                            .filter(|iana| iana.as_id() != tz_code)
+                                            .and_then(|iana| date.to_date_time_with_tz(iana))
+                                            .map(|dt| dt.timestamp())
+                                            .filter(|iana_timestamp| *iana_timestamp != timestamp)
+                                    {
+                                        sched_comp.tz_warnings.push(ItipTzWarning {
+                                            name: &entry.name,
+                                            tz_id,
+                                            timestamp,
+                                            iana_timestamp,
+                                        });
+                                    }
+
                                     ItipEntryValue::DateTime(ItipDateTime {
                                         date: date.as_ref(),
                                         tz_id,
-                                        tz_code: tz.as_id(),
-                                        timestamp: date
-                                            .to_date_time_with_tz(tz)
-                                            .map(|dt| dt.timestamp())
-                                            .unwrap_or_else(|| {
-                                                date.to_timestamp().unwrap_or_default()
-                                            }),
+                                        tz_code,
+                                        timestamp,
                                     })
                                 }
                                 ICalendarValue::Duration(v) => ItipEntryValue::Duration(v),
@@ -330,7 +444,6 @@ pub fn itip_snapshot<'x, 'y>(
                                 ICalendarValue::Period(v) => ItipEntryValue::Period(v),
                                 ICalendarValue::Integer(v) => ItipEntryValue::Integer(*v),
                                 ICalendarValue::Text(v) => ItipEntryValue::Text(v.as_str()),
-                                ICalendarValue::Status(v) => ItipEntryValue::Status(v),
                                 _ => continue,
                             };
                             sched_comp.entries.insert(ItipEntry {
@@ -343,15 +456,106 @@ pub fn itip_snapshot<'x, 'y>(
                 }
             }
 
+            // DTEND (or DUE for VTODO) takes precedence over DTSTART plus DURATION
+            sched_comp.end = sched_comp.explicit_end().or_else(|| {
+                sched_comp
+                    .start()
+                    .zip(sched_comp.duration())
+                    .map(|(start, duration)| start + duration)
+            });
+
+            // Collect the alarms defined for this component
+            for alarm_id in &comp.component_ids {
+                let Some(alarm) = ical
+                    .components
+                    .get(*alarm_id as usize)
+                    .filter(|alarm| alarm.component_type == ICalendarComponentType::VAlarm)
+                else {
+                    continue;
+                };
+                let mut trigger = None;
+                let mut action = None;
+
+                for entry in &alarm.entries {
+                    match &entry.name {
+                        ICalendarProperty::Trigger => {
+                            trigger = match entry.values.first() {
+                                Some(ICalendarValue::Duration(duration)) => {
+                                    Some(ItipAlarmTrigger::Relative {
+                                        duration,
+                                        related_end: entry.params.iter().any(|param| {
+                                            matches!(
+                                                (&param.name, &param.value),
+                                                (
+                                                    ICalendarParameterName::Related,
+                                                    ICalendarParameterValue::Related(
+                                                        ICalendarRelated::End
+                                                    )
+                                                )
+                                            )
+                                        }),
+                                    })
+                                }
+                                Some(ICalendarValue::PartialDateTime(date)) => {
+                                    let tz_id = entry.tz_id();
+                                    let tz = tz_resolver
+                                        .get_or_insert_with(|| ical.build_tz_resolver())
+                                        .resolve_or_default(tz_id);
+                                    Some(ItipAlarmTrigger::Absolute(ItipDateTime {
+                                        date: date.as_ref(),
+                                        tz_id,
+                                        tz_code: tz.as_id(),
+                                        timestamp: date
+                                            .to_date_time_with_tz(tz)
+                                            .map(|dt| dt.timestamp())
+                                            .unwrap_or_else(|| {
+                                                date.to_timestamp().unwrap_or_default()
+                                            }),
+                                    }))
+                                }
+                                _ => None,
+                            };
+                        }
+                        ICalendarProperty::Action => {
+                            action = entry.values.first().and_then(|v| v.as_text());
+                        }
+                        _ => {}
+                    }
+                }
+
+                if let Some(trigger) = trigger {
+                    sched_comp.alarms.push(ItipAlarm {
+                        comp_id: *alarm_id as u16,
+                        trigger,
+                        action,
+                    });
+                }
+            }
+
             if components.insert(instance_id, sched_comp).is_some() {
                 return Err(ItipError::MultipleObjectInstances);
+            } else if components.len() > limits.max_overrides {
+                return Err(ItipError::TooManyOverrides);
             }
         }
     }
 
     if has_local_emails {
+        let organizer = organizer.ok_or(ItipError::NoSchedulingInfo)?;
+
+        // The organizer is often listed as an attendee as well
+        for comp in components.values_mut() {
+            comp.attendees = std::mem::take(&mut comp.attendees)
+                .into_iter()
+                .map(|mut attendee| {
+                    attendee.is_organizer = attendee.email.email == organizer.email.email;
+                    attendee
+                })
+                .collect();
+        }
+
         Ok(ItipSnapshots {
-            organizer: organizer.ok_or(ItipError::NoSchedulingInfo)?,
+            organizer,
             uid: uid.ok_or(ItipError::MissingUid)?,
             components,
         })
@@ -360,6 +564,44 @@ pub fn itip_snapshot<'x, 'y>(
     }
 }
 
+fn attendee_email(
+    entry: &ICalendarEntry,
+    account_emails: &[String],
+    aliases: Option<&AHashMap<String, String>>,
+) -> Option<Email> {
+    let value = entry.values.first();
+
+    value
+        .and_then(|v| v.as_text())
+        .and_then(|v| Email::new(v, account_emails))
+        .or_else(|| match value {
+            Some(ICalendarValue::Uri(uri)) => Email::from_uri(uri, account_emails),
+            _ => None,
+        })
+        .or_else(|| {
+            // Last resort, match by the configured aliases
+            let aliases = aliases?;
+            value
+                .and_then(|v| v.as_text())
+                .into_iter()
+                .chain(attendee_name(entry))
+                .find_map(|alias| aliases.get(&alias.trim().to_lowercase()))
+                .and_then(|email| Email::new(email, account_emails))
+        })
+}
+
+fn attendee_name(entry: &ICalendarEntry) -> Option<&str> {
+    entry
+        .params
+        .iter()
+        .find_map(|param| match (&param.name, &param.value) {
+            (ICalendarParameterName::Cn, ICalendarParameterValue::Text(name)) => {
+                Some(name.as_str())
+            }
+            _ => None,
+        })
+}
+
 impl ItipSnapshots<'_> {
     pub fn sender_is_organizer_or_attendee(&self, email: &str) -> bool {
         self.organizer.email.email == email
@@ -371,6 +613,39 @@ impl ItipSnapshots<'_> {
             })
     }
 
+    /// Returns the instances with a local attendee that overlap any of the busy intervals.
+    /// Instances without an end inherit the duration of the main instance.
+    pub fn conflicts_with(&self, busy: &[(i64, i64)]) -> Vec<InstanceId> {
+        let main_duration = self
+            .main_instance()
+            .and_then(|main| Some(main.end? - main.start()?));
+        let mut conflicts = self
+            .components
+            .iter()
+            .filter(|(_, snapshot)| snapshot.has_local_attendee() && !snapshot.is_cancelled())
+            .filter_map(|(instance_id, snapshot)| {
+                let (start, is_all_day) = snapshot.start_date_time()?;
+                let end = snapshot
+                    .end
+                    .or_else(|| main_duration.map(|duration| start + duration))
+                    .unwrap_or(
+                        start
+                            + if is_all_day {
+                                DEFAULT_ALL_DAY_DURATION
+                            } else {
+                                DEFAULT_EVENT_DURATION
+                            },
+                    );
+
+                busy.iter()
+                    .any(|(busy_start, busy_end)| *busy_start < end && start < *busy_end)
+                    .then_some(*instance_id)
+            })
+            .collect::<Vec<_>>();
+        conflicts.sort_unstable();
+        conflicts
+    }
+
     pub fn main_instance(&self) -> Option<&ItipSnapshot<'_>> {
         self.components.get(&InstanceId::Main)
     }
@@ -379,9 +654,209 @@ impl ItipSnapshots<'_> {
         self.main_instance()
             .unwrap_or_else(|| self.components.values().next().unwrap())
     }
+
+    pub fn affected_instances(
+        &self,
+        ical: &ICalendar,
+        rid: &RecurrenceId,
+        max_instances: usize,
+        max_horizon: i64,
+    ) -> Vec<InstanceId> {
+        let mut instances = BTreeMap::from([(rid.date, rid.entry_id)]);
+
+        if rid.this_and_future {
+            // Add overridden instances on or after the recurrence id
+            instances.extend(
+                self.components
+                    .keys()
+                    .filter_map(|instance_id| match instance_id {
+                        InstanceId::Recurrence(override_id) if override_id.date > rid.date => {
+                            Some((override_id.date, override_id.entry_id))
+                        }
+                        _ => None,
+                    }),
+            );
+
+            // Add the following instances of the main recurrence rule, occurrences before
+            // the recurrence id do not count towards the limit
+            if let Some(main) = self.main_instance() {
+                let mut max_expansions = max_instances;
+                loop {
+                    let expansions = horizon_expansions(ical, max_expansions, max_horizon);
+                    let events = ical.expand_dates(Tz::Floating, expansions).events;
+                    let horizon = horizon_cutoff(
+                        events.iter().map(|event| event.start.timestamp()).min(),
+                        max_horizon,
+                    );
+                    let num_events = events.len();
+                    let mut num_following = 0;
+                    let mut past_horizon = false;
+                    for event in events {
+                        let date = event.start.timestamp();
+                        if event.comp_id as u16 != main.comp_id {
+                            continue;
+                        } else if date > horizon {
+                            past_horizon = true;
+                        } else if date > rid.date {
+                            instances.entry(date).or_insert(rid.entry_id);
+                            num_following += 1;
+                        }
+                    }
+
+                    // Expanding further does not yield instances once the horizon is reached
+                    if num_following >= max_instances
+                        || num_following == num_events
+                        || num_events < expansions
+                        || expansions < max_expansions
+                        || past_horizon
+                    {
+                        break;
+                    }
+                    max_expansions = max_expansions.saturating_add(num_events - num_following);
+                }
+            }
+        }
+
+        instances
+            .into_iter()
+            .take(max_instances)
+            .map(|(date, entry_id)| {
+                InstanceId::Recurrence(RecurrenceId {
+                    entry_id,
+                    date,
+                    this_and_future: false,
+                })
+            })
+            .collect()
+    }
+
+    pub fn recurrence_set(
+        &self,
+        ical: &ICalendar,
+        max_instances: usize,
+        max_horizon: i64,
+    ) -> RecurrenceSet {
+        let mut set = RecurrenceSet::default();
+        let Some(main) = self.main_instance() else {
+            return set;
+        };
+
+        // Expand the recurrence rule of the main instance, leaving room for
+        // the instances that will be excluded
+        let num_exdates = main
+            .entries
+            .iter()
+            .filter(|entry| entry.name == &ICalendarProperty::Exdate)
+            .count();
+        let max_expansions =
+            horizon_expansions(ical, max_instances.saturating_add(num_exdates), max_horizon);
+        let events = ical.expand_dates(Tz::Floating, max_expansions).events;
+        let horizon = horizon_cutoff(
+            events.iter().map(|event| event.start.timestamp()).min(),
+            max_horizon,
+        );
+        for event in events {
+            let date = event.start.timestamp();
+            if event.comp_id as u16 == main.comp_id && date <= horizon {
+                set.dates.insert(date);
+            }
+        }
+
+        // Apply RDATE additions before EXDATE exclusions (RFC 5545 § 3.8.5)
+        for entry in &main.entries {
+            if let (ICalendarProperty::Rdate, ItipEntryValue::DateTime(dt)) =
+                (entry.name, &entry.value)
+            {
+                set.dates.insert(dt.timestamp);
+            }
+        }
+        for entry in &main.entries {
+            if let (ICalendarProperty::Exdate, ItipEntryValue::DateTime(dt)) =
+                (entry.name, &entry.value)
+            {
+                set.dates.remove(&dt.timestamp);
+            }
+        }
+
+        while set.dates.len() > max_instances {
+            set.dates.pop_last();
+        }
+
+        set
+    }
+
+    pub fn instance_starts(&self) -> impl Iterator<Item = (InstanceId, i64)> + '_ {
+        self.components
+            .iter()
+            .filter_map(|(instance_id, snapshot)| {
+                snapshot.start().map(|start| (*instance_id, start))
+            })
+    }
 }
 
 impl ItipSnapshot<'_> {
+    pub fn partstat_summary(&self) -> PartStatCounts {
+        let mut counts = PartStatCounts::default();
+        for attendee in &self.attendees {
+            // PARTSTAT defaults to NEEDS-ACTION (RFC 5545 Section 3.2.12)
+            match attendee.part_stat {
+                Some(ICalendarParticipationStatus::Accepted) => counts.accepted += 1,
+                Some(ICalendarParticipationStatus::Declined) => counts.declined += 1,
+                Some(ICalendarParticipationStatus::Tentative) => counts.tentative += 1,
+                Some(ICalendarParticipationStatus::NeedsAction) | None => counts.needs_action += 1,
+                Some(_) => counts.other += 1,
+            }
+        }
+        counts
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.status, Some(ICalendarStatus::Cancelled))
+    }
+
+    pub fn start(&self) -> Option<i64> {
+        self.entries
+            .iter()
+            .find_map(|entry| match (entry.name, &entry.value) {
+                (ICalendarProperty::Dtstart, ItipEntryValue::DateTime(dt)) => Some(dt.timestamp),
+                _ => None,
+            })
+    }
+
+    fn start_date_time(&self) -> Option<(i64, bool)> {
+        self.entries
+            .iter()
+            .find_map(|entry| match (entry.name, &entry.value) {
+                (ICalendarProperty::Dtstart, ItipEntryValue::DateTime(dt)) => {
+                    Some((dt.timestamp, dt.date.hour.is_none()))
+                }
+                _ => None,
+            })
+    }
+
+    fn explicit_end(&self) -> Option<i64> {
+        self.entries
+            .iter()
+            .find_map(|entry| match (entry.name, &entry.value) {
+                (
+                    ICalendarProperty::Dtend | ICalendarProperty::Due,
+                    ItipEntryValue::DateTime(dt),
+                ) => Some(dt.timestamp),
+                _ => None,
+            })
+    }
+
+    pub fn duration(&self) -> Option<i64> {
+        self.entries
+            .iter()
+            .find_map(|entry| match (entry.name, &entry.value) {
+                (ICalendarProperty::Duration, ItipEntryValue::Duration(duration)) => {
+                    Some(duration.as_seconds())
+                }
+                _ => None,
+            })
+    }
+
     pub fn has_local_attendee(&self) -> bool {
         self.attendees
             .iter()
@@ -404,6 +879,12 @@ impl ItipSnapshot<'_> {
             .find(|attendee| attendee.email.email == email)
     }
 
+    pub fn should_force_send(&self, organizer: &Organizer<'_>, email: &str) -> bool {
+        self.attendee_by_email(email)
+            .is_some_and(|attendee| attendee.force_send.is_some())
+            || (organizer.email.email == email && organizer.force_send.is_some())
+    }
+
     pub fn build_summary(
         &self,
         include_guests: Option<&Organizer<'_>>,
@@ -443,7 +924,7 @@ impl ItipSnapshot<'_> {
         if let Some(organizer) = include_guests {
             let mut attendees = Vec::with_capacity(self.attendees.len());
             for attendee in &self.attendees {
-                if attendee.email.email != organizer.email.email {
+                if !attendee.is_organizer {
                     attendees.push(ItipParticipant {
                         email: attendee.email.email.to_string(),
                         name: attendee.name.map(|n| n.to_string()),
@@ -9,7 +9,7 @@ use chrono::Utc;
 use common::{
     KV_RATE_LIMIT_CONTACT, Server,
     config::network::{ContactForm, FieldOrDefault},
-    ip_to_bytes, psl,
+    ip_to_bytes,
 };
 use email::message::delivery::{IngestMessage, IngestRecipient, LocalDeliveryStatus, MailDelivery};
 use http_proto::*;
@@ -90,7 +90,7 @@ impl FormHandler for Server {
             if let Some(domain) = from_email.rsplit_once('@').and_then(|(local, domain)| {
                 if !local.is_empty()
                     && domain.contains('.')
-                    && psl::domain(domain.as_bytes()).is_some_and(|d| d.suffix().typ().is_some())
+                    && self.psl_listed_domain(domain).is_some()
                 {
                     Some(domain)
                 } else {
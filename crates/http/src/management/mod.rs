@@ -13,6 +13,7 @@ pub mod queue;
 pub mod reload;
 pub mod report;
 pub mod settings;
+pub mod sieve;
 pub mod spam;
 pub mod stores;
 pub mod troubleshoot;
@@ -27,6 +28,7 @@ pub mod enterprise;
 use enterprise::telemetry::TelemetryApi;
 // SPDX-SnippetEnd
 
+use self::sieve::SieveHandler;
 use crate::auth::oauth::auth::OAuthApiHandler;
 use common::{Server, auth::AccessToken};
 use crypto::CryptoHandler;
@@ -125,6 +127,12 @@ impl ManagementApi for Server {
                 self.handle_manage_spam(req, path, body, session, &access_token)
                     .await
             }
+            "sieve" => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::SpamFilterTrain)?;
+
+                self.handle_run_sieve(req, path, body, &access_token).await
+            }
             "restart" if req.method() == Method::GET => {
                 // Validate the access token
                 access_token.assert_has_permission(Permission::Restart)?;
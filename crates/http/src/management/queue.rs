@@ -87,6 +87,19 @@ pub struct Recipient {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub orcpt: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub last_response: Option<LastResponse>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct LastResponse {
+    pub hostname: String,
+    pub command: String,
+    pub code: u16,
+    pub enhanced_code: String,
+    pub message: String,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -320,6 +333,72 @@ impl QueueManagement for Server {
                     Err(trc::ResourceEvent::NotFound.into_err())
                 }
             }
+            ("retry", Some(queue_id), &Method::PATCH) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MessageQueueUpdate)?;
+
+                let queue_id = queue_id.parse().unwrap_or_default();
+                if self
+                    .read_message(queue_id, QueueName::default())
+                    .await
+                    .is_some_and(|message| {
+                        tenant_domains
+                            .as_ref()
+                            .is_none_or(|domains| message.has_domain(domains))
+                    })
+                {
+                    let _ = self
+                        .inner
+                        .ipc
+                        .queue_tx
+                        .send(QueueEvent::Retry(queue_id))
+                        .await;
+
+                    Ok(JsonResponse::new(json!({
+                            "data": true,
+                    }))
+                    .into_http_response())
+                } else {
+                    Err(trc::ResourceEvent::NotFound.into_err())
+                }
+            }
+            ("hold", Some(queue_id), &Method::PATCH) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MessageQueueUpdate)?;
+
+                let queue_id = queue_id.parse().unwrap_or_default();
+                let until = params
+                    .parse::<FutureTimestamp>("until")
+                    .map(|t| t.into_inner())
+                    .ok_or_else(|| {
+                        trc::ResourceEvent::BadParameters
+                            .into_err()
+                            .details("until")
+                    })?;
+                if self
+                    .read_message(queue_id, QueueName::default())
+                    .await
+                    .is_some_and(|message| {
+                        tenant_domains
+                            .as_ref()
+                            .is_none_or(|domains| message.has_domain(domains))
+                    })
+                {
+                    let _ = self
+                        .inner
+                        .ipc
+                        .queue_tx
+                        .send(QueueEvent::HoldUntil { queue_id, until })
+                        .await;
+
+                    Ok(JsonResponse::new(json!({
+                            "data": true,
+                    }))
+                    .into_http_response())
+                } else {
+                    Err(trc::ResourceEvent::NotFound.into_err())
+                }
+            }
             ("messages", None, &Method::DELETE) => {
                 // Validate the access token
                 access_token.assert_has_permission(Permission::MessageQueueDelete)?;
@@ -416,6 +495,55 @@ impl QueueManagement for Server {
                     Err(trc::ResourceEvent::NotFound.into_err())
                 }
             }
+            ("limits", None, &Method::GET) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MessageQueueGet)?;
+
+                let (tx, rx) = tokio::sync::oneshot::channel();
+                let stats = if self
+                    .inner
+                    .ipc
+                    .queue_tx
+                    .send(QueueEvent::LimiterStats(tx))
+                    .await
+                    .is_ok()
+                {
+                    rx.await.unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
+
+                Ok(JsonResponse::new(json!({
+                        "data": stats,
+                }))
+                .into_http_response())
+            }
+            ("flush-held", None, &Method::PATCH) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MessageQueueUpdate)?;
+
+                // Tenants can only release messages for their own domains
+                let domain = params.get("domain").map(|d| d.to_lowercase());
+                if let Some(tenant_domains) = &tenant_domains
+                    && domain
+                        .as_ref()
+                        .is_none_or(|domain| !tenant_domains.contains(domain))
+                {
+                    return Err(trc::ResourceEvent::NotFound.into_err());
+                }
+
+                let _ = self
+                    .inner
+                    .ipc
+                    .queue_tx
+                    .send(QueueEvent::FlushOnHold { filter: domain })
+                    .await;
+
+                Ok(JsonResponse::new(json!({
+                        "data": true,
+                }))
+                .into_http_response())
+            }
             ("reports", None, &Method::GET) => {
                 // Validate the access token
                 access_token.assert_has_permission(Permission::OutgoingReportList)?;
@@ -551,6 +679,39 @@ impl QueueManagement for Server {
                 }))
                 .into_http_response())
             }
+            ("health", None, &Method::GET) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MessageQueueGet)?;
+
+                let health = &self.inner.data.queue_health;
+                Ok(JsonResponse::new(json!({
+                        "data": {
+                            "active": self.inner.data.queue_status.load(Ordering::Relaxed),
+                            "nextRefresh": health.next_refresh.load(Ordering::Relaxed),
+                            "lastProcessed": health.last_processed.load(Ordering::Relaxed),
+                            "dueMessages": health.due_messages.load(Ordering::Relaxed),
+                            "onHold": health.on_hold.load(Ordering::Relaxed),
+                            "locked": health.locked.load(Ordering::Relaxed),
+                        },
+                }))
+                .into_http_response())
+            }
+            ("forecast", None, &Method::GET) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MessageQueueGet)?;
+
+                let forecast = self.queue_forecast().await?;
+                Ok(JsonResponse::new(json!({
+                        "data": {
+                            "overdue": forecast.overdue,
+                            "nextMinute": forecast.next_minute,
+                            "nextHour": forecast.next_hour,
+                            "nextDay": forecast.next_day,
+                            "later": forecast.later,
+                        },
+                }))
+                .into_http_response())
+            }
             ("status", Some(action), &Method::PATCH) => {
                 // Validate the access token
                 access_token.assert_has_permission(Permission::MessageQueueUpdate)?;
@@ -616,6 +777,20 @@ impl Message {
                         None
                     },
                     orcpt: rcpt.orcpt.as_ref().map(|orcpt| orcpt.to_string()),
+                    last_response: rcpt
+                        .last_response()
+                        .map(|(hostname, response)| LastResponse {
+                            hostname: hostname.to_string(),
+                            command: response.command.to_string(),
+                            code: response.response.code.into(),
+                            enhanced_code: format!(
+                                "{}.{}.{}",
+                                response.response.esc[0],
+                                response.response.esc[1],
+                                response.response.esc[2]
+                            ),
+                            message: response.response.message.to_string(),
+                        }),
                 })
                 .collect(),
 
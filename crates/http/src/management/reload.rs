@@ -71,6 +71,42 @@ impl ManageReload for Server {
                 }))
                 .into_http_response())
             }
+            (Some("public-suffix"), &Method::GET) => {
+                let result = self.reload_public_suffix().await?;
+                let list = self.inner.data.public_suffix.load().metadata();
+
+                self.cluster_broadcast(BroadcastEvent::ReloadPublicSuffix)
+                    .await;
+
+                Ok(JsonResponse::new(json!({
+                    "data": {
+                        "entries": list.suffixes + list.exceptions + list.wildcards,
+                        "suffixes": list.suffixes,
+                        "exceptions": list.exceptions,
+                        "wildcards": list.wildcards,
+                        "private": list.private,
+                        "sources": list.sources,
+                        "loadedAt": list.loaded_at,
+                        "warnings": result.config.warnings,
+                        "errors": result.config.errors,
+                    },
+                }))
+                .into_http_response())
+            }
+            (Some("sieve"), &Method::GET) => {
+                let result = self.reload_sieve_scripts().await?;
+                if let Some(core) = result.new_core {
+                    self.inner.shared_core.store(core.into());
+
+                    self.cluster_broadcast(BroadcastEvent::ReloadSieveScripts)
+                        .await;
+                }
+
+                Ok(JsonResponse::new(json!({
+                    "data": result.config,
+                }))
+                .into_http_response())
+            }
             (_, &Method::GET) => {
                 let result = self.reload().await?;
                 if !UrlParams::new(req.uri().query()).has_key("dry-run") {
@@ -0,0 +1,315 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::{Server, auth::AccessToken, scripts::ScriptModification};
+use directory::backend::internal::manage;
+use http_proto::*;
+use hyper::Method;
+use mail_parser::MessageParser;
+use serde_json::json;
+use sieve::{Envelope, runtime::Variable};
+use smtp::scripts::{
+    ScriptAction, ScriptParameters, ScriptResult, ScriptTrace, event_loop::RunScript,
+};
+use std::future::Future;
+use std::time::SystemTime;
+use trc::SieveEvent;
+use utils::url_params::UrlParams;
+
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "action")]
+#[serde(rename_all = "lowercase")]
+pub enum Response {
+    Accept {
+        modifications: Vec<ScriptModification>,
+        actions: Vec<ScriptAction>,
+    },
+    Replace {
+        message: String,
+        modifications: Vec<ScriptModification>,
+        actions: Vec<ScriptAction>,
+    },
+    Reject {
+        reason: String,
+        actions: Vec<ScriptAction>,
+    },
+    Discard {
+        actions: Vec<ScriptAction>,
+    },
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct TracedResponse {
+    #[serde(flatten)]
+    response: Response,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<Vec<ScriptTrace>>,
+}
+
+pub trait SieveHandler: Sync + Send {
+    fn handle_run_sieve(
+        &self,
+        req: &HttpRequest,
+        path: Vec<&str>,
+        body: Option<Vec<u8>>,
+        access_token: &AccessToken,
+    ) -> impl Future<Output = trc::Result<HttpResponse>> + Send;
+}
+
+impl SieveHandler for Server {
+    async fn handle_run_sieve(
+        &self,
+        req: &HttpRequest,
+        path: Vec<&str>,
+        body: Option<Vec<u8>>,
+        access_token: &AccessToken,
+    ) -> trc::Result<HttpResponse> {
+        // Scripts are run in the order they appear in the path, those the caller
+        // is not allowed to run are reported as not found
+        if req.method() != Method::POST {
+            return Err(trc::ResourceEvent::NotFound.into_err());
+        }
+        let mut scripts = Vec::with_capacity(path.len().saturating_sub(1));
+        for name in path.iter().skip(1).filter(|name| !name.is_empty()) {
+            let is_allowed = match self.core.sieve.trusted_scripts_access.get(*name) {
+                Some(allowed) => allowed.contains(&access_token.name.to_lowercase()),
+                None => access_token.tenant.is_none(),
+            };
+            match self.core.sieve.trusted_scripts.get(*name) {
+                Some(script) if is_allowed => {
+                    scripts.push((script.clone(), name.to_string()));
+                }
+                _ => {
+                    return Err(trc::ResourceEvent::NotFound.into_err());
+                }
+            }
+        }
+        if scripts.is_empty() {
+            return Err(trc::ResourceEvent::NotFound.into_err());
+        }
+
+        let mut variables: Vec<(String, Variable)> = vec![
+            (
+                "now".to_string(),
+                SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs())
+                    .into(),
+            ),
+            ("test".to_string(), Variable::Integer(1)),
+        ];
+        let mut envelope: Vec<(Envelope, Variable)> = Vec::new();
+
+        // Repeated parameters are passed with a numeric suffix (env_to, env_to2, ...)
+        let mut query = UrlParams::new(req.uri().query())
+            .into_inner()
+            .into_iter()
+            .collect::<Vec<_>>();
+        query.sort_by_cached_key(|(key, _)| {
+            let base = key.trim_end_matches(|c: char| c.is_ascii_digit());
+            let index = key[base.len()..].parse::<u64>().unwrap_or(1);
+            (base.to_string(), index)
+        });
+
+        let mut envelope_from = Vec::new();
+        let mut envelope_to = Vec::new();
+        let mut envelope_orcpt = Vec::new();
+        let mut with_trace = false;
+        for (key, value) in query {
+            if key.starts_with("env_from") {
+                envelope_from.push(Variable::from(value.to_lowercase()));
+                continue;
+            } else if key.starts_with("env_to") {
+                envelope_to.push(Variable::from(value.to_lowercase()));
+                continue;
+            } else if key.starts_with("env_orcpt") {
+                envelope_orcpt.push(Variable::from(value.to_lowercase()));
+                continue;
+            }
+
+            let env = match key.as_ref() {
+                "now" => {
+                    let now = value.parse::<u64>().map_err(|_| {
+                        trc::ResourceEvent::BadParameters
+                            .into_err()
+                            .details("Invalid 'now' timestamp.")
+                    })?;
+                    variables[0].1 = now.into();
+                    continue;
+                }
+                "trace" => {
+                    with_trace = value == "1" || value.eq_ignore_ascii_case("true");
+                    continue;
+                }
+                "env_ret" => Envelope::Ret,
+                "env_notify" => Envelope::Notify,
+                "env_id" | "env_envid" => Envelope::Envid,
+                "env_bytime" => Envelope::ByTimeAbsolute,
+                "env_bymode" => Envelope::ByMode,
+                "env_bytrace" => Envelope::ByTrace,
+                _ => {
+                    variables.push((key.into_owned(), value.into_owned().into()));
+                    continue;
+                }
+            };
+
+            envelope.push((env, value.into_owned().into()));
+        }
+
+        for (env, mut values) in [
+            (Envelope::From, envelope_from),
+            (Envelope::Orcpt, envelope_orcpt),
+        ] {
+            match values.len() {
+                0 => {}
+                1 => {
+                    envelope.push((env, values.pop().unwrap()));
+                }
+                _ => {
+                    envelope.push((env, Variable::from(values)));
+                }
+            }
+        }
+        if !envelope_to.is_empty() {
+            envelope.push((Envelope::To, Variable::from(envelope_to)));
+        }
+
+        // Use the request body as the message being filtered
+        let mut message = body.filter(|body| !body.is_empty());
+        let mut replaced = None;
+
+        // Run scripts, threading modifications forward
+        let mut actions = Vec::new();
+        let mut trace = Vec::new();
+        let mut modifications = Vec::new();
+        let num_scripts = scripts.len();
+        let mut result = None;
+        for (script, script_id) in scripts {
+            let mut params = ScriptParameters::new();
+            for (name, value) in &variables {
+                params = params.set_variable(name.clone(), value.clone());
+            }
+            for (name, value) in &envelope {
+                params = params.set_envelope(*name, value.clone());
+            }
+            if let Some(message) = &message {
+                params = params.with_message(
+                    MessageParser::new()
+                        .parse(message)
+                        .ok_or_else(|| manage::error("Failed to parse message.", None::<u64>))?,
+                );
+            }
+            if with_trace {
+                if num_scripts > 1 {
+                    trace.push(ScriptTrace::Script {
+                        name: script_id.clone(),
+                    });
+                }
+                params = params.with_trace(&mut trace);
+            }
+
+            let script_result = match tokio::time::timeout(
+                self.core.sieve.test_timeout,
+                self.run_script(
+                    script_id.clone(),
+                    script,
+                    params
+                        .with_runtime(&self.core.sieve.test_runtime)
+                        .with_access_token(access_token)
+                        .with_actions(&mut actions),
+                ),
+            )
+            .await
+            {
+                Ok(result) => result,
+                Err(_) => {
+                    trc::event!(
+                        Sieve(SieveEvent::RuntimeError),
+                        Id = script_id,
+                        Reason = "Script execution timed out",
+                        Elapsed = self.core.sieve.test_timeout,
+                    );
+
+                    ScriptResult::Reject("Script execution timed out".to_string())
+                }
+            };
+
+            let script_modifications = match script_result {
+                ScriptResult::Accept { modifications } => modifications,
+                ScriptResult::Replace {
+                    message: new_message,
+                    modifications,
+                } => {
+                    message = Some(new_message.clone());
+                    replaced = Some(new_message);
+                    modifications
+                }
+                ScriptResult::Reject(reason) => {
+                    result = Some(Response::Reject {
+                        reason,
+                        actions: std::mem::take(&mut actions),
+                    });
+                    break;
+                }
+                ScriptResult::Discard => {
+                    result = Some(Response::Discard {
+                        actions: std::mem::take(&mut actions),
+                    });
+                    break;
+                }
+            };
+
+            // Make the changes visible to the next script
+            let mut headers = Vec::new();
+            for modification in &script_modifications {
+                match modification {
+                    ScriptModification::AddHeader { name, value } => {
+                        headers.extend_from_slice(name.as_bytes());
+                        headers.extend_from_slice(b": ");
+                        headers.extend_from_slice(value.as_bytes());
+                        if !value.ends_with('\n') {
+                            headers.extend_from_slice(b"\r\n");
+                        }
+                    }
+                    ScriptModification::SetEnvelope { name, value } => {
+                        envelope.retain(|(env, _)| env != name);
+                        envelope.push((*name, value.clone().into()));
+                    }
+                }
+            }
+            if !headers.is_empty() {
+                headers.extend_from_slice(message.as_deref().unwrap_or(b"\r\n"));
+                message = Some(headers);
+            }
+            modifications.extend(script_modifications);
+        }
+
+        let result = match (result, replaced) {
+            (Some(result), _) => result,
+            (None, Some(message)) => Response::Replace {
+                message: String::from_utf8(message).map_err(|_| {
+                    trc::ResourceEvent::BadParameters
+                        .into_err()
+                        .details("Replaced message is not valid UTF-8.")
+                })?,
+                modifications,
+                actions,
+            },
+            (None, None) => Response::Accept {
+                modifications,
+                actions,
+            },
+        };
+
+        Ok(JsonResponse::new(json!({
+            "data": TracedResponse {
+                response: result,
+                trace: with_trace.then_some(trace),
+            },
+        }))
+        .into_http_response())
+    }
+}
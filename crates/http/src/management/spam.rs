@@ -4,7 +4,11 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use common::{Server, auth::AccessToken, config::spamfilter::SpamFilterAction, psl};
+use common::{
+    Server,
+    auth::AccessToken,
+    config::spamfilter::{DNSBL_METRICS_BUCKET, DnsBlBucket, SpamFilterAction},
+};
 use directory::{
     Permission,
     backend::internal::manage::{self, ManageDirectory},
@@ -25,7 +29,11 @@ use spam_filter::{
 };
 use std::future::Future;
 use std::net::IpAddr;
-use store::{ahash::AHashMap, write::BatchBuilder};
+use store::{
+    ahash::AHashMap,
+    write::{BatchBuilder, now},
+};
+use utils::url_params::UrlParams;
 
 pub trait ManageSpamHandler: Sync + Send {
     fn handle_manage_spam(
@@ -177,6 +185,53 @@ impl ManageSpamHandler for Server {
                 }))
                 .into_http_response())
             }
+            (Some("dnsbl"), Some("metrics"), &Method::GET) => {
+                // Validate the access token
+                access_token.assert_has_permission(Permission::MetricsList)?;
+
+                let hours = UrlParams::new(req.uri().query())
+                    .parse::<u64>("hours")
+                    .unwrap_or(24);
+                let since = now().saturating_sub(hours * DNSBL_METRICS_BUCKET);
+                let mut servers = serde_json::Map::new();
+                for (id, buckets) in self.inner.data.dnsbl_metrics.snapshot(since) {
+                    let mut total = DnsBlBucket::default();
+                    for bucket in &buckets {
+                        total.merge(bucket);
+                    }
+                    let ratio = |count: u64| {
+                        if total.queries > 0 {
+                            count as f64 / total.queries as f64
+                        } else {
+                            0.0
+                        }
+                    };
+
+                    servers.insert(
+                        id,
+                        json!({
+                            "queries": total.queries,
+                            "hits": total.hits,
+                            "nxdomain": total.nxdomain,
+                            "errors": total.errors,
+                            "hitRate": ratio(total.hits),
+                            "errorRate": ratio(total.errors),
+                            "elapsedTotal": total.elapsed_total,
+                            "elapsedMax": total.elapsed_max,
+                            "elapsedAvg": total.elapsed_total.checked_div(total.queries),
+                            "elapsedP50": total.latency_percentile(0.5),
+                            "elapsedP95": total.latency_percentile(0.95),
+                            "elapsedP99": total.latency_percentile(0.99),
+                            "buckets": buckets,
+                        }),
+                    );
+                }
+
+                Ok(JsonResponse::new(json!({
+                    "data": servers,
+                }))
+                .into_http_response())
+            }
             (Some("classify"), _, &Method::POST) => {
                 // Validate the access token
                 access_token.assert_has_permission(Permission::SpamFilterTest)?;
@@ -275,7 +330,7 @@ impl ManageSpamHandler for Server {
                         dkim_output: &dkim_output,
                         rfc5321_mail_from_domain: mail_from_domain.unwrap_or(ehlo_domain.as_str()),
                         spf_output: &spf_mail_from_result,
-                        domain_suffix_fn: |domain| psl::domain_str(domain).unwrap_or(domain),
+                        domain_suffix_fn: |domain| self.psl_domain(domain).unwrap_or(domain),
                     }))
                     .await;
                 let dmarc_pass = matches!(dmarc_output.spf_result(), DmarcResult::Pass)
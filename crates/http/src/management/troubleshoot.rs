@@ -17,7 +17,6 @@ use common::{
         queue::MxConfig,
         resolver::{Policy, Tlsa},
     },
-    psl,
 };
 use directory::backend::internal::manage;
 use http_body_util::{StreamBody, combinators::BoxBody};
@@ -982,7 +981,7 @@ async fn dmarc_troubleshoot(
             dkim_output: &dkim_output,
             rfc5321_mail_from_domain: mail_from_domain.unwrap_or(ehlo_domain.as_str()),
             spf_output: &mail_spf_output,
-            domain_suffix_fn: |domain| psl::domain_str(domain).unwrap_or(domain),
+            domain_suffix_fn: |domain| server.psl_domain(domain).unwrap_or(domain),
         }))
         .await;
     let dmarc_pass = matches!(dmarc_output.spf_result(), DmarcResult::Pass)
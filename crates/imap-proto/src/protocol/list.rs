@@ -11,6 +11,8 @@ use super::{
     status::{Status, StatusItem},
 };
 
+pub const HIERARCHY_DELIMITER: char = '/';
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Arguments {
     Basic {
@@ -225,7 +227,9 @@ impl ListItem {
             }
             attr.serialize(buf);
         }
-        buf.extend_from_slice(b") \"/\" ");
+        buf.extend_from_slice(b") ");
+        quoted_string(buf, HIERARCHY_DELIMITER.encode_utf8(&mut [0u8; 4]));
+        buf.push(b' ');
         let mut extra_tags = Vec::new();
 
         if normalized_mailbox_name != self.mailbox_name {
@@ -7,19 +7,125 @@
 use super::{ImapResponse, quoted_string};
 
 pub struct Response {
-    pub shared_prefix: Option<String>,
+    pub delimiter: char,
+    pub shared_prefixes: Vec<String>,
+    // Namespace response extensions (RFC 2342) added to the non-personal namespaces
+    pub shared_extensions: Vec<(String, Vec<String>)>,
 }
 
+pub const EXT_ACCESS: &str = "X-ACCESS";
+pub const EXT_ACCESS_MAYBE_READ_ONLY: &str = "MAY-BE-READ-ONLY";
+
 impl ImapResponse for Response {
     fn serialize(self) -> Vec<u8> {
+        let mut delimiter = [0u8; 4];
+        let delimiter = self.delimiter.encode_utf8(&mut delimiter);
+
         let mut buf = Vec::with_capacity(64);
-        if let Some(shared_prefix) = &self.shared_prefix {
-            buf.extend_from_slice(b"* NAMESPACE ((\"\" \"/\")) ((");
-            quoted_string(&mut buf, shared_prefix);
-            buf.extend_from_slice(b" \"/\")) NIL\r\n");
-        } else {
-            buf.extend_from_slice(b"* NAMESPACE ((\"\" \"/\")) NIL NIL\r\n");
-        }
+        buf.extend_from_slice(b"* NAMESPACE ");
+        serialize_namespaces(&mut buf, [""], delimiter, &[]);
+        buf.push(b' ');
+
+        // Shared accounts are mounted under the shared folder, each account root
+        // is advertised as an other users' namespace
+        serialize_namespaces(
+            &mut buf,
+            self.shared_prefixes.iter().map(|p| p.as_str()),
+            delimiter,
+            &self.shared_extensions,
+        );
+        buf.extend_from_slice(b" NIL\r\n");
         buf
     }
 }
+
+fn serialize_namespaces<'x>(
+    buf: &mut Vec<u8>,
+    prefixes: impl IntoIterator<Item = &'x str>,
+    delimiter: &str,
+    extensions: &[(String, Vec<String>)],
+) {
+    let mut prefixes = prefixes.into_iter().peekable();
+    if prefixes.peek().is_some() {
+        buf.push(b'(');
+        for prefix in prefixes {
+            buf.push(b'(');
+            quoted_string(buf, prefix);
+            buf.push(b' ');
+            quoted_string(buf, delimiter);
+            for (name, values) in extensions {
+                buf.push(b' ');
+                quoted_string(buf, name);
+                buf.extend_from_slice(b" (");
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        buf.push(b' ');
+                    }
+                    quoted_string(buf, value);
+                }
+                buf.push(b')');
+            }
+            buf.push(b')');
+        }
+        buf.push(b')');
+    } else {
+        buf.extend_from_slice(b"NIL");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::ImapResponse;
+
+    #[test]
+    fn serialize_namespace() {
+        for (response, expected) in [
+            (
+                super::Response {
+                    delimiter: '/',
+                    shared_prefixes: vec![],
+                    shared_extensions: vec![],
+                },
+                "* NAMESPACE ((\"\" \"/\")) NIL NIL\r\n",
+            ),
+            (
+                super::Response {
+                    delimiter: '/',
+                    shared_prefixes: vec![
+                        "Shared Folders/jane@example.com".into(),
+                        "Shared Folders/sales".into(),
+                    ],
+                    shared_extensions: vec![],
+                },
+                concat!(
+                    "* NAMESPACE ((\"\" \"/\")) ((\"Shared Folders/jane@example.com\" \"/\")",
+                    "(\"Shared Folders/sales\" \"/\")) NIL\r\n"
+                ),
+            ),
+            (
+                super::Response {
+                    delimiter: '.',
+                    shared_prefixes: vec!["Shared Folders.sales".into()],
+                    shared_extensions: vec![],
+                },
+                "* NAMESPACE ((\"\" \".\")) ((\"Shared Folders.sales\" \".\")) NIL\r\n",
+            ),
+            (
+                super::Response {
+                    delimiter: '/',
+                    shared_prefixes: vec!["Shared Folders/sales".into()],
+                    shared_extensions: vec![(
+                        super::EXT_ACCESS.into(),
+                        vec![super::EXT_ACCESS_MAYBE_READ_ONLY.into()],
+                    )],
+                },
+                concat!(
+                    "* NAMESPACE ((\"\" \"/\")) ((\"Shared Folders/sales\" \"/\" ",
+                    "\"X-ACCESS\" (\"MAY-BE-READ-ONLY\"))) NIL\r\n"
+                ),
+            ),
+        ] {
+            assert_eq!(String::from_utf8(response.serialize()).unwrap(), expected);
+        }
+    }
+}
@@ -4,24 +4,66 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+use super::ImapContext;
 use crate::core::Session;
 use common::listener::SessionStream;
 use directory::Permission;
 use imap_proto::{
     Command, StatusResponse,
-    protocol::{ImapResponse, namespace::Response},
+    protocol::{
+        ImapResponse,
+        list::HIERARCHY_DELIMITER,
+        namespace::{EXT_ACCESS, EXT_ACCESS_MAYBE_READ_ONLY, Response},
+    },
     receiver::Request,
 };
+use std::time::Instant;
 
 impl<T: SessionStream> Session<T> {
     pub async fn handle_namespace(&mut self, request: Request<Command>) -> trc::Result<()> {
         // Validate access
         self.assert_has_permission(Permission::ImapNamespace)?;
+        let op_start = Instant::now();
+
+        // Refresh mailboxes so the advertised prefixes match what LIST returns
+        let data = self.state.session_data();
+        data.synchronize_mailboxes(false)
+            .await
+            .imap_ctx(&request.tag, trc::location!())?;
+
+        // List the root of every account shared with the user, accounts without
+        // access to shared folders are only given the personal namespace
+        let has_shared_namespace = data
+            .access_token
+            .has_permission(Permission::ImapSharedNamespace);
+        let mut shared_prefixes = Vec::new();
+        let mut shared_mailboxes = 0;
+        if has_shared_namespace {
+            for account in data.mailboxes.lock().iter() {
+                if let Some(prefix) = &account.prefix {
+                    shared_mailboxes += account.mailbox_state.len();
+                    if !shared_prefixes.contains(prefix) {
+                        shared_prefixes.push(prefix.clone());
+                    }
+                }
+            }
+        }
+
+        // Shared roots may only grant read access, let clients know before they try to write
+        let shared_extensions = if self.server.core.imap.shared_read_only_hint {
+            vec![(
+                EXT_ACCESS.to_string(),
+                vec![EXT_ACCESS_MAYBE_READ_ONLY.to_string()],
+            )]
+        } else {
+            vec![]
+        };
 
         trc::event!(
             Imap(trc::ImapEvent::Namespace),
             SpanId = self.session_id,
-            Elapsed = trc::Value::Duration(0)
+            Total = shared_mailboxes,
+            Elapsed = op_start.elapsed()
         );
 
         self.write_bytes(
@@ -29,11 +71,9 @@ impl<T: SessionStream> Session<T> {
                 .with_tag(request.tag)
                 .serialize(
                     Response {
-                        shared_prefix: if self.state.session_data().mailboxes.lock().len() > 1 {
-                            Some(self.server.core.jmap.shared_folder.as_str().into())
-                        } else {
-                            None
-                        },
+                        delimiter: HIERARCHY_DELIMITER,
+                        shared_prefixes,
+                        shared_extensions,
                     }
                     .serialize(),
                 ),
@@ -23,6 +23,17 @@ pub struct SetError<P: Property> {
     #[serde(rename = "existingId")]
     #[serde(skip_serializing_if = "Option::is_none")]
     existing_id: Option<Id>,
+
+    #[serde(rename = "scriptError")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    script_error: Option<Box<ScriptError>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ScriptError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
 }
 
 #[derive(Debug, Clone)]
@@ -133,6 +144,7 @@ impl<T: Property> SetError<T> {
             description: None,
             properties: None,
             existing_id: None,
+            script_error: None,
         }
     }
 
@@ -163,6 +175,16 @@ impl<T: Property> SetError<T> {
         self
     }
 
+    pub fn with_script_error(mut self, line: usize, column: usize, message: String) -> Self {
+        self.script_error = Box::new(ScriptError {
+            line,
+            column,
+            message,
+        })
+        .into();
+        self
+    }
+
     pub fn invalid_properties() -> Self {
         Self::new(SetErrorType::InvalidProperties)
     }
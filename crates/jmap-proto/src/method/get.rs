@@ -116,6 +116,8 @@ impl<T: JmapObject> GetRequest<T> {
         }
     }
 
+    /// Returns the requested ids, failing with `requestTooLarge` when more than
+    /// `max_objects_in_get` ids were requested instead of truncating the list.
     pub fn unwrap_ids(&mut self, max_objects_in_get: usize) -> trc::Result<Option<Vec<T::Id>>> {
         if let Some(ids) = self.ids.take() {
             let ids = ids.unwrap();
@@ -129,3 +131,34 @@ impl<T: JmapObject> GetRequest<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::GetRequest;
+    use crate::{
+        object::sieve::Sieve,
+        request::reference::{MaybeIdReference, MaybeResultReference},
+    };
+    use types::id::Id;
+
+    #[test]
+    fn unwrap_ids_max_objects() {
+        let request = |count: u64| GetRequest::<Sieve> {
+            ids: Some(MaybeResultReference::Value(
+                (0..count)
+                    .map(|id| MaybeIdReference::Id(Id::new(id)))
+                    .collect(),
+            )),
+            ..Default::default()
+        };
+
+        assert_eq!(request(3).unwrap_ids(3).unwrap().unwrap().len(), 3);
+        assert!(
+            request(4)
+                .unwrap_ids(3)
+                .unwrap_err()
+                .matches(trc::EventType::Jmap(trc::JmapEvent::RequestTooLarge))
+        );
+        assert_eq!(GetRequest::<Sieve>::default().unwrap_ids(3).unwrap(), None);
+    }
+}
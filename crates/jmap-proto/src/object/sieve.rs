@@ -21,6 +21,7 @@ pub enum SieveProperty {
     Name,
     BlobId,
     IsActive,
+    Source,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -41,6 +42,7 @@ impl Property for SieveProperty {
             SieveProperty::Id => "id",
             SieveProperty::Name => "name",
             SieveProperty::IsActive => "isActive",
+            SieveProperty::Source => "source",
         }
         .into()
     }
@@ -85,6 +87,7 @@ impl SieveProperty {
             b"name" => SieveProperty::Name,
             b"blobId" => SieveProperty::BlobId,
             b"isActive" => SieveProperty::IsActive,
+            b"source" => SieveProperty::Source,
         )
     }
 }
@@ -24,6 +24,7 @@ pub enum VacationResponseProperty {
     Subject,
     TextBody,
     HtmlBody,
+    ReplyOncePerList,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -46,6 +47,7 @@ impl Property for VacationResponseProperty {
             VacationResponseProperty::IsEnabled => "isEnabled",
             VacationResponseProperty::ToDate => "toDate",
             VacationResponseProperty::Subject => "subject",
+            VacationResponseProperty::ReplyOncePerList => "replyOncePerList",
         }
         .into()
     }
@@ -90,6 +92,7 @@ impl VacationResponseProperty {
             b"textBody" => VacationResponseProperty::TextBody,
             b"htmlBody" => VacationResponseProperty::HtmlBody,
             b"subject" => VacationResponseProperty::Subject,
+            b"replyOncePerList" => VacationResponseProperty::ReplyOncePerList,
         )
     }
 }
@@ -251,6 +251,7 @@ impl CalendarEventQuery for Server {
                 ));
             };
             let max_instances = self.core.groupware.max_ical_instances;
+            let max_horizon = self.core.groupware.max_ical_horizon;
             let mut expanded_results = Vec::with_capacity(results.len() as usize);
             let has_uid_comparator = comparators
                 .iter()
@@ -288,7 +289,7 @@ impl CalendarEventQuery for Server {
                 };
                 for expansion in calendar_event
                     .data
-                    .expand(default_tz, time_range)
+                    .expand(default_tz, time_range, max_horizon)
                     .unwrap_or_default()
                 {
                     if expanded_results.len() < max_instances {
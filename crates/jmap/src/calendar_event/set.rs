@@ -25,7 +25,7 @@ use groupware::{
         CalendarEventData, EVENT_DRAFT, EVENT_HIDE_ATTENDEES, EVENT_INVITE_OTHERS,
         EVENT_INVITE_SELF,
     },
-    scheduling::{ItipMessages, event_create::itip_create, event_update::itip_update},
+    scheduling::{ItipLimits, ItipMessages, event_create::itip_create, event_update::itip_update},
 };
 use http_proto::HttpSessionData;
 use jmap_proto::{
@@ -275,12 +275,13 @@ impl CalendarEventSet for Server {
 
             // Check size and quota
             new_calendar_event.size = new_calendar_event.data.event.size() as u32;
-            if new_calendar_event.size as usize > self.core.groupware.max_ical_size {
+            let max_ical_size = self.ical_size_limit(account_id).await?;
+            if new_calendar_event.size as usize > max_ical_size {
                 response.not_updated.append(
                     id,
                     SetError::invalid_properties().with_description(format!(
                         "Event size {} exceeds the maximum allowed size of {} bytes.",
-                        new_calendar_event.size, self.core.groupware.max_ical_size
+                        new_calendar_event.size, max_ical_size
                     )),
                 );
                 continue 'update;
@@ -296,6 +297,7 @@ impl CalendarEventSet for Server {
                 new_calendar_event.data.event,
                 Tz::Floating,
                 self.core.groupware.max_ical_instances,
+                self.core.groupware.max_ical_horizon,
                 &mut next_email_alarm,
             );
 
@@ -315,11 +317,13 @@ impl CalendarEventSet for Server {
                         &mut new_calendar_event.data.event,
                         &old_ical,
                         access_token.emails.as_slice(),
+                        ItipLimits::from(&self.core.groupware),
                     )
                 } else {
                     itip_create(
                         &mut new_calendar_event.data.event,
                         access_token.emails.as_slice(),
+                        ItipLimits::from(&self.core.groupware),
                     )
                 };
 
@@ -594,11 +598,12 @@ impl CalendarEventSet for Server {
 
         // Check size and quota
         let size = ical.size();
-        if size > self.core.groupware.max_ical_size {
+        let max_ical_size = self.ical_size_limit(account_id).await?;
+        if size > max_ical_size {
             return Ok(Err(SetError::invalid_properties().with_description(
                 format!(
                     "Event size {} exceeds the maximum allowed size of {} bytes.",
-                    size, self.core.groupware.max_ical_size
+                    size, max_ical_size
                 ),
             )));
         }
@@ -609,6 +614,7 @@ impl CalendarEventSet for Server {
             ical,
             Tz::Floating,
             self.core.groupware.max_ical_instances,
+            self.core.groupware.max_ical_horizon,
             &mut next_email_alarm,
         );
         event.size = size as u32;
@@ -621,7 +627,11 @@ impl CalendarEventSet for Server {
             && access_token.has_permission(Permission::CalendarSchedulingSend)
             && event.data.event_range_end() > now() as i64
         {
-            match itip_create(&mut event.data.event, access_token.emails.as_slice()) {
+            match itip_create(
+                &mut event.data.event,
+                access_token.emails.as_slice(),
+                ItipLimits::from(&self.core.groupware),
+            ) {
                 Ok(messages) => {
                     if messages.iter().map(|r| r.to.len()).sum::<usize>()
                         < self.core.groupware.itip_outbound_max_recipients
@@ -239,12 +239,13 @@ impl ContactCardSet for Server {
             }
 
             // Check size and quota
-            if new_contact_card.size as usize > self.core.groupware.max_vcard_size {
+            let max_vcard_size = self.vcard_size_limit(account_id).await?;
+            if new_contact_card.size as usize > max_vcard_size {
                 response.not_updated.append(
                     id,
                     SetError::invalid_properties().with_description(format!(
                         "Contact size {} exceeds the maximum allowed size of {} bytes.",
-                        new_contact_card.size, self.core.groupware.max_vcard_size
+                        new_contact_card.size, max_vcard_size
                     )),
                 );
                 continue 'update;
@@ -398,11 +399,12 @@ impl ContactCardSet for Server {
 
         // Check size and quota
         let size = card.size();
-        if size > self.core.groupware.max_vcard_size {
+        let max_vcard_size = self.vcard_size_limit(account_id).await?;
+        if size > max_vcard_size {
             return Ok(Err(SetError::invalid_properties().with_description(
                 format!(
                     "Contact size {} exceeds the maximum allowed size of {} bytes.",
-                    size, self.core.groupware.max_vcard_size
+                    size, max_vcard_size
                 ),
             )));
         }
@@ -14,7 +14,9 @@ use calcard::{
     },
     jscalendar::{JSCalendar, JSCalendarProperty, JSCalendarValue},
 };
-use common::{Server, TinyCalendarPreferences, auth::AccessToken};
+use common::{
+    Server, TinyCalendarPreferences, auth::AccessToken, config::groupware::FreeBusyVisibility,
+};
 use directory::Permission;
 use groupware::{
     cache::GroupwareCache,
@@ -82,6 +84,7 @@ impl PrincipalGetAvailability for Server {
         }
         let return_event_details = !properties.is_empty();
         let max_instances = self.core.groupware.max_ical_instances;
+        let max_horizon = self.core.groupware.max_ical_horizon;
         let filter = TimeRange {
             start: request.utc_start.timestamp(),
             end: request.utc_end.timestamp(),
@@ -101,7 +104,12 @@ impl PrincipalGetAvailability for Server {
 
             // Obtain shared ids
             let is_account_owner = principal_id == account_id;
-            let shared_ids = if !access_token.is_member(account_id) {
+            let is_member = access_token.is_member(account_id);
+            let visibility = self.core.groupware.free_busy_visibility(is_member);
+            if visibility == FreeBusyVisibility::None {
+                continue;
+            }
+            let shared_ids = if !is_member {
                 // Condition: The user has the "mayReadFreeBusy" permission for the calendar.
                 let shared_ids = resources.shared_items(
                     access_token,
@@ -302,17 +310,28 @@ impl PrincipalGetAvailability for Server {
                     continue 'next_event;
                 }
 
-                for expansion in event.data.expand(default_tz, filter).unwrap_or_default() {
+                for expansion in event
+                    .data
+                    .expand(default_tz, filter, max_horizon)
+                    .unwrap_or_default()
+                {
                     let Some(busy_status) = matching_component_ids.get(&expansion.comp_id) else {
                         continue;
                     };
                     if periods.len() < max_instances {
+                        // Busy-only visibility discloses neither the status nor the event
+                        let is_full = visibility == FreeBusyVisibility::Full;
                         periods.push(FreeBusyResult {
                             utc_start: expansion.start,
                             utc_end: expansion.end,
-                            busy_status: *busy_status,
+                            busy_status: if is_full {
+                                *busy_status
+                            } else {
+                                BusyStatus::Confirmed
+                            },
                             expansion_id: expansion.comp_id,
                             document_id,
+                            is_full,
                         });
                     } else {
                         return Err(trc::JmapEvent::RequestTooLarge
@@ -382,6 +401,7 @@ struct FreeBusyResult {
     busy_status: BusyStatus,
     expansion_id: u32,
     document_id: u32,
+    is_full: bool,
 }
 
 impl From<FreeBusyResult> for BusyPeriod {
@@ -390,20 +410,21 @@ impl From<FreeBusyResult> for BusyPeriod {
             utc_start: UTCDate::from_timestamp(value.utc_start),
             utc_end: UTCDate::from_timestamp(value.utc_end),
             busy_status: Some(value.busy_status),
-            event: JSCalendar(Value::Object(Map::from(vec![
-                (
-                    Key::Property(JSCalendarProperty::Id),
-                    Value::Element(JSCalendarValue::Id(<Id as CalendarSyntheticId>::new(
-                        value.expansion_id,
-                        value.document_id,
-                    ))),
-                ),
-                (
-                    Key::Property(JSCalendarProperty::BaseEventId),
-                    Value::Element(JSCalendarValue::Id(Id::from(value.document_id))),
-                ),
-            ])))
-            .into(),
+            event: value.is_full.then(|| {
+                JSCalendar(Value::Object(Map::from(vec![
+                    (
+                        Key::Property(JSCalendarProperty::Id),
+                        Value::Element(JSCalendarValue::Id(<Id as CalendarSyntheticId>::new(
+                            value.expansion_id,
+                            value.document_id,
+                        ))),
+                    ),
+                    (
+                        Key::Property(JSCalendarProperty::BaseEventId),
+                        Value::Element(JSCalendarValue::Id(Id::from(value.document_id))),
+                    ),
+                ])))
+            }),
         }
     }
 }
@@ -12,8 +12,11 @@ use jmap_proto::{
     object::sieve::{Sieve, SieveProperty, SieveValue},
 };
 use jmap_tools::{Map, Value};
-use store::{ValueKey, write::{AlignedBytes, Archive}};
 use std::future::Future;
+use store::{
+    ValueKey,
+    write::{AlignedBytes, Archive},
+};
 use trc::AddContext;
 use types::{
     blob::{BlobClass, BlobId, BlobSection},
@@ -123,6 +126,23 @@ impl SieveScriptGet for Server {
                             Value::Element(SieveValue::BlobId(blob_id)),
                         );
                     }
+                    SieveProperty::Source => {
+                        // The blob holds the script source followed by the compiled script
+                        let source = self
+                            .blob_store()
+                            .get_blob(
+                                sieve.blob_hash.0.as_slice(),
+                                0..u32::from(sieve.size) as usize,
+                            )
+                            .await
+                            .caused_by(trc::location!())?
+                            .map(|bytes| {
+                                Value::Str(String::from_utf8_lossy(&bytes).into_owned().into())
+                            })
+                            .unwrap_or(Value::Null);
+
+                        result.insert_unchecked(SieveProperty::Source, source);
+                    }
                 }
             }
             response.list.push(result.into());
@@ -6,5 +6,20 @@
 
 pub mod get;
 pub mod query;
+pub mod rollback;
 pub mod set;
 pub mod validate;
+
+use jmap_proto::error::set::{SetError, SetErrorType};
+use jmap_tools::Property;
+use sieve::compiler::{CompileError, ErrorType};
+
+pub(crate) fn compile_error<P: Property>(err: CompileError) -> SetError<P> {
+    SetError::new(if let ErrorType::ScriptTooLong = &err.error_type() {
+        SetErrorType::TooLarge
+    } else {
+        SetErrorType::InvalidScript
+    })
+    .with_script_error(err.line_num(), err.line_pos(), err.to_string())
+    .with_description(err.to_string())
+}
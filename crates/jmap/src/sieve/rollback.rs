@@ -0,0 +1,127 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::{Server, auth::AccessToken, storage::index::ObjectIndexBuilder};
+use email::sieve::{SeenIdsSeed, SieveScript, SieveScriptVersion, versions::SieveScriptVersioning};
+use std::future::Future;
+use store::{
+    Serialize, ValueKey,
+    write::{AlignedBytes, Archive, Archiver, BatchBuilder, BlobLink, BlobOp, now},
+};
+use trc::AddContext;
+use types::{blob_hash::BlobHash, collection::Collection, field::SieveField};
+
+pub trait SieveScriptRollback: Sync + Send {
+    fn sieve_script_rollback(
+        &self,
+        account_id: u32,
+        document_id: u32,
+        version: usize,
+        access_token: &AccessToken,
+    ) -> impl Future<Output = trc::Result<bool>> + Send;
+}
+
+impl SieveScriptRollback for Server {
+    async fn sieve_script_rollback(
+        &self,
+        account_id: u32,
+        document_id: u32,
+        version: usize,
+        access_token: &AccessToken,
+    ) -> trc::Result<bool> {
+        // Fetch record
+        let Some(sieve_) = self
+            .store()
+            .get_value::<Archive<AlignedBytes>>(ValueKey::archive(
+                account_id,
+                Collection::SieveScript,
+                document_id,
+            ))
+            .await?
+        else {
+            return Ok(false);
+        };
+        let sieve = sieve_
+            .to_unarchived::<SieveScript>()
+            .caused_by(trc::location!())?;
+
+        // Obtain the requested version, 0 being the most recent one
+        let mut versions = self.sieve_script_versions(account_id, document_id).await?;
+        if version >= versions.versions.len() {
+            return Ok(false);
+        }
+        let restored = versions.versions.remove(version);
+        let seen_ids_seed = versions.seen_ids_seed(&sieve_.version);
+
+        // The current script becomes the most recent version
+        let current_hash = BlobHash::from(&sieve.inner.blob_hash);
+        let evicted = versions.push(
+            SieveScriptVersion {
+                blob_hash: current_hash.clone(),
+                size: u32::from(sieve.inner.size),
+                replaced_at: now(),
+            },
+            self.core.jmap.sieve_max_versions.max(1),
+        );
+        let changes = sieve
+            .deserialize::<SieveScript>()
+            .caused_by(trc::location!())?
+            .with_blob_hash(restored.blob_hash.clone())
+            .with_size(restored.size);
+
+        // Seen ids are hashed with the archive hash, which changes with the restored
+        // blob. Carry the current seed forward so vacation responses are not re-sent.
+        versions.seen_ids = Archive::<AlignedBytes>::extract_hash(
+            &Archiver::new(changes.clone())
+                .serialize()
+                .caused_by(trc::location!())?,
+        )
+        .map(|archive_hash| SeenIdsSeed {
+            archive_hash,
+            seed: seen_ids_seed,
+        });
+
+        let mut batch = BatchBuilder::new();
+        batch
+            .with_account_id(account_id)
+            .with_collection(Collection::SieveScript)
+            .with_document(document_id)
+            .custom(
+                ObjectIndexBuilder::new()
+                    .with_current(sieve)
+                    .with_changes(changes)
+                    .with_access_token(access_token),
+            )
+            .caused_by(trc::location!())?
+            .set(
+                BlobOp::Link {
+                    hash: current_hash,
+                    to: BlobLink::Document,
+                },
+                Vec::new(),
+            )
+            .set(
+                SieveField::Versions,
+                Archiver::new(versions.clone())
+                    .serialize()
+                    .caused_by(trc::location!())?,
+            );
+        for version in evicted {
+            if version.blob_hash != restored.blob_hash && !versions.contains(&version.blob_hash) {
+                batch.clear(BlobOp::Link {
+                    hash: version.blob_hash,
+                    to: BlobLink::Document,
+                });
+            }
+        }
+        batch.commit_point();
+
+        self.commit_batch(batch)
+            .await
+            .caused_by(trc::location!())
+            .map(|_| true)
+    }
+}
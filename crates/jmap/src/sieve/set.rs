@@ -4,14 +4,15 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use crate::{blob::download::BlobDownload, changes::state::StateManager};
+use crate::{blob::download::BlobDownload, changes::state::StateManager, sieve::compile_error};
 use common::{
     Server,
     auth::{AccessToken, ResourceToken},
     storage::index::ObjectIndexBuilder,
 };
 use email::sieve::{
-    ArchivedSieveScript, SieveScript, delete::SieveScriptDelete, ingest::SieveScriptIngest,
+    ArchivedSieveScript, SieveScript, delete::SieveScriptDelete, include::SieveScriptIncludes,
+    ingest::SieveScriptIngest, versions::SieveScriptVersioning,
 };
 use http_proto::HttpSessionData;
 use jmap_proto::{
@@ -24,14 +25,16 @@ use jmap_proto::{
 };
 use jmap_tools::{Key, Map, Value};
 use rand::distr::Alphanumeric;
-use sieve::compiler::ErrorType;
 use std::future::Future;
 use store::{
-    Serialize, SerializeInfallible, ValueKey, rand::{Rng, rng}, write::{AlignedBytes, Archive, Archiver, BatchBuilder}
+    Serialize, SerializeInfallible, ValueKey,
+    rand::{Rng, rng},
+    write::{AlignedBytes, Archive, Archiver, BatchBuilder},
 };
 use trc::AddContext;
 use types::{
     blob::{BlobClass, BlobId, BlobSection},
+    blob_hash::BlobHash,
     collection::{Collection, SyncCollection},
     field::{PrincipalField, SieveField},
     id::Id,
@@ -208,6 +211,10 @@ impl SieveScriptSet for Server {
                 let sieve = sieve_
                     .to_unarchived::<SieveScript>()
                     .caused_by(trc::location!())?;
+                let previous = (
+                    BlobHash::from(&sieve.inner.blob_hash),
+                    u32::from(sieve.inner.size),
+                );
 
                 match self
                     .sieve_set_item(
@@ -254,8 +261,20 @@ impl SieveScriptSet for Server {
                         // Write record
                         batch
                             .custom(builder.with_access_token(ctx.access_token))
-                            .caused_by(trc::location!())?
-                            .commit_point();
+                            .caused_by(trc::location!())?;
+
+                        // Keep the replaced script for rollback
+                        if let Some(blob_id) = &blob_id {
+                            self.sieve_script_keep_version(
+                                account_id,
+                                document_id,
+                                previous,
+                                &blob_id.hash,
+                                &mut batch,
+                            )
+                            .await?;
+                        }
+                        batch.commit_point();
 
                         // Update blobId property if needed
                         let mut result = Map::with_capacity(1);
@@ -488,19 +507,31 @@ impl SieveScriptSet for Server {
                     // Compile script
                     match self.core.sieve.untrusted_compiler.compile(&bytes) {
                         Ok(script) => {
+                            // Reject include cycles and excessive nesting
+                            if let Err(reason) = self
+                                .sieve_script_resolve_includes(
+                                    ctx.resource_token.account_id,
+                                    &changes.name,
+                                    &script,
+                                )
+                                .await?
+                            {
+                                return Ok(Err(SetError::new(SetErrorType::InvalidScript)
+                                    .with_property(SieveProperty::BlobId)
+                                    .with_description(reason)));
+                            }
+
                             changes.size = bytes.len() as u32;
-                            bytes.extend(Archiver::new(script).untrusted().serialize().caused_by(trc::location!())?);
+                            bytes.extend(
+                                Archiver::new(script)
+                                    .untrusted()
+                                    .serialize()
+                                    .caused_by(trc::location!())?,
+                            );
                             bytes.into()
                         }
                         Err(err) => {
-                            return Ok(Err(SetError::new(
-                                if let ErrorType::ScriptTooLong = &err.error_type() {
-                                    SetErrorType::TooLarge
-                                } else {
-                                    SetErrorType::InvalidScript
-                                },
-                            )
-                            .with_description(err.to_string())));
+                            return Ok(Err(compile_error(err)));
                         }
                     }
                 } else {
@@ -4,7 +4,7 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use crate::blob::download::BlobDownload;
+use crate::{blob::download::BlobDownload, sieve::compile_error};
 use common::{Server, auth::AccessToken};
 use jmap_proto::{
     error::set::{SetError, SetErrorType},
@@ -37,9 +37,7 @@ impl SieveScriptValidate for Server {
                         .map(|bytes| self.core.sieve.untrusted_compiler.compile(&bytes))
                     {
                         Some(Ok(_)) => None,
-                        Some(Err(err)) => SetError::new(SetErrorType::InvalidScript)
-                            .with_description(err.to_string())
-                            .into(),
+                        Some(Err(err)) => compile_error(err).into(),
                         None => SetError::new(SetErrorType::BlobNotFound).into(),
                     }
                 }
@@ -54,6 +54,7 @@ impl VacationResponseGet for Server {
             VacationResponseProperty::Subject,
             VacationResponseProperty::TextBody,
             VacationResponseProperty::HtmlBody,
+            VacationResponseProperty::ReplyOncePerList,
         ]);
         let mut response = GetResponse {
             account_id: request.account_id.into(),
@@ -155,6 +156,12 @@ impl VacationResponseGet for Server {
                                     vacation.and_then(|r| r.html_body.as_ref()),
                                 );
                             }
+                            VacationResponseProperty::ReplyOncePerList => {
+                                result.insert_unchecked(
+                                    VacationResponseProperty::ReplyOncePerList,
+                                    vacation.is_some_and(|r| r.reply_once_per_list),
+                                );
+                            }
                         }
                     }
                     response.list.push(result.into());
@@ -213,6 +213,17 @@ impl VacationResponseSet for Server {
                     (Key::Property(VacationResponseProperty::IsEnabled), Value::Null) => {
                         is_active = false;
                     }
+                    (
+                        Key::Property(VacationResponseProperty::ReplyOncePerList),
+                        Value::Bool(value),
+                    ) => {
+                        vacation.reply_once_per_list = value;
+                        build_script = true;
+                    }
+                    (Key::Property(VacationResponseProperty::ReplyOncePerList), Value::Null) => {
+                        vacation.reply_once_per_list = false;
+                        build_script = true;
+                    }
                     (
                         Key::Property(
                             VacationResponseProperty::Subject
@@ -368,7 +379,17 @@ impl VacationResponseSet for Server {
     fn build_script(&self, obj: &mut SieveScript) -> trc::Result<Vec<u8>> {
         // Build Sieve script
         let mut script = Vec::with_capacity(1024);
-        script.extend_from_slice(b"require [\"vacation\", \"relational\", \"date\"];\r\n\r\n");
+        let reply_once_per_list = obj
+            .vacation_response
+            .as_ref()
+            .is_some_and(|v| v.reply_once_per_list);
+        if reply_once_per_list {
+            script.extend_from_slice(
+                b"require [\"vacation\", \"relational\", \"date\", \"duplicate\"];\r\n\r\n",
+            );
+        } else {
+            script.extend_from_slice(b"require [\"vacation\", \"relational\", \"date\"];\r\n\r\n");
+        }
         let mut num_blocks = 0;
 
         // Add start date
@@ -387,6 +408,34 @@ impl VacationResponseSet for Server {
             num_blocks += 1;
         }
 
+        if reply_once_per_list {
+            // Reply to mailing list messages at most once per List-Id, other
+            // messages are deduplicated by sender as usual
+            script.extend_from_slice(
+                concat!(
+                    "if not anyof (header :matches \"Auto-Submitted\" \"auto-*\", ",
+                    "header :is \"Precedence\" [\"bulk\", \"junk\"]) {\r\n",
+                    "if anyof (not exists \"List-Id\", ",
+                    "not duplicate :header \"List-Id\") {\r\n"
+                )
+                .as_bytes(),
+            );
+            num_blocks += 2;
+        } else {
+            // Do not reply to automatic or mailing list messages (RFC 3834)
+            script.extend_from_slice(
+                concat!(
+                    "if not anyof (header :matches \"Auto-Submitted\" \"auto-*\", ",
+                    "exists \"List-Id\", ",
+                    "header :is \"Precedence\" [\"bulk\", \"list\", \"junk\"]) {\r\n"
+                )
+                .as_bytes(),
+            );
+            num_blocks += 1;
+        }
+
+        // Without :days the runtime's default vacation expiry applies, so changes
+        // to the renotify interval also cover existing vacation responses
         script.extend_from_slice(b"vacation :mime ");
         if let Some(value) = obj
             .vacation_response
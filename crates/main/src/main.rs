@@ -9,14 +9,17 @@
 #![warn(clippy::cast_possible_wrap)]
 #![warn(clippy::cast_sign_loss)]
 
-use common::{config::server::ServerProtocol, core::BuildServer, manager::boot::BootManager};
+use common::{
+    config::server::ServerProtocol, core::BuildServer, ipc::QueueEvent, manager::boot::BootManager,
+};
 use http::HttpSessionManager;
 use imap::core::ImapSessionManager;
 use managesieve::core::ManageSieveSessionManager;
 use pop3::Pop3SessionManager;
 use services::{StartServices, broadcast::subscriber::spawn_broadcast_subscriber};
 use smtp::{StartQueueManager, core::SmtpSessionManager};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
 use trc::Collector;
 use utils::wait_for_shutdown;
 
@@ -95,6 +98,7 @@ async fn main() -> std::io::Result<()> {
     });
 
     // Start broadcast subscriber
+    let inner = init.inner.clone();
     spawn_broadcast_subscriber(init.inner, shutdown_rx);
 
     // Wait for shutdown signal
@@ -103,9 +107,31 @@ async fn main() -> std::io::Result<()> {
     // Shutdown collector
     Collector::shutdown();
 
+    // Drain the queue before the services stop it
+    let drain = if let Some(timeout) = inner.build_server().core.smtp.queue.drain_timeout {
+        let (tx, rx) = oneshot::channel();
+        inner
+            .ipc
+            .queue_tx
+            .send(QueueEvent::Drain {
+                deadline: Instant::now() + timeout,
+                tx,
+            })
+            .await
+            .is_ok()
+            .then_some((timeout, rx))
+    } else {
+        None
+    };
+
     // Stop services
     let _ = shutdown_tx.send(true);
 
+    // Wait for the queue to deliver due messages before exiting
+    if let Some((timeout, rx)) = drain {
+        let _ = tokio::time::timeout(timeout, rx).await;
+    }
+
     // Wait for services to finish
     tokio::time::sleep(Duration::from_secs(1)).await;
 
@@ -7,7 +7,7 @@
 use crate::core::{Command, ResponseCode, Session, StatusResponse};
 use common::{listener::SessionStream, storage::index::ObjectIndexBuilder};
 use directory::Permission;
-use email::sieve::SieveScript;
+use email::sieve::{SieveScript, include::SieveScriptIncludes, versions::SieveScriptVersioning};
 use imap_proto::receiver::Request;
 use sieve::compiler::ErrorType;
 use std::time::Instant;
@@ -16,7 +16,7 @@ use store::{
     write::{AlignedBytes, Archive, Archiver, BatchBuilder},
 };
 use trc::AddContext;
-use types::{collection::Collection, field::SieveField};
+use types::{blob_hash::BlobHash, collection::Collection, field::SieveField};
 
 impl<T: SessionStream> Session<T> {
     pub async fn handle_putscript(&mut self, request: Request<Command>) -> trc::Result<Vec<u8>> {
@@ -76,6 +76,12 @@ impl<T: SessionStream> Session<T> {
             .compile(&script_bytes)
         {
             Ok(compiled_script) => {
+                // Reject include cycles and excessive nesting
+                self.server
+                    .sieve_script_resolve_includes(account_id, &name, &compiled_script)
+                    .await?
+                    .map_err(|reason| trc::ManageSieveEvent::Error.into_err().details(reason))?;
+
                 script_bytes.extend(
                     Archiver::new(compiled_script)
                         .untrusted()
@@ -119,6 +125,10 @@ impl<T: SessionStream> Session<T> {
             let script = script_
                 .to_unarchived::<SieveScript>()
                 .caused_by(trc::location!())?;
+            let previous = (
+                BlobHash::from(&script.inner.blob_hash),
+                u32::from(script.inner.size),
+            );
 
             // Write script blob
             let (blob_hash, blob_hold) = self
@@ -147,6 +157,17 @@ impl<T: SessionStream> Session<T> {
                 .caused_by(trc::location!())?
                 .clear(blob_hold);
 
+            // Keep the replaced script for rollback
+            self.server
+                .sieve_script_keep_version(
+                    account_id,
+                    document_id,
+                    previous,
+                    &blob_hash,
+                    &mut batch,
+                )
+                .await?;
+
             self.server
                 .commit_batch(batch)
                 .await
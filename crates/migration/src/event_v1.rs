@@ -110,6 +110,7 @@ pub(crate) async fn migrate_calendar_events_v012(server: &Server) -> trc::Result
                             migrate_icalendar_v02(event.data.event),
                             calcard_latest::common::timezone::Tz::Floating,
                             server.core.groupware.max_ical_instances,
+                            server.core.groupware.max_ical_horizon,
                             &mut next_email_alarm,
                         ),
                         preferences: Default::default(),
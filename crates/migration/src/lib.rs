@@ -12,6 +12,7 @@ use crate::{
     v012::migrate_v0_12,
     v013::migrate_v0_13,
     v014::{SUBSPACE_BITMAP_ID, migrate_principal_v0_14, migrate_v0_14},
+    v015::migrate_v0_15,
 };
 use common::{DATABASE_SCHEMA_VERSION, Server, manager::boot::DEFAULT_SETTINGS};
 use std::time::Duration;
@@ -59,6 +60,7 @@ pub mod v011;
 pub mod v012;
 pub mod v013;
 pub mod v014;
+pub mod v015;
 
 const LOCK_WAIT_TIME_ACCOUNT: u64 = 3 * 60;
 const LOCK_WAIT_TIME_CORE: u64 = 5 * 60;
@@ -107,6 +109,7 @@ pub async fn try_migrate(server: &Server) -> trc::Result<()> {
                         .caused_by(trc::location!())?;
                     migrate_v0_13(server).await.caused_by(trc::location!())?;
                     migrate_v0_14(server).await.caused_by(trc::location!())?;
+                    migrate_v0_15(server).await.caused_by(trc::location!())?;
                 }
                 2 => {
                     migrate_v0_12(server, false)
@@ -114,13 +117,19 @@ pub async fn try_migrate(server: &Server) -> trc::Result<()> {
                         .caused_by(trc::location!())?;
                     migrate_v0_13(server).await.caused_by(trc::location!())?;
                     migrate_v0_14(server).await.caused_by(trc::location!())?;
+                    migrate_v0_15(server).await.caused_by(trc::location!())?;
                 }
                 3 => {
                     migrate_v0_13(server).await.caused_by(trc::location!())?;
                     migrate_v0_14(server).await.caused_by(trc::location!())?;
+                    migrate_v0_15(server).await.caused_by(trc::location!())?;
                 }
                 4 => {
                     migrate_v0_14(server).await.caused_by(trc::location!())?;
+                    migrate_v0_15(server).await.caused_by(trc::location!())?;
+                }
+                5 => {
+                    migrate_v0_15(server).await.caused_by(trc::location!())?;
                 }
                 _ => {
                     panic!("Unknown migration version: {version}");
@@ -169,6 +178,11 @@ pub async fn try_migrate(server: &Server) -> trc::Result<()> {
         }
         Some(4) => {
             migrate_v0_14(server).await.caused_by(trc::location!())?;
+            migrate_v0_15(server).await.caused_by(trc::location!())?;
+            false
+        }
+        Some(5) => {
+            migrate_v0_15(server).await.caused_by(trc::location!())?;
             false
         }
         Some(version) => {
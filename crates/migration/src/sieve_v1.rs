@@ -217,6 +217,7 @@ impl TryFromLegacy for VacationResponse {
                 .get(&Property::HtmlBody)
                 .as_string()
                 .map(|s| s.to_string()),
+            reply_once_per_list: false,
         };
 
         if vacation.from_date.is_some()
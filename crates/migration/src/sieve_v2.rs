@@ -47,7 +47,7 @@ pub(crate) async fn migrate_sieve_v013(server: &Server, account_id: u32) -> trc:
                         name: old_sieve.name,
                         blob_hash: old_sieve.blob_hash,
                         size: old_sieve.size,
-                        vacation_response: old_sieve.vacation_response,
+                        vacation_response: old_sieve.vacation_response.map(Into::into),
                     };
 
                     let mut batch = BatchBuilder::new();
@@ -94,6 +94,66 @@ pub(crate) async fn migrate_sieve_v013(server: &Server, account_id: u32) -> trc:
     Ok(num_migrated)
 }
 
+pub(crate) async fn migrate_sieve_v015(server: &Server, account_id: u32) -> trc::Result<u64> {
+    let mut migrate = Vec::new();
+
+    server
+        .archives(
+            account_id,
+            Collection::SieveScript,
+            &(),
+            |document_id, archive| {
+                match archive.deserialize_untrusted::<LegacySieveScript>() {
+                    Ok(legacy) => {
+                        migrate.push((document_id, legacy));
+                    }
+                    Err(err) => {
+                        if archive.deserialize_untrusted::<SieveScript>().is_err() {
+                            return Err(err
+                                .account_id(account_id)
+                                .document_id(document_id)
+                                .caused_by(trc::location!()));
+                        }
+                    }
+                }
+
+                Ok(true)
+            },
+        )
+        .await
+        .caused_by(trc::location!())?;
+
+    let num_migrated = migrate.len() as u64;
+    for (document_id, legacy) in migrate {
+        let script = SieveScript {
+            name: legacy.name,
+            blob_hash: legacy.blob_hash,
+            size: legacy.size,
+            vacation_response: legacy.vacation_response.map(Into::into),
+        };
+
+        let mut batch = BatchBuilder::new();
+        batch
+            .with_account_id(account_id)
+            .with_collection(Collection::SieveScript)
+            .with_document(document_id)
+            .set(
+                Field::ARCHIVE,
+                Archiver::new(script)
+                    .serialize()
+                    .caused_by(trc::location!())?,
+            );
+
+        server
+            .store()
+            .write(batch.build_all())
+            .await
+            .caused_by(trc::location!())?;
+    }
+
+    Ok(num_migrated)
+}
+
 #[derive(
     rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Default, Clone, PartialEq, Eq,
 )]
@@ -103,5 +163,41 @@ pub struct SieveScriptV2 {
     pub is_active: bool,
     pub blob_hash: BlobHash,
     pub size: u32,
-    pub vacation_response: Option<VacationResponse>,
+    pub vacation_response: Option<LegacyVacationResponse>,
+}
+
+#[derive(
+    rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Default, Clone, PartialEq, Eq,
+)]
+#[rkyv(derive(Debug))]
+pub struct LegacySieveScript {
+    pub name: String,
+    pub blob_hash: BlobHash,
+    pub size: u32,
+    pub vacation_response: Option<LegacyVacationResponse>,
+}
+
+#[derive(
+    rkyv::Archive, rkyv::Deserialize, rkyv::Serialize, Debug, Default, Clone, PartialEq, Eq,
+)]
+#[rkyv(derive(Debug))]
+pub struct LegacyVacationResponse {
+    pub from_date: Option<u64>,
+    pub to_date: Option<u64>,
+    pub subject: Option<String>,
+    pub text_body: Option<String>,
+    pub html_body: Option<String>,
+}
+
+impl From<LegacyVacationResponse> for VacationResponse {
+    fn from(legacy: LegacyVacationResponse) -> Self {
+        VacationResponse {
+            from_date: legacy.from_date,
+            to_date: legacy.to_date,
+            subject: legacy.subject,
+            text_body: legacy.text_body,
+            html_body: legacy.html_body,
+            reply_once_per_list: false,
+        }
+    }
 }
@@ -0,0 +1,79 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::sieve_v2::migrate_sieve_v015;
+use common::Server;
+use directory::backend::internal::manage::ManageDirectory;
+use std::sync::Arc;
+use store::rand::{self, seq::SliceRandom};
+use tokio::sync::Semaphore;
+use trc::AddContext;
+
+pub async fn migrate_v0_15(server: &Server) -> trc::Result<()> {
+    // Migrate account data
+    let mut principal_ids = server
+        .store()
+        .principal_ids(None, None)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<Vec<_>>();
+    principal_ids.shuffle(&mut rand::rng());
+    let semaphore = Arc::new(Semaphore::new(
+        std::env::var("NUM_THREADS")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or_else(|| num_cpus::get().min(2) * 2),
+    ));
+    let mut tasks = Vec::with_capacity(principal_ids.len());
+    let num_principals = principal_ids.len();
+    for principal_id in principal_ids {
+        let permit = semaphore.clone().acquire_owned().await.unwrap();
+        let _server = server.clone();
+        tasks.push(tokio::spawn(async move {
+            let result = migrate_principal_v0_15(&_server, principal_id).await;
+            drop(permit);
+            result
+        }));
+    }
+    futures::future::join_all(tasks)
+        .await
+        .into_iter()
+        .collect::<Result<trc::Result<()>, _>>()
+        .map_err(|err| {
+            trc::EventType::Server(trc::ServerEvent::ThreadError)
+                .reason(err)
+                .caused_by(trc::location!())
+                .details("Join Error")
+        })??;
+
+    trc::event!(
+        Server(trc::ServerEvent::Startup),
+        Details = format!("Migrated {num_principals} accounts")
+    );
+
+    trc::event!(
+        Server(trc::ServerEvent::Startup),
+        Details = format!("Migration to schema version 6 completed")
+    );
+
+    Ok(())
+}
+
+pub(crate) async fn migrate_principal_v0_15(server: &Server, account_id: u32) -> trc::Result<()> {
+    let scripts = migrate_sieve_v015(server, account_id)
+        .await
+        .caused_by(trc::location!())?;
+
+    if scripts > 0 {
+        trc::event!(
+            Server(trc::ServerEvent::Startup),
+            Details = format!("Migrated account {account_id}: {scripts} sieve scripts")
+        );
+    }
+
+    Ok(())
+}
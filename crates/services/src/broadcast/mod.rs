@@ -93,6 +93,12 @@ impl BroadcastBatch<Vec<BroadcastEvent>> {
                 BroadcastEvent::ReloadSpamFilter => {
                     serialized.push(8u8);
                 }
+                BroadcastEvent::ReloadPublicSuffix => {
+                    serialized.push(9u8);
+                }
+                BroadcastEvent::ReloadSieveScripts => {
+                    serialized.push(10u8);
+                }
             }
         }
         serialized
@@ -191,6 +197,10 @@ where
 
                 8 => Ok(Some(BroadcastEvent::ReloadSpamFilter)),
 
+                9 => Ok(Some(BroadcastEvent::ReloadPublicSuffix)),
+
+                10 => Ok(Some(BroadcastEvent::ReloadSieveScripts)),
+
                 _ => Err(()),
             }
         } else {
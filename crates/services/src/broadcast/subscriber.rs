@@ -197,6 +197,29 @@ pub fn spawn_broadcast_subscriber(inner: Arc<Inner>, mut shutdown_rx: watch::Rec
                                                     );
                                                 }
                                             }
+                                            BroadcastEvent::ReloadPublicSuffix => {
+                                                if let Err(err) = inner.build_server().reload_public_suffix().await {
+                                                    trc::error!(
+                                                        err.details("Failed to reload public suffix list")
+                                                            .caused_by(trc::location!())
+                                                    );
+                                                }
+                                            }
+                                            BroadcastEvent::ReloadSieveScripts => {
+                                                match inner.build_server().reload_sieve_scripts().await {
+                                                    Ok(result) => {
+                                                        if let Some(new_core) = result.new_core {
+                                                            inner.shared_core.store(new_core.into());
+                                                        }
+                                                    }
+                                                    Err(err) => {
+                                                        trc::error!(
+                                                            err.details("Failed to reload Sieve scripts")
+                                                                .caused_by(trc::location!())
+                                                        );
+                                                    }
+                                                }
+                                            }
                                         }
                                     }
                                     Ok(None) => break,
@@ -273,5 +296,11 @@ fn log_event(event: &BroadcastEvent) -> trc::Value {
             trc::Value::Array(vec!["ReloadPushServers".into(), (*account_id).into()])
         }
         BroadcastEvent::ReloadSpamFilter => CompactString::const_new("ReloadSpamFilter").into(),
+        BroadcastEvent::ReloadPublicSuffix => {
+            CompactString::const_new("ReloadPublicSuffix").into()
+        }
+        BroadcastEvent::ReloadSieveScripts => {
+            CompactString::const_new("ReloadSieveScripts").into()
+        }
     }
 }
@@ -25,7 +25,6 @@ use common::{
         spamfilter::SpamFilterAction,
     },
     listener::SessionStream,
-    psl,
     scripts::ScriptModification,
 };
 use mail_auth::{
@@ -261,7 +260,7 @@ impl<T: SessionStream> Session<T> {
                                 },
                                 spf_output,
                                 domain_suffix_fn: |domain| {
-                                    psl::domain_str(domain).unwrap_or(domain)
+                                    self.server.psl_domain(domain).unwrap_or(domain)
                                 },
                             },
                         ))
@@ -15,18 +15,20 @@ use crate::outbound::mta_sts::lookup::MtaStsLookup;
 use crate::outbound::mta_sts::verify::VerifyPolicy;
 use crate::outbound::{client::StartTlsResult, dane::verify::TlsaVerify};
 use crate::queue::dsn::SendDsn;
+use crate::queue::manager::NextEvent;
 use crate::queue::spool::SmtpSpool;
 use crate::queue::throttle::IsAllowed;
 use crate::queue::{
-    Error, FROM_REPORT, HostResponse, MessageWrapper, QueueEnvelope, QueuedMessage, Status,
+    Error, FROM_REPORT, HostResponse, MessageWrapper, QueueEnvelope, QueuedMessage,
+    RCPT_FALLBACK_ROUTE, RCPT_HELD, Status,
 };
 use crate::reporting::SmtpReporting;
 use crate::{queue::ErrorDetails, reporting::tls::TlsRptOptions};
 use ahash::AHashMap;
 use common::Server;
-use common::config::smtp::queue::RoutingStrategy;
+use common::config::smtp::queue::{QueueExpiry, RoutingStrategy};
 use common::config::{server::ServerProtocol, smtp::report::AggregateFrequency};
-use common::ipc::{PolicyType, QueueEvent, QueueEventStatus, TlsEvent};
+use common::ipc::{PolicyType, QueueEvent, QueueEventStatus, QueueStateEvent, TlsEvent};
 use compact_str::ToCompactString;
 use mail_auth::{
     mta_sts::TlsRpt,
@@ -39,10 +41,11 @@ use std::{
     time::Instant,
 };
 use store::write::{BatchBuilder, QueueClass, ValueClass, now};
+use tokio::task::JoinHandle;
 use trc::{DaneEvent, DeliveryEvent, MtaStsEvent, ServerEvent, TlsRptEvent};
 
 impl QueuedMessage {
-    pub fn try_deliver(self, server: Server) {
+    pub fn try_deliver(self, server: Server) -> JoinHandle<()> {
         #![allow(clippy::large_futures)]
         tokio::spawn(async move {
             // Lock queue event
@@ -83,9 +86,14 @@ impl QueuedMessage {
                         Total = message.message.recipients.len(),
                     );
 
-                    // Attempt delivery
+                    // Attempt delivery, wake-ups for a due delay notification only send the DSN
                     let start_time = Instant::now();
-                    let queue_event = self.deliver_task(server.clone(), message).await;
+                    let queue_event = match message.message.next_event(self.queue_name.into()) {
+                        Some(event @ NextEvent::Notify(_)) if event.is_due(now()) => {
+                            self.notify_task(server.clone(), message).await
+                        }
+                        _ => self.deliver_task(server.clone(), message).await,
+                    };
 
                     trc::event!(
                         Delivery(DeliveryEvent::AttemptEnd),
@@ -143,11 +151,20 @@ impl QueuedMessage {
                     CausedBy = trc::location!(),
                 );
             }
-        });
+        })
+    }
+
+    async fn notify_task(self, server: Server, mut message: MessageWrapper) -> QueueEventStatus {
+        // Send due Delivery Status Notifications, recipients are retried on their own schedule
+        server.send_dsn(&mut message).await;
+        message.save_changes(&server, self.due.into()).await;
+
+        QueueEventStatus::Deferred
     }
 
     async fn deliver_task(self, server: Server, mut message: MessageWrapper) -> QueueEventStatus {
         // Check that the message still has recipients to be delivered
+        let final_rcpts = message.final_recipients();
         let has_pending_delivery = message.has_pending_delivery();
         let span_id = message.span_id;
 
@@ -168,13 +185,19 @@ impl QueuedMessage {
                 );
 
                 // All message recipients expired, do not re-queue. (DSN has been already sent)
-                message.remove(&server, self.due.into()).await;
+                let state_events = message.final_state_events(&final_rcpts);
+                if message.remove(&server, self.due.into()).await {
+                    server.notify_queue_states(state_events);
+                }
 
                 return QueueEventStatus::Completed;
             }
             _ => {
                 // Re-queue the message if its not yet due for delivery
-                message.save_changes(&server, self.due.into()).await;
+                let state_events = message.final_state_events(&final_rcpts);
+                if message.save_changes(&server, self.due.into()).await {
+                    server.notify_queue_states(state_events);
+                }
                 return QueueEventStatus::Deferred;
             }
         }
@@ -208,7 +231,10 @@ impl QueuedMessage {
                     }
                 }
 
-                message.save_changes(&server, self.due.into()).await;
+                let state_events = message.final_state_events(&final_rcpts);
+                if message.save_changes(&server, self.due.into()).await {
+                    server.notify_queue_states(state_events);
+                }
 
                 return QueueEventStatus::Deferred;
             }
@@ -218,6 +244,7 @@ impl QueuedMessage {
         let queue_config = &server.core.smtp.queue;
         let now_ = now();
         let mut routes: AHashMap<(&str, &RoutingStrategy), Vec<usize>> = AHashMap::new();
+        let mut escalated_rcpts = Vec::new();
         for (rcpt_idx, rcpt) in message.message.recipients.iter().enumerate() {
             if matches!(
                 &rcpt.status,
@@ -226,7 +253,7 @@ impl QueuedMessage {
                 && rcpt.queue == message.queue_name
             {
                 let envelope = QueueEnvelope::new(&message.message, rcpt);
-                let route = server.get_route_or_default(
+                let mut route = server.get_route_or_default(
                     &server
                         .eval_if::<String, _>(&queue_config.route, &envelope, message.span_id)
                         .await
@@ -234,6 +261,27 @@ impl QueuedMessage {
                     message.span_id,
                 );
 
+                // Escalate to the fallback route after too many temporary failures,
+                // the recipient keeps its retry schedule and expiration.
+                if let Some(fallback_route) = &queue_config.fallback_route
+                    && !matches!(route, RoutingStrategy::Local)
+                    && (rcpt.flags & RCPT_FALLBACK_ROUTE != 0
+                        || (matches!(&rcpt.status, Status::TemporaryFailure(_))
+                            && rcpt.retry.inner >= queue_config.fallback_attempts))
+                {
+                    if rcpt.flags & RCPT_FALLBACK_ROUTE == 0 {
+                        trc::event!(
+                            Delivery(DeliveryEvent::FallbackRoute),
+                            SpanId = message.span_id,
+                            To = rcpt.address().to_string(),
+                            Id = fallback_route.clone(),
+                            Total = rcpt.retry.inner,
+                        );
+                        escalated_rcpts.push(rcpt_idx);
+                    }
+                    route = server.get_route_or_default(fallback_route, message.span_id);
+                }
+
                 routes
                     .entry((rcpt.domain_part(), route))
                     .or_default()
@@ -566,7 +614,16 @@ impl QueuedMessage {
 
             // Try delivering message
             let mut last_status: Status<HostResponse<Box<str>>, ErrorDetails> = Status::Scheduled;
+            let mut last_reply = None;
+            let mut hosts_too_small = 0;
             'next_host: for remote_host in &remote_hosts {
+                // Remember the last reply received from a remote host
+                if let Status::TemporaryFailure(err) = &last_status
+                    && err.remote_response().is_some()
+                {
+                    last_reply = Some(last_status.clone());
+                }
+
                 // Validate MTA-STS
                 envelope.mx = remote_host.hostname();
                 if let Some(mta_sts_policy) = &mta_sts_policy {
@@ -627,6 +684,24 @@ impl QueuedMessage {
                     }
                 }
 
+                // Skip hosts that advertised a maximum size below the message size
+                if let Some(max_size) = server.inner.cache.remote_max_size.get(envelope.mx)
+                    && message.message.size > max_size
+                {
+                    trc::event!(
+                        Delivery(DeliveryEvent::MessageTooLarge),
+                        SpanId = message.span_id,
+                        Domain = domain.to_string(),
+                        Hostname = envelope.mx.to_string(),
+                        Size = message.message.size,
+                        Limit = max_size,
+                    );
+
+                    last_status = Status::message_too_large(envelope.mx, max_size).into_temporary();
+                    hosts_too_small += 1;
+                    continue 'next_host;
+                }
+
                 // Obtain source and remote IPs
                 let time = Instant::now();
                 let resolve_result = match server.resolve_host(remote_host, &envelope).await {
@@ -952,7 +1027,7 @@ impl QueuedMessage {
                         &server.inner.data.smtp_connectors.pki_verify
                     };
 
-                    if !remote_host.implicit_tls() {
+                    let result = if !remote_host.implicit_tls() {
                         // Read greeting
                         smtp_client.timeout = conn_strategy.timeout_greeting;
                         if let Err(status) = smtp_client.read_greeting(envelope.mx).await {
@@ -1079,7 +1154,7 @@ impl QueuedMessage {
                                     message
                                         .deliver(
                                             smtp_client,
-                                            rcpt_idxs,
+                                            rcpt_idxs.clone(),
                                             &mut delivery_results,
                                             params,
                                         )
@@ -1137,7 +1212,7 @@ impl QueuedMessage {
                                         message
                                             .deliver(
                                                 smtp_client,
-                                                rcpt_idxs,
+                                                rcpt_idxs.clone(),
                                                 &mut delivery_results,
                                                 params,
                                             )
@@ -1193,7 +1268,12 @@ impl QueuedMessage {
                             );
 
                             message
-                                .deliver(smtp_client, rcpt_idxs, &mut delivery_results, params)
+                                .deliver(
+                                    smtp_client,
+                                    rcpt_idxs.clone(),
+                                    &mut delivery_results,
+                                    params,
+                                )
                                 .await
                         }
                     } else {
@@ -1233,8 +1313,21 @@ impl QueuedMessage {
 
                         // Deliver message
                         message
-                            .deliver(smtp_client, rcpt_idxs, &mut delivery_results, params)
+                            .deliver(
+                                smtp_client,
+                                rcpt_idxs.clone(),
+                                &mut delivery_results,
+                                params,
+                            )
                             .await
+                    };
+
+                    // Try the next host if the message exceeds the advertised size
+                    if let Err(max_size) = result {
+                        last_status =
+                            Status::message_too_large(envelope.mx, max_size).into_temporary();
+                        hosts_too_small += 1;
+                        continue 'next_host;
                     }
 
                     // Continue with the next domain/route
@@ -1242,11 +1335,26 @@ impl QueuedMessage {
                 }
             }
 
+            // Fail permanently only when every host advertised a smaller maximum size
+            if hosts_too_small > 0 && hosts_too_small == remote_hosts.len() {
+                last_status = last_status.into_permanent();
+            }
+
+            // Do not let a later local failure hide the last remote reply
+            if let Some(reply) = last_reply
+                && matches!(&last_status, Status::TemporaryFailure(err) if err.remote_response().is_none())
+            {
+                last_status = reply;
+            }
+
             // Update status
             delivery_results.push(DeliveryResult::domain(last_status, rcpt_idxs));
         }
 
         // Apply status changes
+        for rcpt_idx in escalated_rcpts {
+            message.message.recipients[rcpt_idx].flags |= RCPT_FALLBACK_ROUTE;
+        }
         for delivery_result in delivery_results {
             match delivery_result {
                 DeliveryResult::Domain { status, rcpt_idxs } => {
@@ -1274,7 +1382,15 @@ impl QueuedMessage {
         server.send_dsn(&mut message).await;
 
         // Notify queue manager
+        let mut state_events = message.final_state_events(&final_rcpts);
         if message.message.next_event(None).is_some() {
+            if let Some(next_retry) = message.message.next_delivery_event(None) {
+                state_events.push(QueueStateEvent::Deferred {
+                    queue_id: message.queue_id,
+                    next_retry,
+                });
+            }
+
             trc::event!(
                 Queue(trc::QueueEvent::Rescheduled),
                 SpanId = span_id,
@@ -1287,7 +1403,9 @@ impl QueuedMessage {
             );
 
             // Save changes to disk
-            message.save_changes(&server, self.due.into()).await;
+            if message.save_changes(&server, self.due.into()).await {
+                server.notify_queue_states(state_events);
+            }
 
             QueueEventStatus::Deferred
         } else {
@@ -1298,7 +1416,9 @@ impl QueuedMessage {
             );
 
             // Delete message from queue
-            message.remove(&server, self.due.into()).await;
+            if message.remove(&server, self.due.into()).await {
+                server.notify_queue_states(state_events);
+            }
 
             QueueEventStatus::Completed
         }
@@ -1312,6 +1432,43 @@ pub enum PendingDelivery {
 }
 
 impl MessageWrapper {
+    /// Returns which recipients were already delivered or bounced
+    pub fn final_recipients(&self) -> Vec<bool> {
+        self.message
+            .recipients
+            .iter()
+            .map(|rcpt| {
+                matches!(
+                    rcpt.status,
+                    Status::Completed(_) | Status::PermanentFailure(_)
+                )
+            })
+            .collect()
+    }
+
+    /// Builds the state events of recipients delivered or bounced since `final_rcpts`
+    pub fn final_state_events(&self, final_rcpts: &[bool]) -> Vec<QueueStateEvent> {
+        self.message
+            .recipients
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !final_rcpts.get(*idx).copied().unwrap_or_default())
+            .filter_map(|(_, rcpt)| match &rcpt.status {
+                Status::Completed(_) => QueueStateEvent::Delivered {
+                    queue_id: self.queue_id,
+                    recipient: rcpt.address().to_string(),
+                }
+                .into(),
+                Status::PermanentFailure(_) => QueueStateEvent::Bounced {
+                    queue_id: self.queue_id,
+                    recipient: rcpt.address().to_string(),
+                }
+                .into(),
+                Status::Scheduled | Status::TemporaryFailure(_) => None,
+            })
+            .collect()
+    }
+
     /// Marks as failed all domains that reached their expiration time
     pub fn has_pending_delivery(&mut self) -> PendingDelivery {
         let now = now();
@@ -1385,6 +1542,7 @@ impl MessageWrapper {
     ) {
         let needs_retry = matches!(&status, Status::TemporaryFailure(_) | Status::Scheduled);
         self.message.recipients[rcpt_idx].status = status;
+        self.message.recipients[rcpt_idx].flags &= !RCPT_HELD;
 
         if needs_retry {
             let envelope = QueueEnvelope::new(&self.message, &self.message.recipients[rcpt_idx]);
@@ -1396,11 +1554,37 @@ impl MessageWrapper {
                 self.span_id,
             );
             let rcpt = &mut self.message.recipients[rcpt_idx];
-            rcpt.retry.due = now()
-                + queue.retry[std::cmp::min(rcpt.retry.inner as usize, queue.retry.len() - 1)];
+            let retry = server
+                .core
+                .smtp
+                .queue
+                .domain_retry_schedule(rcpt.domain_part())
+                .unwrap_or(&queue.retry);
+            rcpt.retry.due =
+                now() + retry[std::cmp::min(rcpt.retry.inner as usize, retry.len() - 1)];
             rcpt.retry.inner += 1;
             rcpt.expires = queue.expiry;
             rcpt.queue = queue.virtual_queue;
+
+            // Bounce as soon as the attempt cap is reached rather than on the next retry
+            if let QueueExpiry::Attempts(max_attempts) = rcpt.expires
+                && rcpt.retry.inner >= max_attempts
+                && let Status::TemporaryFailure(err) = &rcpt.status
+            {
+                trc::event!(
+                    Delivery(DeliveryEvent::Failed),
+                    SpanId = self.span_id,
+                    QueueId = self.queue_id,
+                    QueueName = self.queue_name.as_str().to_string(),
+                    To = rcpt.address().to_string(),
+                    Reason = from_error_details(&err.details),
+                    Details = "Maximum number of delivery attempts exceeded.",
+                    Total = rcpt.retry.inner,
+                );
+
+                rcpt.status =
+                    std::mem::replace(&mut rcpt.status, Status::Scheduled).into_permanent();
+            }
         }
     }
 
@@ -153,6 +153,23 @@ impl Status<HostResponse<Box<str>>, ErrorDetails> {
         })
     }
 
+    pub fn message_too_large(entity: &str, max_size: u64) -> Self {
+        Status::PermanentFailure(ErrorDetails {
+            entity: entity.into(),
+            details: Error::UnexpectedResponse(UnexpectedResponse {
+                command: "".into(),
+                response: Response {
+                    code: 552,
+                    esc: [5, 3, 4],
+                    message: format!(
+                        "Message size exceeds the maximum of {max_size} bytes accepted by {entity}"
+                    )
+                    .into_boxed_str(),
+                },
+            }),
+        })
+    }
+
     pub fn from_mail_auth_error(entity: &str, err: mail_auth::Error) -> Self {
         match &err {
             mail_auth::Error::DnsRecordNotFound(code) => Status::PermanentFailure(ErrorDetails {
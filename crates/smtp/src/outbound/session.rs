@@ -33,13 +33,16 @@ pub struct SessionParams<'x> {
 }
 
 impl MessageWrapper {
+    /// Delivers the message to the recipients, returning the maximum size advertised
+    /// by the remote host as an error when the message exceeds it. In that case no
+    /// statuses are recorded and delivery should be attempted on the next host.
     pub(super) async fn deliver<T: AsyncRead + AsyncWrite + Unpin>(
         &self,
         mut smtp_client: SmtpClient<T>,
         rcpt_idxs: Vec<usize>,
         statuses: &mut Vec<DeliveryResult>,
         mut params: SessionParams<'_>,
-    ) {
+    ) -> Result<(), u64> {
         // Obtain capabilities
         let time = Instant::now();
         let capabilities = if let Some(capabilities) = params.capabilities.take() {
@@ -67,11 +70,34 @@ impl MessageWrapper {
                     );
                     smtp_client.quit().await;
                     statuses.push(DeliveryResult::domain(status, rcpt_idxs));
-                    return;
+                    return Ok(());
                 }
             }
         };
 
+        // Record the maximum message size advertised by the remote host
+        if params.is_smtp && capabilities.has_capability(EXT_SIZE) && capabilities.size > 0 {
+            let max_size = capabilities.size as u64;
+            params.server.inner.cache.remote_max_size.insert(
+                params.hostname.to_string(),
+                max_size,
+                params.server.core.smtp.queue.remote_size_ttl,
+            );
+
+            if self.message.size > max_size {
+                trc::event!(
+                    Delivery(DeliveryEvent::MessageTooLarge),
+                    SpanId = params.session_id,
+                    Hostname = params.hostname.to_string(),
+                    Size = self.message.size,
+                    Limit = max_size,
+                );
+
+                smtp_client.quit().await;
+                return Err(max_size);
+            }
+        }
+
         // Authenticate
         if let Some(credentials) = params.credentials {
             let time = Instant::now();
@@ -89,7 +115,7 @@ impl MessageWrapper {
                     Status::from_smtp_error(params.hostname, "AUTH ...", err),
                     rcpt_idxs,
                 ));
-                return;
+                return Ok(());
             }
 
             trc::event!(
@@ -153,7 +179,7 @@ impl MessageWrapper {
                     Status::from_smtp_error(params.hostname, &cmd, err),
                     rcpt_idxs,
                 ));
-                return;
+                return Ok(());
             }
         }
 
@@ -237,7 +263,7 @@ impl MessageWrapper {
                         Status::from_smtp_error(params.hostname, "", err),
                         rcpt_idxs,
                     ));
-                    return;
+                    return Ok(());
                 }
             }
         }
@@ -260,7 +286,7 @@ impl MessageWrapper {
 
                 smtp_client.quit().await;
                 statuses.push(DeliveryResult::domain(status, rcpt_idxs));
-                return;
+                return Ok(());
             }
 
             if params.is_smtp {
@@ -304,7 +330,7 @@ impl MessageWrapper {
                                 ),
                                 rcpt_idxs,
                             ));
-                            return;
+                            return Ok(());
                         }
                     }
                     Err(status) => {
@@ -318,7 +344,7 @@ impl MessageWrapper {
 
                         smtp_client.quit().await;
                         statuses.push(DeliveryResult::domain(status, rcpt_idxs));
-                        return;
+                        return Ok(());
                     }
                 }
             } else {
@@ -394,13 +420,15 @@ impl MessageWrapper {
 
                         smtp_client.quit().await;
                         statuses.push(DeliveryResult::domain(status, rcpt_idxs));
-                        return;
+                        return Ok(());
                     }
                 }
             }
         }
 
         smtp_client.quit().await;
+
+        Ok(())
     }
 
     fn build_mail_from(&self, capabilities: &EhloResponse<String>) -> String {
@@ -12,6 +12,7 @@ use super::{
 use crate::queue::{MessageWrapper, UnexpectedResponse};
 use crate::reporting::SmtpReporting;
 use common::Server;
+use common::config::smtp::queue::{DsnRecipient, DsnStatus, DsnText, DsnTextBuilder};
 use mail_builder::MessageBuilder;
 use mail_builder::headers::HeaderType;
 use mail_builder::headers::content_type::ContentType;
@@ -22,11 +23,14 @@ use smtp_proto::{
 };
 use std::fmt::Write;
 use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
 use store::write::now;
 
 pub trait SendDsn: Sync + Send {
     fn send_dsn(&self, message: &mut MessageWrapper) -> impl Future<Output = ()> + Send;
     fn log_dsn(&self, message: &MessageWrapper) -> impl Future<Output = ()> + Send;
+    fn set_dsn_text_builder(&self, builder: impl DsnTextBuilder + 'static);
 }
 
 impl SendDsn for Server {
@@ -124,6 +128,103 @@ impl SendDsn for Server {
             }
         }
     }
+
+    fn set_dsn_text_builder(&self, builder: impl DsnTextBuilder + 'static) {
+        self.inner
+            .data
+            .dsn_text
+            .store(Some(Arc::new(Arc::new(builder) as Arc<dyn DsnTextBuilder>)));
+    }
+}
+
+/// Default DSN text, [`SendDsn::set_dsn_text_builder`] installs a different one.
+pub struct DefaultDsnText;
+
+impl DsnTextBuilder for DefaultDsnText {
+    fn build<'x>(
+        &'x self,
+        _server: &'x Server,
+        _return_path: &'x str,
+        recipients: &'x [DsnRecipient<'x>],
+    ) -> Pin<Box<dyn Future<Output = DsnText> + Send + 'x>> {
+        Box::pin(async move {
+            let mut txt_success = String::new();
+            let mut txt_delay = String::new();
+            let mut txt_failed = String::new();
+
+            for rcpt in recipients {
+                match rcpt.status {
+                    DsnStatus::Delivered => txt_success.push_str(&rcpt.details),
+                    DsnStatus::Delayed => txt_delay.push_str(&rcpt.details),
+                    DsnStatus::Failed => txt_failed.push_str(&rcpt.details),
+                }
+            }
+
+            let has_success = !txt_success.is_empty();
+            let has_delay = !txt_delay.is_empty();
+            let has_failure = !txt_failed.is_empty();
+
+            let mut txt =
+                String::with_capacity(txt_success.len() + txt_delay.len() + txt_failed.len() + 128);
+            let (subject, is_mixed) = if has_success && !has_delay && !has_failure {
+                txt.push_str(
+                    "Your message has been successfully delivered to the following recipients:\r\n\r\n",
+                );
+                ("Successfully delivered message", false)
+            } else if has_delay && !has_success && !has_failure {
+                txt.push_str("There was a temporary problem delivering your message to the following recipients:\r\n\r\n");
+                ("Warning: Delay in message delivery", false)
+            } else if has_failure && !has_success && !has_delay {
+                txt.push_str(
+                    "Your message could not be delivered to the following recipients:\r\n\r\n",
+                );
+                ("Failed to deliver message", false)
+            } else if has_success {
+                txt.push_str("Your message has been partially delivered:\r\n\r\n");
+                ("Partially delivered message", true)
+            } else {
+                txt.push_str("Your message could not be delivered to some recipients:\r\n\r\n");
+                (
+                    "Warning: Temporary and permanent failures during message delivery",
+                    true,
+                )
+            };
+
+            if has_success {
+                if is_mixed {
+                    txt.push_str(
+                        "    ----- Delivery to the following addresses was successful -----\r\n",
+                    );
+                }
+
+                txt.push_str(&txt_success);
+                txt.push_str("\r\n");
+            }
+
+            if has_delay {
+                if is_mixed {
+                    txt.push_str(
+                        "    ----- There was a temporary problem delivering to these addresses -----\r\n",
+                    );
+                }
+                txt.push_str(&txt_delay);
+                txt.push_str("\r\n");
+            }
+
+            if has_failure {
+                if is_mixed {
+                    txt.push_str("    ----- Delivery to the following addresses failed -----\r\n");
+                }
+                txt.push_str(&txt_failed);
+                txt.push_str("\r\n");
+            }
+
+            DsnText {
+                subject: subject.to_string(),
+                body: txt,
+            }
+        })
+    }
 }
 
 const MAX_HEADER_SIZE: usize = 4096;
@@ -133,160 +234,130 @@ impl MessageWrapper {
         let config = &server.core.smtp.queue;
         let now = now();
 
-        let mut txt_success = String::new();
-        let mut txt_delay = String::new();
-        let mut txt_failed = String::new();
+        let mut included = Vec::new();
         let mut dsn = String::new();
 
-        for rcpt in &mut self.message.recipients {
+        for (rcpt_idx, rcpt) in self.message.recipients.iter_mut().enumerate() {
             if rcpt.has_flag(RCPT_DSN_SENT | RCPT_NOTIFY_NEVER) {
                 continue;
             }
             match &rcpt.status {
-                Status::Completed(response) => {
+                Status::Completed(_) => {
                     rcpt.flags |= RCPT_DSN_SENT;
                     if !rcpt.has_flag(RCPT_NOTIFY_SUCCESS) {
                         continue;
                     }
                     rcpt.write_dsn(&mut dsn);
                     rcpt.status.write_dsn(&mut dsn);
-                    response.write_dsn_text(&rcpt.address, &mut txt_success);
                 }
-                Status::TemporaryFailure(response)
+                Status::TemporaryFailure(_)
                     if rcpt.notify.due <= now && rcpt.has_flag(RCPT_NOTIFY_DELAY) =>
                 {
                     rcpt.write_dsn(&mut dsn);
                     rcpt.status.write_dsn(&mut dsn);
                     rcpt.write_dsn_will_retry_until(self.message.created, &mut dsn);
-                    response.write_dsn_text(&rcpt.address, &mut txt_delay);
                 }
-                Status::PermanentFailure(response) => {
+                Status::PermanentFailure(_) => {
                     rcpt.flags |= RCPT_DSN_SENT;
                     if !rcpt.has_flag(RCPT_NOTIFY_FAILURE) {
                         continue;
                     }
                     rcpt.write_dsn(&mut dsn);
                     rcpt.status.write_dsn(&mut dsn);
-                    response.write_dsn_text(&rcpt.address, &mut txt_failed);
                 }
                 Status::Scheduled if rcpt.notify.due <= now && rcpt.has_flag(RCPT_NOTIFY_DELAY) => {
                     // This case should not happen under normal circumstances
                     rcpt.write_dsn(&mut dsn);
                     rcpt.status.write_dsn(&mut dsn);
                     rcpt.write_dsn_will_retry_until(self.message.created, &mut dsn);
-                    ErrorDetails {
-                        entity: "localhost".into(),
-                        details: Error::ConcurrencyLimited,
-                    }
-                    .write_dsn_text(&rcpt.address, &mut txt_delay);
                 }
                 _ => continue,
             }
 
+            included.push(rcpt_idx);
             dsn.push_str("\r\n");
         }
 
-        // Build text response
-        let txt_len = txt_success.len() + txt_delay.len() + txt_failed.len();
-        if txt_len == 0 {
-            return None;
-        }
-
-        let has_success = !txt_success.is_empty();
-        let has_delay = !txt_delay.is_empty();
-        let has_failure = !txt_failed.is_empty();
+        // Update next delay notification time, including recipients that did not
+        // request delay notifications so they stop waking up the queue
+        let mut changes = Vec::new();
+        for (rcpt_idx, rcpt) in self.message.recipients.iter().enumerate() {
+            if matches!(
+                &rcpt.status,
+                Status::TemporaryFailure(_) | Status::Scheduled
+            ) && rcpt.notify.due <= now
+            {
+                let envelope = QueueEnvelope::new(&self.message, rcpt);
 
-        let mut txt = String::with_capacity(txt_len + 128);
-        let (subject, is_mixed) = if has_success && !has_delay && !has_failure {
-            txt.push_str(
-                "Your message has been successfully delivered to the following recipients:\r\n\r\n",
-            );
-            ("Successfully delivered message", false)
-        } else if has_delay && !has_success && !has_failure {
-            txt.push_str("There was a temporary problem delivering your message to the following recipients:\r\n\r\n");
-            ("Warning: Delay in message delivery", false)
-        } else if has_failure && !has_success && !has_delay {
-            txt.push_str(
-                "Your message could not be delivered to the following recipients:\r\n\r\n",
-            );
-            ("Failed to deliver message", false)
-        } else if has_success {
-            txt.push_str("Your message has been partially delivered:\r\n\r\n");
-            ("Partially delivered message", true)
-        } else {
-            txt.push_str("Your message could not be delivered to some recipients:\r\n\r\n");
-            (
-                "Warning: Temporary and permanent failures during message delivery",
-                true,
-            )
-        };
+                let queue_id = server
+                    .eval_if::<String, _>(&server.core.smtp.queue.queue, &envelope, self.span_id)
+                    .await
+                    .unwrap_or_else(|| "default".to_string());
+                let queue = server.get_queue_or_default(&queue_id, self.span_id);
 
-        if has_success {
-            if is_mixed {
-                txt.push_str(
-                    "    ----- Delivery to the following addresses was successful -----\r\n",
-                );
+                if let Some(next_notify) =
+                    queue.notify.get((rcpt.notify.inner + 1) as usize).copied()
+                {
+                    changes.push((rcpt_idx, 1, now + next_notify));
+                } else {
+                    changes.push((rcpt_idx, 0, u64::MAX));
+                }
             }
-
-            txt.push_str(&txt_success);
-            txt.push_str("\r\n");
         }
 
-        if has_delay {
-            if is_mixed {
-                txt.push_str(
-                    "    ----- There was a temporary problem delivering to these addresses -----\r\n",
-                );
-            }
-            txt.push_str(&txt_delay);
-            txt.push_str("\r\n");
+        for (rcpt_idx, inner, due) in changes {
+            let rcpt = &mut self.message.recipients[rcpt_idx];
+            rcpt.notify.inner += inner;
+            rcpt.notify.due = due;
         }
 
-        if has_failure {
-            if is_mixed {
-                txt.push_str("    ----- Delivery to the following addresses failed -----\r\n");
-            }
-            txt.push_str(&txt_failed);
-            txt.push_str("\r\n");
+        // Build text response
+        if included.is_empty() {
+            return None;
         }
-
-        // Update next delay notification time
-        if has_delay {
-            let mut changes = Vec::new();
-            for (rcpt_idx, rcpt) in self.message.recipients.iter().enumerate() {
-                if matches!(
-                    &rcpt.status,
-                    Status::TemporaryFailure(_) | Status::Scheduled
-                ) && rcpt.notify.due <= now
-                {
-                    let envelope = QueueEnvelope::new(&self.message, rcpt);
-
-                    let queue_id = server
-                        .eval_if::<String, _>(
-                            &server.core.smtp.queue.queue,
-                            &envelope,
-                            self.span_id,
-                        )
-                        .await
-                        .unwrap_or_else(|| "default".to_string());
-                    let queue = server.get_queue_or_default(&queue_id, self.span_id);
-
-                    if let Some(next_notify) =
-                        queue.notify.get((rcpt.notify.inner + 1) as usize).copied()
-                    {
-                        changes.push((rcpt_idx, 1, now + next_notify));
-                    } else {
-                        changes.push((rcpt_idx, 0, u64::MAX));
+        let recipients = included
+            .into_iter()
+            .map(|rcpt_idx| {
+                let rcpt = &self.message.recipients[rcpt_idx];
+                let mut details = String::new();
+                let status = match &rcpt.status {
+                    Status::Completed(response) => {
+                        response.write_dsn_text(&rcpt.address, &mut details);
+                        DsnStatus::Delivered
                     }
-                }
-            }
+                    Status::TemporaryFailure(response) => {
+                        response.write_dsn_text(&rcpt.address, &mut details);
+                        DsnStatus::Delayed
+                    }
+                    Status::PermanentFailure(response) => {
+                        response.write_dsn_text(&rcpt.address, &mut details);
+                        DsnStatus::Failed
+                    }
+                    Status::Scheduled => {
+                        ErrorDetails {
+                            entity: "localhost".into(),
+                            details: Error::ConcurrencyLimited,
+                        }
+                        .write_dsn_text(&rcpt.address, &mut details);
+                        DsnStatus::Delayed
+                    }
+                };
 
-            for (rcpt_idx, inner, due) in changes {
-                let rcpt = &mut self.message.recipients[rcpt_idx];
-                rcpt.notify.inner += inner;
-                rcpt.notify.due = due;
-            }
-        }
+                DsnRecipient {
+                    address: &rcpt.address,
+                    status,
+                    details,
+                }
+            })
+            .collect::<Vec<_>>();
+        let custom_text = server.inner.data.dsn_text.load_full();
+        let text = custom_text
+            .as_deref()
+            .map_or(&DefaultDsnText as &dyn DsnTextBuilder, |builder| {
+                builder.as_ref()
+            })
+            .build(server, &self.message.return_path, &recipients)
+            .await;
 
         // Obtain hostname and sender addresses
         let from_name = server
@@ -373,11 +444,14 @@ impl MessageWrapper {
             )
             .header("Auto-Submitted", HeaderType::Text("auto-generated".into()))
             .message_id(format!("<{}@{}>", make_boundary("."), reporting_mta))
-            .subject(subject)
+            .subject(text.subject)
             .body(MimePart::new(
                 ContentType::new("multipart/report").attribute("report-type", "delivery-status"),
                 BodyPart::Multipart(vec![
-                    MimePart::new(ContentType::new("text/plain"), BodyPart::Text(txt.into())),
+                    MimePart::new(
+                        ContentType::new("text/plain"),
+                        BodyPart::Text(text.body.into()),
+                    ),
                     MimePart::new(
                         ContentType::new("message/delivery-status"),
                         BodyPart::Text(dsn.into()),
@@ -4,23 +4,30 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use super::{Message, QueueId, Status, spool::SmtpSpool};
-use crate::queue::{Recipient, spool::LOCK_EXPIRY};
+use super::{
+    ArchivedError, ArchivedRecipient, ArchivedStatus, Error, Message, QueueId, QueuedMessage,
+    Status, spool::SmtpSpool,
+};
+use crate::queue::{RCPT_HELD, Recipient, spool::LOCK_EXPIRY};
 use ahash::AHashMap;
 use common::{
     Inner,
     config::smtp::queue::{QueueExpiry, QueueName},
     core::BuildServer,
-    ipc::{QueueEvent, QueueEventStatus},
+    ipc::{QueueEvent, QueueEventStatus, QueueLimiterStats},
 };
-use rand::{Rng, seq::SliceRandom};
+use rand::Rng;
 use std::{
     collections::hash_map::Entry,
     sync::{Arc, atomic::Ordering},
     time::{Duration, Instant},
 };
 use store::write::now;
-use tokio::sync::mpsc;
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+use utils::DomainPart;
 
 pub struct Queue {
     pub core: Arc<Inner>,
@@ -30,6 +37,10 @@ pub struct Queue {
     pub next_refresh: Instant,
     pub rx: mpsc::Receiver<QueueEvent>,
     pub is_paused: bool,
+    pub has_backlog: bool,
+    pub drain_deadline: Option<Instant>,
+    pub drain_tx: Option<oneshot::Sender<()>>,
+    pub workers: Vec<(QueuedMessage, JoinHandle<()>)>,
 }
 
 #[derive(Debug)]
@@ -39,6 +50,13 @@ pub struct QueueStats {
     pub last_warning: Instant,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextEvent {
+    Retry(u64),
+    Notify(u64),
+    Expire(u64),
+}
+
 #[derive(Debug)]
 pub struct LockedMessage {
     pub expires: u64,
@@ -64,6 +82,10 @@ impl Queue {
             stats: AHashMap::new(),
             next_refresh: Instant::now() + Duration::from_secs(1),
             is_paused: false,
+            has_backlog: false,
+            drain_deadline: None,
+            drain_tx: None,
+            workers: Vec::new(),
             rx,
         }
     }
@@ -89,20 +111,24 @@ impl Queue {
                     refresh_queue = true;
                 }
                 Ok(None) => {
+                    // The channel is closed while draining, wait for the deliveries in flight
+                    if let Some(deadline) = self.drain_deadline {
+                        self.drain_workers(deadline).await;
+                    }
                     break;
                 }
             };
 
             if !self.is_paused {
-                // Deliver scheduled messages
-                if refresh_queue || self.next_refresh <= Instant::now() {
+                // Deliver scheduled messages, due messages are still delivered while draining
+                if refresh_queue
+                    || self.drain_deadline.is_some()
+                    || self.next_refresh <= Instant::now()
+                {
                     // Process queue events
                     let server = self.core.build_server();
-                    let mut queue_events = server.next_event(self).await;
-
-                    if queue_events.messages.len() > 3 {
-                        queue_events.messages.shuffle(&mut rand::rng());
-                    }
+                    let queue_events = server.next_event(self).await;
+                    let mut on_hold = 0;
 
                     for queue_event in &queue_events.messages {
                         // Fetch queue stats
@@ -123,7 +149,8 @@ impl Queue {
                         if stats.has_capacity() {
                             // Deliver message
                             stats.in_flight += 1;
-                            queue_event.try_deliver(server.clone());
+                            self.workers
+                                .push((*queue_event, queue_event.try_deliver(server.clone())));
                         } else {
                             if stats.last_warning.elapsed() >= BACK_PRESSURE_WARN_INTERVAL {
                                 stats.last_warning = Instant::now();
@@ -136,26 +163,107 @@ impl Queue {
                             }
                             self.locked
                                 .remove(&(queue_event.queue_id, queue_event.queue_name));
+                            on_hold += 1;
                         }
                     }
 
-                    // Remove expired locks
+                    // Remove expired locks and finished workers
                     let now = now();
+                    self.workers.retain(|(_, worker)| !worker.is_finished());
                     self.locked.retain(|_, locked| {
                         locked.expires > now && locked.revision == self.locked_revision
                     });
 
                     self.next_refresh = Instant::now()
                         + Duration::from_secs(queue_events.next_refresh.saturating_sub(now));
+                    self.has_backlog = queue_events.has_backlog;
+
+                    // Publish queue health
+                    let health = &self.core.data.queue_health;
+                    health
+                        .next_refresh
+                        .store(queue_events.next_refresh, Ordering::Relaxed);
+                    health
+                        .due_messages
+                        .store(queue_events.messages.len(), Ordering::Relaxed);
+                    health.on_hold.store(on_hold, Ordering::Relaxed);
+                    health.locked.store(self.locked.len(), Ordering::Relaxed);
+                    if !queue_events.messages.is_empty() {
+                        health.last_processed.store(now, Ordering::Relaxed);
+                    }
                 }
             } else {
                 // Queue is paused
                 self.next_refresh = Instant::now() + Duration::from_secs(86400);
+                self.core
+                    .data
+                    .queue_health
+                    .next_refresh
+                    .store(now() + 86400, Ordering::Relaxed);
+            }
+        }
+
+        if let Some(drain_tx) = self.drain_tx.take() {
+            let _ = drain_tx.send(());
+        }
+    }
+
+    async fn drain_workers(&mut self, deadline: Instant) {
+        let server = self.core.build_server();
+        let mut on_hold = 0;
+
+        for (queue_event, mut worker) in self.workers.drain(..) {
+            if tokio::time::timeout_at(deadline.into(), &mut worker)
+                .await
+                .is_err()
+            {
+                // Release deliveries that did not finish in time, their events stay
+                // due in the spool and are retried as soon as the queue restarts
+                worker.abort();
+                let _ = worker.await;
+                server
+                    .unlock_event(queue_event.queue_id, queue_event.queue_name)
+                    .await;
+                on_hold += 1;
             }
         }
+
+        self.core
+            .data
+            .queue_health
+            .on_hold
+            .store(on_hold, Ordering::Relaxed);
+    }
+
+    fn start_drain(&mut self, deadline: Instant) {
+        // Stop accepting events, only completions of the deliveries in flight are processed
+        self.drain_deadline = Some(deadline);
+        self.rx.close();
+    }
+
+    pub fn limiter_stats(&self) -> Vec<QueueLimiterStats> {
+        let mut stats = self
+            .stats
+            .iter()
+            .map(|(queue_name, stats)| QueueLimiterStats {
+                queue_name: queue_name.to_string(),
+                in_flight: stats.in_flight,
+                max_in_flight: stats.max_in_flight,
+            })
+            .collect::<Vec<_>>();
+        stats.sort_unstable_by(|a, b| {
+            b.in_flight
+                .cmp(&a.in_flight)
+                .then_with(|| a.queue_name.cmp(&b.queue_name))
+        });
+        stats
     }
 
     async fn handle_event(&mut self, event: QueueEvent) -> bool {
+        if self.drain_deadline.is_some() && !matches!(event, QueueEvent::WorkerDone { .. }) {
+            return false;
+        }
+
         match event {
             QueueEvent::WorkerDone {
                 queue_id,
@@ -168,7 +276,7 @@ impl Queue {
                 match status {
                     QueueEventStatus::Completed => {
                         self.locked.remove(&(queue_id, queue_name));
-                        !self.locked.is_empty() || !queue_stats.has_capacity()
+                        !self.locked.is_empty() || !queue_stats.has_capacity() || self.has_backlog
                     }
                     QueueEventStatus::Locked => {
                         let expires = LOCK_EXPIRY + rand::rng().random_range(5..10);
@@ -184,7 +292,7 @@ impl Queue {
                                 revision: self.locked_revision,
                             },
                         );
-                        self.locked.len() > 1 || !queue_stats.has_capacity()
+                        self.locked.len() > 1 || !queue_stats.has_capacity() || self.has_backlog
                     }
                     QueueEventStatus::Deferred => {
                         self.locked.remove(&(queue_id, queue_name));
@@ -213,34 +321,81 @@ impl Queue {
 
                 false
             }
-            QueueEvent::Stop => {
-                self.rx.close();
-                self.is_paused = true;
+            QueueEvent::FlushOnHold { filter } => {
+                let server = self.core.build_server();
+                tokio::spawn(async move {
+                    if server.flush_on_hold(filter.as_deref()).await > 0 {
+                        let _ = server.inner.ipc.queue_tx.send(QueueEvent::Refresh).await;
+                    }
+                });
+
+                false
+            }
+            QueueEvent::HoldUntil { queue_id, until } => {
+                let server = self.core.build_server();
+                tokio::spawn(async move {
+                    if server.hold_until(queue_id, until).await {
+                        let _ = server.inner.ipc.queue_tx.send(QueueEvent::Refresh).await;
+                    }
+                });
+
+                false
+            }
+            QueueEvent::Retry(queue_id) => {
+                let server = self.core.build_server();
+                tokio::spawn(async move {
+                    if server.retry_now(queue_id).await {
+                        let _ = server.inner.ipc.queue_tx.send(QueueEvent::Refresh).await;
+                    }
+                });
+
+                false
+            }
+            QueueEvent::LimiterStats(tx) => {
+                let _ = tx.send(self.limiter_stats());
                 false
             }
+            QueueEvent::Drain { deadline, tx } => {
+                self.start_drain(deadline);
+                self.drain_tx = Some(tx);
+                true
+            }
+            QueueEvent::Stop => {
+                // Wait for in-flight deliveries when a drain timeout is configured
+                if let Some(timeout) = self.core.build_server().core.smtp.queue.drain_timeout {
+                    self.start_drain(Instant::now() + timeout);
+                    true
+                } else {
+                    self.rx.close();
+                    self.is_paused = true;
+                    false
+                }
+            }
         }
     }
 }
 
 impl Message {
-    pub fn next_event(&self, queue: Option<QueueName>) -> Option<u64> {
-        let mut next_event = None;
+    pub fn next_event(&self, queue: Option<QueueName>) -> Option<NextEvent> {
+        let mut next_event: Option<NextEvent> = None;
 
         for rcpt in &self.recipients {
             if matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
                 && queue.is_none_or(|q| rcpt.queue == q)
             {
-                let mut earlier_event = std::cmp::min(rcpt.retry.due, rcpt.notify.due);
+                let mut earlier_event = NextEvent::Retry(rcpt.retry.due);
 
-                if let Some(expires) = rcpt.expiration_time(self.created) {
-                    earlier_event = std::cmp::min(earlier_event, expires);
+                if rcpt.notify.due < earlier_event.due() {
+                    earlier_event = NextEvent::Notify(rcpt.notify.due);
                 }
 
-                if let Some(next_event) = &mut next_event {
-                    if earlier_event < *next_event {
-                        *next_event = earlier_event;
-                    }
-                } else {
+                if let Some(expires) = rcpt.expiration_time(self.created)
+                    && expires < earlier_event.due()
+                {
+                    earlier_event = NextEvent::Expire(expires);
+                }
+
+                if next_event.is_none_or(|next_event| earlier_event < next_event) {
                     next_event = Some(earlier_event);
                 }
             }
@@ -308,6 +463,11 @@ impl Message {
         expires
     }
 
+    /// Returns the key used to schedule deliveries fairly across senders
+    pub fn tenant(&self) -> &str {
+        self.return_path.domain_part()
+    }
+
     pub fn next_events(&self) -> AHashMap<QueueName, u64> {
         let mut next_events = AHashMap::new();
 
@@ -335,6 +495,84 @@ impl Message {
 
         next_events
     }
+
+    pub fn dedup_recipients(&mut self) {
+        let mut idx = 0;
+
+        while idx < self.recipients.len() {
+            let rcpt = &self.recipients[idx];
+            let duplicate = self.recipients[..idx].iter().position(|prev| {
+                matches!(prev.status, Status::Scheduled | Status::TemporaryFailure(_))
+                    && prev.queue == rcpt.queue
+                    && std::mem::discriminant(&prev.status) == std::mem::discriminant(&rcpt.status)
+                    && prev.address.eq_ignore_ascii_case(&rcpt.address)
+            });
+
+            if let Some(pos) = duplicate {
+                let rcpt = self.recipients.remove(idx);
+                let prev = &mut self.recipients[pos];
+
+                if rcpt.retry.due < prev.retry.due {
+                    prev.retry = rcpt.retry;
+                }
+                if rcpt.notify.due < prev.notify.due {
+                    prev.notify = rcpt.notify;
+                }
+                match (&mut prev.expires, rcpt.expires) {
+                    (QueueExpiry::Ttl(prev), QueueExpiry::Ttl(ttl)) if ttl < *prev => {
+                        *prev = ttl;
+                    }
+                    (QueueExpiry::Attempts(prev), QueueExpiry::Attempts(attempts))
+                        if attempts < *prev =>
+                    {
+                        *prev = attempts;
+                    }
+                    _ => {}
+                }
+                prev.flags |= rcpt.flags;
+                if prev.orcpt.is_none() {
+                    prev.orcpt = rcpt.orcpt;
+                }
+            } else {
+                idx += 1;
+            }
+        }
+    }
+}
+
+impl NextEvent {
+    pub fn due(&self) -> u64 {
+        match self {
+            NextEvent::Retry(due) | NextEvent::Notify(due) | NextEvent::Expire(due) => *due,
+        }
+    }
+
+    pub fn is_due(&self, now: u64) -> bool {
+        self.due() <= now
+    }
+
+    fn priority(&self) -> u8 {
+        match self {
+            NextEvent::Retry(_) => 0,
+            NextEvent::Notify(_) => 1,
+            NextEvent::Expire(_) => 2,
+        }
+    }
+}
+
+impl PartialOrd for NextEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NextEvent {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Retries take precedence over notifications and expirations due at the same time
+        self.due()
+            .cmp(&other.due())
+            .then_with(|| self.priority().cmp(&other.priority()))
+    }
 }
 
 impl Recipient {
@@ -351,6 +589,37 @@ impl Recipient {
             QueueExpiry::Attempts(count) => self.retry.inner >= count,
         }
     }
+
+    pub fn is_on_hold(&self) -> bool {
+        self.flags & RCPT_HELD != 0 || self.is_limited()
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.flags & RCPT_HELD == 0
+            && matches!(
+                &self.status,
+                Status::TemporaryFailure(err)
+                    if matches!(err.details, Error::RateLimited | Error::ConcurrencyLimited)
+            )
+    }
+}
+
+impl ArchivedRecipient {
+    pub fn is_on_hold(&self) -> bool {
+        self.flags.to_native() & RCPT_HELD != 0 || self.is_limited()
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.flags.to_native() & RCPT_HELD == 0
+            && matches!(
+                &self.status,
+                ArchivedStatus::TemporaryFailure(err)
+                    if matches!(
+                        err.details,
+                        ArchivedError::RateLimited | ArchivedError::ConcurrencyLimited
+                    )
+            )
+    }
 }
 
 pub trait SpawnQueue {
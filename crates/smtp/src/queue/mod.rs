@@ -128,6 +128,8 @@ pub const FROM_AUTOGENERATED: u64 = 1 << 37;
 pub const RCPT_DSN_SENT: u64 = 1 << 32;
 //pub const RCPT_STATUS_CHANGED: u64 = 1 << 33;
 pub const RCPT_SPAM_PAYLOAD: u64 = 1 << 34;
+pub const RCPT_FALLBACK_ROUTE: u64 = 1 << 35;
+pub const RCPT_HELD: u64 = 1 << 36;
 
 #[derive(
     Debug,
@@ -445,6 +447,34 @@ impl ArchivedRecipient {
     pub fn domain_part(&self) -> &str {
         self.address.domain_part()
     }
+
+    /// Returns the host and the reply that caused the last failed delivery attempt
+    pub fn last_response(&self) -> Option<(&str, &ArchivedUnexpectedResponse)> {
+        match &self.status {
+            ArchivedStatus::TemporaryFailure(err) | ArchivedStatus::PermanentFailure(err) => err
+                .remote_response()
+                .map(|response| (err.entity.as_ref(), response)),
+            ArchivedStatus::Scheduled | ArchivedStatus::Completed(_) => None,
+        }
+    }
+}
+
+impl ErrorDetails {
+    pub fn remote_response(&self) -> Option<&UnexpectedResponse> {
+        match &self.details {
+            Error::UnexpectedResponse(response) => Some(response),
+            _ => None,
+        }
+    }
+}
+
+impl ArchivedErrorDetails {
+    pub fn remote_response(&self) -> Option<&ArchivedUnexpectedResponse> {
+        match &self.details {
+            ArchivedError::UnexpectedResponse(response) => Some(response),
+            _ => None,
+        }
+    }
 }
 
 pub trait InstantFromTimestamp {
@@ -11,11 +11,13 @@ use super::{
 use crate::queue::manager::{LockedMessage, Queue};
 use crate::queue::{
     FROM_AUTHENTICATED, FROM_AUTOGENERATED, FROM_DSN, FROM_REPORT, FROM_UNAUTHENTICATED,
-    FROM_UNAUTHENTICATED_DMARC, MessageWrapper,
+    FROM_UNAUTHENTICATED_DMARC, MessageWrapper, RCPT_HELD,
 };
+use ahash::AHashMap;
 use common::config::smtp::queue::QueueName;
-use common::ipc::QueueEvent;
+use common::ipc::{QueueEvent, QueueStateEvent};
 use common::{KV_LOCK_QUEUE_MESSAGE, Server};
+use rand::seq::SliceRandom;
 use std::borrow::Cow;
 use std::collections::hash_map::Entry;
 use std::future::Future;
@@ -33,12 +35,39 @@ use types::blob_hash::BlobHash;
 use utils::DomainPart;
 
 pub const LOCK_EXPIRY: u64 = 10 * 60; // 10 minutes
-pub const QUEUE_REFRESH: u64 = 5 * 60; // 5 minutes
 const INFINITE_LOCK: u64 = 60 * 60 * 24 * 365; // 1 year
 
 pub struct QueuedMessages {
     pub messages: Vec<QueuedMessage>,
     pub next_refresh: u64,
+    pub has_backlog: bool,
+}
+
+/// Number of scheduled queue events grouped by how soon they are due
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct QueueForecast {
+    pub overdue: usize,
+    pub next_minute: usize,
+    pub next_hour: usize,
+    pub next_day: usize,
+    pub later: usize,
+}
+
+/// Delivery status of a queued message aggregated over its recipients
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueMessageStatus {
+    pub queue_id: QueueId,
+    pub status: Status<(), ()>,
+    pub next_event: Option<u64>,
+    pub recipients: RecipientSummary,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RecipientSummary {
+    pub scheduled: usize,
+    pub completed: usize,
+    pub temporary_failure: usize,
+    pub permanent_failure: usize,
 }
 
 pub trait SmtpSpool: Sync + Send {
@@ -68,6 +97,19 @@ pub trait SmtpSpool: Sync + Send {
         &self,
         id: QueueId,
     ) -> impl Future<Output = trc::Result<Option<Archive<AlignedBytes>>>> + Send;
+
+    fn flush_on_hold(&self, domain: Option<&str>) -> impl Future<Output = usize> + Send;
+
+    fn hold_until(&self, queue_id: QueueId, until: u64) -> impl Future<Output = bool> + Send;
+
+    fn retry_now(&self, queue_id: QueueId) -> impl Future<Output = bool> + Send;
+
+    fn queue_forecast(&self) -> impl Future<Output = trc::Result<QueueForecast>> + Send;
+
+    fn queue_status(
+        &self,
+        ids: &[QueueId],
+    ) -> impl Future<Output = trc::Result<Vec<QueueMessageStatus>>> + Send;
 }
 
 impl SmtpSpool for Server {
@@ -99,6 +141,7 @@ impl SmtpSpool for Server {
 
     async fn next_event(&self, queue: &mut Queue) -> QueuedMessages {
         let now = now();
+        let max_idle = self.core.smtp.queue.max_idle.as_secs();
         let from_key = ValueKey::from(ValueClass::Queue(QueueClass::MessageEvent(
             store::write::QueueEvent {
                 due: 0,
@@ -108,7 +151,7 @@ impl SmtpSpool for Server {
         )));
         let to_key = ValueKey::from(ValueClass::Queue(QueueClass::MessageEvent(
             store::write::QueueEvent {
-                due: now + QUEUE_REFRESH,
+                due: now + max_idle,
                 queue_id: u64::MAX,
                 queue_name: [u8::MAX; 8],
             },
@@ -116,15 +159,31 @@ impl SmtpSpool for Server {
 
         let mut events = QueuedMessages {
             messages: Vec::new(),
-            next_refresh: now + QUEUE_REFRESH,
+            next_refresh: now + max_idle,
+            has_backlog: false,
         };
 
+        // Messages being delivered count towards the in-memory limit
         queue.locked_revision += 1;
+        let in_flight = queue
+            .stats
+            .values()
+            .map(|stats| stats.in_flight)
+            .sum::<usize>();
+        let max_in_memory = self
+            .core
+            .smtp
+            .queue
+            .max_in_memory
+            .map(|max| max.saturating_sub(in_flight));
+        let max_per_tenant = self.core.smtp.queue.max_per_tenant;
+        let mut tenants: AHashMap<Vec<u8>, Vec<QueuedMessage>> = AHashMap::new();
+        let mut total_events = 0;
         let result = self
             .store()
             .iterate(
-                IterateParams::new(from_key, to_key).ascending().no_values(),
-                |key, _| {
+                IterateParams::new(from_key, to_key).ascending(),
+                |key, value| {
                     let due = key.deserialize_be_u64(0)?;
 
                     if due <= now {
@@ -138,6 +197,29 @@ impl SmtpSpool for Server {
                                     .ctx(trc::Key::Key, key)
                             })?;
 
+                        // Stop reading once the in-memory limit is reached, the remaining
+                        // events are picked up once an in-flight delivery completes
+                        if max_in_memory.is_some_and(|max| total_events >= max) {
+                            events.has_backlog = true;
+                            return Ok(false);
+                        }
+
+                        // Null sender messages do not share a tenant
+                        let tenant = if !value.is_empty() {
+                            Cow::Borrowed(value)
+                        } else {
+                            Cow::Owned([&[0u8][..], &queue_id.to_be_bytes()].concat())
+                        };
+
+                        // Leave events over the per-tenant limit until a delivery completes
+                        let tenant_events = tenants
+                            .get(tenant.as_ref())
+                            .map_or(0, |events| events.len());
+                        if max_per_tenant.is_some_and(|max| tenant_events >= max) {
+                            events.has_backlog = true;
+                            return Ok(true);
+                        }
+
                         let add_event = queue
                             .stats
                             .get(&queue_name)
@@ -168,11 +250,13 @@ impl SmtpSpool for Server {
                             };
 
                         if add_event {
-                            events.messages.push(QueuedMessage {
+                            total_events += 1;
+                            let event = QueuedMessage {
                                 due,
                                 queue_id,
                                 queue_name,
-                            });
+                            };
+                            tenants.entry(tenant.into_owned()).or_default().push(event);
                         }
 
                         Ok(true)
@@ -193,6 +277,28 @@ impl SmtpSpool for Server {
             );
         }
 
+        // Round-robin across tenants so a single sender cannot monopolize delivery
+        events.messages.reserve(total_events);
+        let mut rng = rand::rng();
+        let mut tenants = tenants
+            .into_values()
+            .map(|mut events| {
+                events.shuffle(&mut rng);
+                events.into_iter()
+            })
+            .collect::<Vec<_>>();
+        tenants.shuffle(&mut rng);
+        while !tenants.is_empty() {
+            tenants.retain_mut(|tenant| {
+                if let Some(event) = tenant.next() {
+                    events.messages.push(event);
+                    true
+                } else {
+                    false
+                }
+            });
+        }
+
         events
     }
 
@@ -283,6 +389,173 @@ impl SmtpSpool for Server {
             )))
             .await
     }
+
+    async fn flush_on_hold(&self, domain: Option<&str>) -> usize {
+        let now = now();
+        let from_key = ValueKey::from(ValueClass::Queue(QueueClass::Message(0)));
+        let to_key = ValueKey::from(ValueClass::Queue(QueueClass::Message(u64::MAX)));
+
+        // Find messages with recipients held back by rate or concurrency limiters
+        let mut queue_ids = Vec::new();
+        if let Err(err) = self
+            .store()
+            .iterate(
+                IterateParams::new(from_key, to_key).ascending(),
+                |key, value| {
+                    let message_ = <Archive<AlignedBytes> as Deserialize>::deserialize(value)
+                        .add_context(|ctx| ctx.ctx(trc::Key::Key, key))?;
+                    let message = message_
+                        .unarchive::<Message>()
+                        .add_context(|ctx| ctx.ctx(trc::Key::Key, key))?;
+                    if message.recipients.iter().any(|rcpt| {
+                        rcpt.retry.due.to_native() > now
+                            && rcpt.is_limited()
+                            && domain.is_none_or(|domain| {
+                                rcpt.domain_part().eq_ignore_ascii_case(domain)
+                            })
+                    }) {
+                        queue_ids.push(key.deserialize_be_u64(0)?);
+                    }
+                    Ok(true)
+                },
+            )
+            .await
+        {
+            trc::error!(
+                err.details("Failed to read queue.")
+                    .caused_by(trc::location!())
+            );
+        }
+
+        // Release them, recipients held by an operator are left untouched
+        let mut released = 0;
+        for queue_id in queue_ids {
+            if let Some(mut message) = self.read_message(queue_id, QueueName::default()).await {
+                let mut has_changes = false;
+
+                for rcpt in &mut message.message.recipients {
+                    if rcpt.retry.due > now
+                        && rcpt.is_limited()
+                        && domain
+                            .is_none_or(|domain| rcpt.domain_part().eq_ignore_ascii_case(domain))
+                    {
+                        rcpt.retry.due = now;
+                        has_changes = true;
+                    }
+                }
+
+                if has_changes && message.save_changes(self, None).await {
+                    released += 1;
+                }
+            }
+        }
+
+        released
+    }
+
+    async fn hold_until(&self, queue_id: QueueId, until: u64) -> bool {
+        // Defer pending recipients until released by the operator or the release time passes
+        if let Some(mut message) = self.read_message(queue_id, QueueName::default()).await {
+            let mut has_changes = false;
+
+            for rcpt in &mut message.message.recipients {
+                if matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_)) {
+                    rcpt.retry.due = until;
+                    rcpt.notify.due = rcpt.notify.due.max(until);
+                    rcpt.flags |= RCPT_HELD;
+                    has_changes = true;
+                }
+            }
+
+            has_changes && message.save_changes(self, None).await
+        } else {
+            false
+        }
+    }
+
+    async fn retry_now(&self, queue_id: QueueId) -> bool {
+        let now = now();
+
+        // Reschedule both scheduled and on hold recipients
+        if let Some(mut message) = self.read_message(queue_id, QueueName::default()).await {
+            let mut has_changes = false;
+
+            for rcpt in &mut message.message.recipients {
+                if matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
+                    && (rcpt.retry.due > now || rcpt.flags & RCPT_HELD != 0)
+                {
+                    rcpt.retry.due = now;
+                    rcpt.flags &= !RCPT_HELD;
+                    has_changes = true;
+                }
+            }
+
+            has_changes && message.save_changes(self, None).await
+        } else {
+            false
+        }
+    }
+
+    async fn queue_forecast(&self) -> trc::Result<QueueForecast> {
+        let now = now();
+        let from_key = ValueKey::from(ValueClass::Queue(QueueClass::MessageEvent(
+            store::write::QueueEvent {
+                due: 0,
+                queue_id: 0,
+                queue_name: [0; 8],
+            },
+        )));
+        let to_key = ValueKey::from(ValueClass::Queue(QueueClass::MessageEvent(
+            store::write::QueueEvent {
+                due: u64::MAX,
+                queue_id: u64::MAX,
+                queue_name: [u8::MAX; 8],
+            },
+        )));
+
+        // Each event holds the next due time of a message in a virtual queue
+        let mut forecast = QueueForecast::default();
+        self.store()
+            .iterate(
+                IterateParams::new(from_key, to_key).ascending().no_values(),
+                |key, _| {
+                    let due = key.deserialize_be_u64(0)?;
+                    match due.saturating_sub(now) {
+                        0 => forecast.overdue += 1,
+                        1..=60 => forecast.next_minute += 1,
+                        61..=3600 => forecast.next_hour += 1,
+                        3601..=86400 => forecast.next_day += 1,
+                        _ => forecast.later += 1,
+                    }
+
+                    Ok(true)
+                },
+            )
+            .await
+            .caused_by(trc::location!())
+            .map(|_| forecast)
+    }
+
+    async fn queue_status(&self, ids: &[QueueId]) -> trc::Result<Vec<QueueMessageStatus>> {
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+
+        // Fetch each requested message by key, skipping those no longer queued
+        let mut results = Vec::with_capacity(ids.len());
+        for queue_id in ids {
+            if let Some(message) = self
+                .read_message_archive(queue_id)
+                .await
+                .and_then(|archive| archive.map(|a| a.deserialize::<Message>()).transpose())
+                .caused_by(trc::location!())?
+            {
+                results.push(message.delivery_status(queue_id));
+            }
+        }
+
+        Ok(results)
+    }
 }
 
 fn lock_id(queue_id: QueueId, queue_name: QueueName) -> [u8; 16] {
@@ -301,6 +574,9 @@ impl MessageWrapper {
         server: &Server,
         source: MessageSource,
     ) -> bool {
+        // Merge duplicate recipients
+        self.message.dedup_recipients();
+
         // Set flags
         let (flags, event, train_spam) = match source {
             MessageSource::Authenticated => (
@@ -435,7 +711,7 @@ impl MessageWrapper {
                     queue_id: self.queue_id,
                     queue_name: queue_name.into_inner(),
                 })),
-                Vec::new(),
+                self.message.tenant().as_bytes().to_vec(),
             );
         }
 
@@ -510,6 +786,9 @@ impl MessageWrapper {
 
             return false;
         }
+        server.notify_queue_state(QueueStateEvent::Queued {
+            queue_id: self.queue_id,
+        });
 
         // Queue the message
         if server
@@ -576,7 +855,7 @@ impl MessageWrapper {
                     queue_id: self.queue_id,
                     queue_name: queue_name.into_inner(),
                 })),
-                Vec::new(),
+                self.message.tenant().as_bytes().to_vec(),
             );
         }
 
@@ -733,6 +1012,35 @@ impl MessageWrapper {
     }
 }
 
+impl Message {
+    pub fn delivery_status(&self, queue_id: QueueId) -> QueueMessageStatus {
+        let mut recipients = RecipientSummary::default();
+        for rcpt in &self.recipients {
+            match &rcpt.status {
+                Status::Scheduled => recipients.scheduled += 1,
+                Status::Completed(_) => recipients.completed += 1,
+                Status::TemporaryFailure(_) => recipients.temporary_failure += 1,
+                Status::PermanentFailure(_) => recipients.permanent_failure += 1,
+            }
+        }
+
+        QueueMessageStatus {
+            queue_id,
+            status: if recipients.temporary_failure > 0 {
+                Status::TemporaryFailure(())
+            } else if recipients.scheduled > 0 {
+                Status::Scheduled
+            } else if recipients.permanent_failure > 0 {
+                Status::PermanentFailure(())
+            } else {
+                Status::Completed(())
+            },
+            next_event: self.next_event(None).map(|event| event.due()),
+            recipients,
+        }
+    }
+}
+
 impl ArchivedMessage {
     pub fn has_domain(&self, domains: &[String]) -> bool {
         self.recipients.iter().any(|r| {
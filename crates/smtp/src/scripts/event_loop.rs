@@ -22,7 +22,7 @@ use smtp_proto::{
 use std::{borrow::Cow, future::Future, sync::Arc, time::Instant};
 use trc::SieveEvent;
 
-use super::{ScriptModification, ScriptParameters, ScriptResult};
+use super::{ScriptAction, ScriptModification, ScriptParameters, ScriptResult, ScriptTrace};
 
 pub trait RunScript: Sync + Send {
     fn run_script(
@@ -42,10 +42,9 @@ impl RunScript for Server {
     ) -> ScriptResult {
         // Create filter instance
         let time = Instant::now();
-        let mut instance = self
-            .core
-            .sieve
-            .trusted_runtime
+        let mut instance = params
+            .runtime
+            .unwrap_or(&self.core.sieve.trusted_runtime)
             .filter_parsed(params.message.unwrap_or_else(|| Message {
                 parts: vec![MessagePart {
                     headers: vec![],
@@ -69,6 +68,9 @@ impl RunScript for Server {
 
         let mut reject_reason = None;
         let mut modifications = vec![];
+        let mut actions = params.actions;
+        let mut trace = params.trace;
+        let mut num_traced_actions = 0;
         let mut keep_id = usize::MAX;
 
         // Start event loop
@@ -77,7 +79,14 @@ impl RunScript for Server {
                 Ok(event) => match event {
                     Event::IncludeScript { name, optional } => {
                         let name_ = name.as_str().to_lowercase();
-                        if let Some(script) = self.core.sieve.trusted_scripts.get(&name_) {
+                        let script = self.core.sieve.trusted_scripts.get(&name_);
+                        if let Some(trace) = &mut trace {
+                            trace.push(ScriptTrace::Include {
+                                name: name_.clone(),
+                                found: script.is_some(),
+                            });
+                        }
+                        if let Some(script) = script {
                             input = Input::script(name, script.clone());
                         } else if optional {
                             input = false.into();
@@ -96,9 +105,9 @@ impl RunScript for Server {
                         values,
                         match_as,
                     } => {
-                        input = false.into();
-                        'outer: for list in lists {
-                            if let Some(store) = self.core.storage.lookups.get(&list) {
+                        let mut result = false;
+                        'outer: for list in &lists {
+                            if let Some(store) = self.core.storage.lookups.get(list) {
                                 for value in &values {
                                     if let Ok(true) = store
                                         .key_exists(if !matches!(match_as, MatchAs::Lowercase) {
@@ -108,7 +117,7 @@ impl RunScript for Server {
                                         })
                                         .await
                                     {
-                                        input = true.into();
+                                        result = true;
                                         break 'outer;
                                     }
                                 }
@@ -117,12 +126,26 @@ impl RunScript for Server {
                                     Sieve(SieveEvent::ListNotFound),
                                     Id = script_id.clone(),
                                     SpanId = session_id,
-                                    Details = list,
+                                    Details = list.clone(),
                                 );
                             }
                         }
+                        if let Some(trace) = &mut trace {
+                            trace.push(ScriptTrace::ListContains {
+                                lists,
+                                values,
+                                result,
+                            });
+                        }
+                        input = result.into();
                     }
                     Event::Function { id, arguments } => {
+                        let traced_arguments = trace.as_ref().map(|_| {
+                            arguments
+                                .iter()
+                                .map(|argument| argument.to_string().into_owned())
+                                .collect::<Vec<_>>()
+                        });
                         input = self
                             .core
                             .run_plugin(
@@ -137,16 +160,57 @@ impl RunScript for Server {
                                 },
                             )
                             .await;
+                        if let (Some(trace), Some(arguments)) = (&mut trace, traced_arguments) {
+                            let name = self.core.plugin_name(id);
+                            trace.push(match &input {
+                                Input::True | Input::False => ScriptTrace::Test {
+                                    name,
+                                    arguments,
+                                    matched: matches!(input, Input::True),
+                                },
+                                Input::FncResult(result) => ScriptTrace::Function {
+                                    name,
+                                    arguments,
+                                    result: result.to_string().into_owned(),
+                                },
+                                _ => ScriptTrace::Function {
+                                    name,
+                                    arguments,
+                                    result: String::new(),
+                                },
+                            });
+                        }
                     }
-                    Event::Keep { message_id, .. } => {
+                    Event::Keep { message_id, flags } => {
+                        if let Some(actions) = &mut actions {
+                            actions.push(ScriptAction::Keep { flags });
+                        }
                         keep_id = message_id;
                         input = true.into();
                     }
+                    Event::FileInto { folder, flags, .. } if actions.is_some() => {
+                        // File into actions are only reported when testing scripts
+                        if let Some(actions) = &mut actions {
+                            actions.push(ScriptAction::FileInto {
+                                mailbox: folder,
+                                flags,
+                            });
+                        }
+                        input = true.into();
+                    }
                     Event::Discard => {
+                        if let Some(actions) = &mut actions {
+                            actions.push(ScriptAction::Discard);
+                        }
                         keep_id = usize::MAX - 1;
                         input = true.into();
                     }
                     Event::Reject { reason, .. } => {
+                        if let Some(actions) = &mut actions {
+                            actions.push(ScriptAction::Reject {
+                                reason: reason.clone(),
+                            });
+                        }
                         reject_reason = reason.into();
                         input = true.into();
                     }
@@ -157,6 +221,20 @@ impl RunScript for Server {
                         by_time,
                         message_id,
                     } => {
+                        // Record action
+                        if let Some(actions) = &mut actions {
+                            let recipients = match &recipient {
+                                Recipient::Address(rcpt) => vec![rcpt.clone()],
+                                Recipient::Group(rcpt_list) => rcpt_list.clone(),
+                                Recipient::List(list) => vec![list.clone()],
+                            };
+                            actions.push(if message_id == 0 {
+                                ScriptAction::Redirect { recipients }
+                            } else {
+                                ScriptAction::SendMessage { recipients }
+                            });
+                        }
+
                         // Build message
                         let mut message = self.new_message(params.return_path.as_str(), session_id);
                         match recipient {
@@ -346,6 +424,11 @@ impl RunScript for Server {
                         input = true.into();
                     }
                     unsupported => {
+                        if let Some(trace) = &mut trace {
+                            trace.push(ScriptTrace::Error {
+                                reason: format!("Unsupported event: {unsupported:?}"),
+                            });
+                        }
                         trc::event!(
                             Sieve(SieveEvent::NotSupported),
                             Id = script_id.clone(),
@@ -357,6 +440,11 @@ impl RunScript for Server {
                     }
                 },
                 Err(err) => {
+                    if let Some(trace) = &mut trace {
+                        trace.push(ScriptTrace::Error {
+                            reason: err.to_string(),
+                        });
+                    }
                     trc::event!(
                         Sieve(SieveEvent::RuntimeError),
                         Id = script_id.clone(),
@@ -366,6 +454,16 @@ impl RunScript for Server {
                     break;
                 }
             }
+
+            // Add any actions taken by this step to the trace
+            if let (Some(trace), Some(actions)) = (&mut trace, &actions) {
+                for action in &actions[num_traced_actions..] {
+                    trace.push(ScriptTrace::Action {
+                        action: action.clone(),
+                    });
+                }
+                num_traced_actions = actions.len();
+            }
         }
 
         // Keep id
@@ -12,7 +12,7 @@ use common::{
 };
 
 use mail_parser::Message;
-use sieve::{Envelope, runtime::Variable};
+use sieve::{Envelope, Runtime, runtime::Variable};
 
 pub mod envelope;
 pub mod event_loop;
@@ -31,6 +31,52 @@ pub enum ScriptResult {
     Discard,
 }
 
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
+pub enum ScriptAction {
+    Keep { flags: Vec<String> },
+    FileInto { mailbox: String, flags: Vec<String> },
+    Redirect { recipients: Vec<String> },
+    SendMessage { recipients: Vec<String> },
+    Reject { reason: String },
+    Discard,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
+pub enum ScriptTrace {
+    Script {
+        name: String,
+    },
+    Include {
+        name: String,
+        found: bool,
+    },
+    ListContains {
+        lists: Vec<String>,
+        values: Vec<String>,
+        result: bool,
+    },
+    Test {
+        name: &'static str,
+        arguments: Vec<String>,
+        matched: bool,
+    },
+    Function {
+        name: &'static str,
+        arguments: Vec<String>,
+        result: String,
+    },
+    Action {
+        action: ScriptAction,
+    },
+    Error {
+        reason: String,
+    },
+}
+
 pub struct ScriptParameters<'x> {
     message: Option<Message<'x>>,
     headers: Option<&'x [u8]>,
@@ -41,6 +87,9 @@ pub struct ScriptParameters<'x> {
     return_path: String,
     sign: Vec<String>,
     access_token: Option<&'x AccessToken>,
+    actions: Option<&'x mut Vec<ScriptAction>>,
+    trace: Option<&'x mut Vec<ScriptTrace>>,
+    runtime: Option<&'x Runtime>,
     session_id: u64,
 }
 
@@ -56,6 +105,9 @@ impl<'x> ScriptParameters<'x> {
             return_path: Default::default(),
             sign: Default::default(),
             access_token: None,
+            actions: None,
+            trace: None,
+            runtime: None,
             session_id: Default::default(),
         }
     }
@@ -117,6 +169,21 @@ impl<'x> ScriptParameters<'x> {
         self
     }
 
+    pub fn with_actions(mut self, actions: &'x mut Vec<ScriptAction>) -> Self {
+        self.actions = Some(actions);
+        self
+    }
+
+    pub fn with_trace(mut self, trace: &'x mut Vec<ScriptTrace>) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    pub fn with_runtime(mut self, runtime: &'x Runtime) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
     pub fn with_session_id(mut self, session_id: u64) -> Self {
         self.session_id = session_id;
         self
@@ -18,7 +18,10 @@ use crate::{
         url::SpamFilterAnalyzeUrl,
     },
 };
-use common::{Server, config::spamfilter::SpamFilterAction};
+use common::{
+    Server,
+    config::spamfilter::{DnsBlTerminal, SpamFilterAction},
+};
 use std::{fmt::Write, future::Future, vec};
 
 // SPDX-SnippetBegin
@@ -38,6 +41,11 @@ pub trait SpamFilterAnalyzeScore: Sync + Send {
         &self,
         ctx: &mut SpamFilterContext<'_>,
     ) -> impl Future<Output = SpamFilterAction<SpamFilterScore>> + Send;
+
+    fn spam_filter_terminal(
+        &self,
+        ctx: &mut SpamFilterContext<'_>,
+    ) -> impl Future<Output = Option<SpamFilterAction<SpamFilterScore>>> + Send;
 }
 
 #[derive(Debug, Default)]
@@ -178,6 +186,9 @@ impl SpamFilterAnalyzeScore for Server {
     ) -> SpamFilterAction<SpamFilterScore> {
         // IP address analysis
         self.spam_filter_analyze_ip(ctx).await;
+        if let Some(result) = self.spam_filter_terminal(ctx).await {
+            return result;
+        }
 
         // DMARC/SPF/DKIM/ARC analysis
         self.spam_filter_analyze_dmarc(ctx).await;
@@ -211,9 +222,15 @@ impl SpamFilterAnalyzeScore for Server {
 
         // E-mail and domain analysis
         self.spam_filter_analyze_domain(ctx).await;
+        if let Some(result) = self.spam_filter_terminal(ctx).await {
+            return result;
+        }
 
         // URL analysis
         self.spam_filter_analyze_url(ctx).await;
+        if let Some(result) = self.spam_filter_terminal(ctx).await {
+            return result;
+        }
 
         // MIME part analysis
         self.spam_filter_analyze_mime(ctx).await;
@@ -246,6 +263,38 @@ impl SpamFilterAnalyzeScore for Server {
         // Final score calculation
         self.spam_filter_finalize(ctx).await
     }
+
+    async fn spam_filter_terminal(
+        &self,
+        ctx: &mut SpamFilterContext<'_>,
+    ) -> Option<SpamFilterAction<SpamFilterScore>> {
+        // Skip the remaining checks after a terminal DNSBL hit
+        match ctx.result.dnsbl_terminal? {
+            DnsBlTerminal::Allow => {
+                // Allowlisted messages are ham whatever the earlier tags scored
+                let mut tags = ctx
+                    .result
+                    .tags
+                    .iter()
+                    .map(|tag| tag.as_str())
+                    .collect::<Vec<_>>();
+                tags.sort_unstable();
+                let score = ctx.result.score.min(0.0);
+                let headers = format!(
+                    "X-Spam-Result: {}\r\nX-Spam-Score: ham, score={score:.2}\r\n",
+                    tags.join(",\r\n\t")
+                );
+
+                Some(SpamFilterAction::Allow(SpamFilterScore {
+                    results: vec![false; ctx.input.env_rcpt_to.len()],
+                    headers,
+                    spam_trap: false,
+                    score,
+                }))
+            }
+            DnsBlTerminal::Block => Some(SpamFilterAction::Reject),
+        }
+    }
 }
 
 pub trait ConfidenceStore {
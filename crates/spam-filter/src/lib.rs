@@ -9,6 +9,7 @@ pub mod modules;
 
 use analysis::ElementLocation;
 use analysis::url::UrlParts;
+use common::config::spamfilter::DnsBlTerminal;
 use mail_auth::{ArcOutput, DkimOutput, DmarcResult, IprevOutput, SpfOutput, dmarc::Policy};
 use mail_parser::Message;
 use modules::html::HtmlToken;
@@ -107,6 +108,7 @@ pub struct SpamFilterResult {
     pub rbl_email_checks: usize,
     pub llm_result: Option<(String, String)>,
     pub spam_trap: bool,
+    pub dnsbl_terminal: Option<DnsBlTerminal>,
 }
 
 pub struct SpamFilterContext<'x> {
@@ -5,24 +5,63 @@
  */
 
 use std::{
-    net::Ipv4Addr,
-    sync::Arc,
+    net::{IpAddr, Ipv4Addr},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
     time::{Duration, Instant},
 };
 
 use common::{
     Server,
-    config::spamfilter::{DnsBlServer, Element, IpResolver, Location},
+    config::spamfilter::{
+        DnsBlListing, DnsBlLookup, DnsBlOutcome, DnsBlResult, DnsBlServer, DnsBlTerminal, Element,
+        IpResolver, Location, V_IP,
+    },
     expr::functions::ResolveVariable,
 };
 
-use mail_auth::{Error, common::resolver::IntoFqdn};
+use futures::future::join_all;
+use mail_auth::{
+    common::resolver::IntoFqdn,
+    hickory_resolver::{
+        Name,
+        proto::{NoRecords, ProtoErrorKind, rr::RecordType},
+    },
+};
+use store::write::now;
 use trc::SpamEvent;
 
-use crate::SpamFilterContext;
+use crate::{SpamFilterContext, SpamFilterResult};
 
 use super::expression::SpamFilterResolver;
 
+#[derive(Debug, Default)]
+pub(crate) struct DnsBlHit {
+    pub tags: Vec<String>,
+    pub score: f64,
+    pub terminal: Option<DnsBlTerminal>,
+}
+
+impl DnsBlHit {
+    // Only actual listings can end the scan, lookups without tags or score do not
+    fn set_terminal(&mut self, terminal: Option<DnsBlTerminal>) {
+        self.terminal = terminal.filter(|_| !self.tags.is_empty() || self.score != 0.0);
+    }
+
+    fn apply(self, result: &mut SpamFilterResult) {
+        // The first terminal hit in configuration order decides
+        if result.dnsbl_terminal.is_none() {
+            result.dnsbl_terminal = self.terminal;
+        }
+        for tag in self.tags {
+            result.add_tag(tag);
+        }
+        result.score += self.score as f32;
+    }
+}
+
 pub(crate) async fn check_dnsbl(
     server: &Server,
     ctx: &mut SpamFilterContext<'_>,
@@ -30,7 +69,7 @@ pub(crate) async fn check_dnsbl(
     scope: Element,
     location: Location,
 ) {
-    let (mut checks, max_checks) = match scope {
+    let (checks, max_checks) = match scope {
         Element::Email => (
             ctx.result.rbl_email_checks,
             server.core.spam.dnsbl.max_email_checks,
@@ -50,22 +89,62 @@ pub(crate) async fn check_dnsbl(
         Element::Header | Element::Body | Element::Any => unreachable!(),
     };
 
-    for dnsbl in &server.core.spam.dnsbl.servers {
-        if dnsbl.scope == scope
-            && checks < max_checks
-            && let Some(tag) = is_dnsbl(
-                server,
-                dnsbl,
-                SpamFilterResolver::new(ctx, resolver, location),
-                scope,
-                &mut checks,
-            )
-            .await
-        {
-            ctx.result.add_tag(tag);
-        }
+    if checks >= max_checks || ctx.result.dnsbl_terminal.is_some() {
+        return;
     }
 
+    // Query all blocklists concurrently so a slow server does not delay the others
+    let checks = AtomicUsize::new(checks);
+    let timeout = server.core.spam.dnsbl.timeout;
+    let ctx_ = &*ctx;
+    let results = join_all(
+        server
+            .core
+            .spam
+            .dnsbl
+            .servers
+            .iter()
+            .filter(|dnsbl| dnsbl.scope == scope)
+            .map(|dnsbl| {
+                let checks = &checks;
+                let resolver = SpamFilterResolver::new(ctx_, resolver, location);
+                async move {
+                    let time = Instant::now();
+                    match tokio::time::timeout(
+                        timeout,
+                        is_dnsbl(server, dnsbl, resolver, scope, checks, max_checks),
+                    )
+                    .await
+                    {
+                        Ok(hit) => hit,
+                        Err(_) => {
+                            server.inner.data.dnsbl_metrics.record(
+                                &dnsbl.id,
+                                DnsBlOutcome::Error,
+                                time.elapsed(),
+                                now(),
+                            );
+                            trc::event!(
+                                Spam(SpamEvent::DnsblError),
+                                Id = dnsbl.id.clone(),
+                                Details = scope.as_str(),
+                                Elapsed = time.elapsed(),
+                                CausedBy = "DNSBL lookup timed out"
+                            );
+
+                            DnsBlHit::default()
+                        }
+                    }
+                }
+            }),
+    )
+    .await;
+
+    for hit in results {
+        hit.apply(&mut ctx.result);
+    }
+
+    let checks = checks.into_inner();
     match scope {
         Element::Email => ctx.result.rbl_email_checks = checks,
         Element::Ip => ctx.result.rbl_ip_checks = checks,
@@ -80,59 +159,51 @@ async fn is_dnsbl(
     config: &DnsBlServer,
     resolver: SpamFilterResolver<'_, impl ResolveVariable>,
     element: Element,
-    checks: &mut usize,
-) -> Option<String> {
+    checks: &AtomicUsize,
+    max_checks: usize,
+) -> DnsBlHit {
     let time = Instant::now();
-    let zone = server
-        .eval_if::<String, _>(&config.zone, &resolver, resolver.ctx.input.span_id)
-        .await?;
 
-    #[cfg(feature = "test_mode")]
+    // Private and reserved addresses are never listed
+    if element == Element::Ip
+        && resolver
+            .item
+            .resolve_variable(V_IP)
+            .to_string()
+            .as_str()
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| is_non_routable(&ip))
     {
-        if zone.contains(".11.20.") {
-            let parts = zone.split('.').collect::<Vec<_>>();
-
-            return if config.tags.if_then.iter().any(|i| i.expr.items.len() == 3) && parts[0] != "2"
-            {
-                None
-            } else {
-                server
-                    .eval_if(
-                        &config.tags,
-                        &SpamFilterResolver::new(
-                            resolver.ctx,
-                            &IpResolver::new(
-                                format!("127.0.{}.{}", parts[1], parts[0]).parse().unwrap(),
-                            ),
-                            resolver.location,
-                        ),
-                        resolver.ctx.input.span_id,
-                    )
-                    .await
-            };
-        }
+        return DnsBlHit::default();
     }
 
-    let result = match server.inner.cache.dns_rbl.get(zone.as_str()) {
-        Some(Some(result)) => result,
-        Some(None) => return None,
+    let Some(zone) = server
+        .eval_if::<String, _>(&config.zone, &resolver, resolver.ctx.input.span_id)
+        .await
+        .filter(|zone| !zone.starts_with('.'))
+    else {
+        return DnsBlHit::default();
+    };
+
+    let (result, is_fresh) = match server.inner.cache.dns_rbl.get(zone.as_str()) {
+        Some(Some(result)) => (result, false),
+        Some(None) => return DnsBlHit::default(),
         None => {
-            *checks += 1;
-
-            match server
-                .core
-                .smtp
-                .resolvers
-                .dns
-                .ipv4_lookup_raw((&zone).into_fqdn().as_ref())
-                .await
+            if checks
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |checks| {
+                    (checks < max_checks).then_some(checks + 1)
+                })
+                .is_err()
             {
-                Ok(result) => {
+                return DnsBlHit::default();
+            }
+
+            match lookup_dnsbl(server, &zone).await {
+                Ok(DnsBlLookup::Listed { ips, expires }) => {
                     trc::event!(
                         Spam(SpamEvent::Dnsbl),
                         Hostname = zone.clone(),
-                        Result = result
-                            .entry
+                        Result = ips
                             .iter()
                             .map(|ip| trc::Value::from(ip.to_string()))
                             .collect::<Vec<_>>(),
@@ -140,25 +211,39 @@ async fn is_dnsbl(
                         Elapsed = time.elapsed()
                     );
 
-                    let entry = Arc::new(IpResolver::new(
-                        result
-                            .entry
-                            .iter()
-                            .copied()
-                            .next()
-                            .unwrap_or(Ipv4Addr::BROADCAST)
-                            .into(),
-                    ));
+                    // Obtain the listing reason
+                    let reason = if config.lookup_reason {
+                        lookup_reason(server, &zone).await.ok()
+                    } else {
+                        None
+                    };
 
-                    server.inner.cache.dns_rbl.insert_with_expiry(
+                    let mut ips = ips.into_iter().map(IpResolver::new).collect::<Vec<_>>();
+                    if ips.is_empty() {
+                        ips.push(IpResolver::new(IpAddr::V4(Ipv4Addr::BROADCAST)));
+                    }
+                    let entry = Arc::new(DnsBlResult { ips, reason });
+                    server.inner.data.dnsbl_metrics.record(
+                        &config.id,
+                        if entry.ips.iter().any(|ip| config.accepts_result(ip)) {
+                            DnsBlOutcome::Hit
+                        } else {
+                            DnsBlOutcome::NxDomain
+                        },
+                        time.elapsed(),
+                        now(),
+                    );
+
+                    let ttl = expires.saturating_duration_since(Instant::now());
+                    server.inner.cache.dns_rbl.insert(
                         zone.to_string(),
                         Some(entry.clone()),
-                        result.expires,
+                        server.core.spam.dnsbl.clamp_ttl(ttl),
                     );
 
-                    entry
+                    (entry, true)
                 }
-                Err(Error::DnsRecordNotFound(_)) => {
+                Ok(DnsBlLookup::NotListed { negative_ttl }) => {
                     trc::event!(
                         Spam(SpamEvent::Dnsbl),
                         Hostname = zone.clone(),
@@ -167,34 +252,427 @@ async fn is_dnsbl(
                         Elapsed = time.elapsed()
                     );
 
+                    server.inner.data.dnsbl_metrics.record(
+                        &config.id,
+                        DnsBlOutcome::NxDomain,
+                        time.elapsed(),
+                        now(),
+                    );
+
+                    // Honor the SOA minimum when it is shorter than the configured TTL
+                    let ttl = negative_ttl
+                        .map_or(config.negative_ttl, |ttl| ttl.min(config.negative_ttl));
                     server.inner.cache.dns_rbl.insert(
                         zone.to_string(),
                         None,
-                        Duration::from_secs(86400),
+                        server.core.spam.dnsbl.clamp_ttl(ttl),
                     );
+                    server.inner.cache.dns_rbl_listings.remove(zone.as_str());
 
-                    return None;
+                    return DnsBlHit::default();
                 }
                 Err(err) => {
                     trc::event!(
                         Spam(SpamEvent::DnsblError),
-                        Hostname = zone,
+                        Hostname = zone.clone(),
                         Elapsed = time.elapsed(),
                         Details = element.as_str(),
                         CausedBy = err.to_string()
                     );
 
-                    return None;
+                    server.inner.data.dnsbl_metrics.record(
+                        &config.id,
+                        DnsBlOutcome::Error,
+                        time.elapsed(),
+                        now(),
+                    );
+
+                    // Avoid retrying a failing blocklist on every message
+                    server
+                        .inner
+                        .cache
+                        .dns_rbl
+                        .insert(zone, None, server.core.spam.dnsbl.error_ttl);
+
+                    return DnsBlHit::default();
                 }
             }
         }
     };
 
+    // Return codes outside the configured masks are not listings
+    if !result.ips.iter().any(|ip| config.accepts_result(ip)) {
+        return DnsBlHit::default();
+    }
+
+    // Soft-tag new listings until they have been listed for long enough
+    if let Some(grace_period) = &config.grace_period {
+        let now = now();
+        let mut listing = server
+            .inner
+            .cache
+            .dns_rbl_listings
+            .get(zone.as_str())
+            .unwrap_or_else(|| DnsBlListing::new(now));
+
+        // Cached results repeat the same answer, only fresh lookups count as checks
+        if is_fresh {
+            listing.checks += 1;
+            server.inner.cache.dns_rbl_listings.insert(zone, listing);
+        }
+
+        if !grace_period.is_established(&listing, now) {
+            return DnsBlHit {
+                tags: grace_period.tag.iter().cloned().collect(),
+                score: 0.0,
+                terminal: None,
+            };
+        }
+    }
+
+    eval_dnsbl(server, config, &resolver, &result).await
+}
+
+async fn eval_dnsbl(
+    server: &Server,
+    config: &DnsBlServer,
+    resolver: &SpamFilterResolver<'_, impl ResolveVariable>,
+    result: &DnsBlResult,
+) -> DnsBlHit {
+    // Evaluate the tags and score expressions for each return code
+    let mut hit = DnsBlHit {
+        tags: Vec::with_capacity(result.ips.len()),
+        score: 0.0,
+        terminal: None,
+    };
+    for entry in result
+        .resolvers()
+        .filter(|entry| config.accepts_result(entry.ip))
+    {
+        let entry_resolver = SpamFilterResolver::new(resolver.ctx, &entry, resolver.location);
+        if let Some(tag) = server
+            .eval_if::<String, _>(&config.tags, &entry_resolver, resolver.ctx.input.span_id)
+            .await
+            && !hit.tags.contains(&tag)
+        {
+            hit.tags.push(tag);
+        }
+        if let Some(score_expr) = &config.score
+            && let Some(score) = server
+                .eval_if::<f64, _>(score_expr, &entry_resolver, resolver.ctx.input.span_id)
+                .await
+        {
+            hit.score += score;
+        }
+    }
+    hit.set_terminal(config.terminal);
+
+    hit
+}
+
+fn is_non_routable(ip: &IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => {
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // Shared address space (RFC 6598)
+                || (ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => {
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local (RFC 4193) and link-local addresses
+                || (ip.segments()[0] & 0xfe00) == 0xfc00
+                || (ip.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+async fn lookup_dnsbl(server: &Server, zone: &str) -> mail_auth::Result<DnsBlLookup> {
+    if let Some(lookup) = &server.inner.data.dnsbl_lookup {
+        return lookup.lookup_zone(zone).await;
+    }
+
+    // Query the resolver directly, the negative answer carries the SOA minimum
+    let resolver = &server.core.smtp.resolvers.dns.0;
+    let name = Name::from_str_relaxed(zone.into_fqdn().as_ref())?;
+    match resolver.lookup(name, RecordType::A).await {
+        Ok(lookup) => Ok(DnsBlLookup::Listed {
+            ips: lookup
+                .record_iter()
+                .filter_map(|record| record.data().as_a().map(|ip| IpAddr::V4(ip.0)))
+                .collect(),
+            expires: lookup.valid_until(),
+        }),
+        Err(err) => match err.kind() {
+            ProtoErrorKind::NoRecordsFound(NoRecords { negative_ttl, .. }) => {
+                Ok(DnsBlLookup::NotListed {
+                    negative_ttl: negative_ttl.map(|ttl| Duration::from_secs(ttl as u64)),
+                })
+            }
+            _ => Err(err.into()),
+        },
+    }
+}
+
+async fn lookup_reason(server: &Server, zone: &str) -> mail_auth::Result<String> {
+    if let Some(lookup) = &server.inner.data.dnsbl_lookup {
+        return lookup.lookup_txt(zone).await;
+    }
+
     server
-        .eval_if(
-            &config.tags,
-            &SpamFilterResolver::new(resolver.ctx, result.as_ref(), resolver.location),
-            resolver.ctx.input.span_id,
-        )
+        .core
+        .smtp
+        .resolvers
+        .dns
+        .txt_raw_lookup(zone)
         .await
+        .map(|reason| String::from_utf8_lossy(&reason).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use common::config::spamfilter::{
+        DNSBL_METRICS_BUCKET, DNSBL_METRICS_MAX_BUCKETS, DnsBlBucket, DnsBlGracePeriod,
+        DnsBlListing, DnsBlMetrics, DnsBlOutcome, DnsBlServer, DnsBlTerminal, IpResolver,
+    };
+    use std::{net::IpAddr, time::Duration};
+    use utils::config::Config;
+
+    use crate::SpamFilterResult;
+
+    use super::{DnsBlHit, is_non_routable};
+
+    #[test]
+    fn dnsbl_non_routable() {
+        for (ip, expected) in [
+            ("10.0.0.1", true),
+            ("172.16.5.4", true),
+            ("192.168.1.1", true),
+            ("127.0.0.1", true),
+            ("169.254.10.20", true),
+            ("100.64.0.1", true),
+            ("0.0.0.0", true),
+            ("::1", true),
+            ("fd00::1", true),
+            ("fe80::1", true),
+            ("::ffff:192.168.1.1", true),
+            ("8.8.8.8", false),
+            ("100.128.0.1", false),
+            ("2001:4860:4860::8888", false),
+            ("::ffff:8.8.8.8", false),
+        ] {
+            assert_eq!(
+                is_non_routable(&ip.parse::<IpAddr>().unwrap()),
+                expected,
+                "{ip}"
+            );
+        }
+    }
+
+    #[test]
+    fn dnsbl_grace_period() {
+        let grace_period = DnsBlGracePeriod {
+            min_age: 3600,
+            min_checks: 3,
+            tag: Some("RBL_NEW_LISTING".to_string()),
+        };
+        let now = 1_700_000_000;
+
+        // Freshly listed IPs are only soft-tagged
+        let mut listing = DnsBlListing::new(now);
+        listing.checks += 1;
+        assert!(!grace_period.is_established(&listing, now));
+
+        // Listed for long enough but not consistently
+        let listing = DnsBlListing {
+            first_seen: now - 7200,
+            checks: 2,
+        };
+        assert!(!grace_period.is_established(&listing, now));
+
+        // Checked often enough but listed too recently
+        let listing = DnsBlListing {
+            first_seen: now - 60,
+            checks: 10,
+        };
+        assert!(!grace_period.is_established(&listing, now));
+
+        // Long-listed IPs are hard-blocked
+        let listing = DnsBlListing {
+            first_seen: now - 7200,
+            checks: 3,
+        };
+        assert!(grace_period.is_established(&listing, now));
+    }
+
+    #[test]
+    fn dnsbl_result_mask() {
+        let mut config = Config::new(
+            r#"
+[spam-filter.dnsbl.server.masked]
+scope = "ip"
+suffix = "zen.example.org"
+tag = "'RBL_LISTED'"
+result-mask = ["127.0.0.2/31", "127.0.0.4/30", "127.0.0.8/30"]
+
+[spam-filter.dnsbl.server.any]
+scope = "ip"
+suffix = "bl.example.org"
+tag = "'RBL_LISTED'"
+"#,
+        )
+        .unwrap();
+        let masked = DnsBlServer::parse(&mut config, "masked".to_string()).unwrap();
+        let any = DnsBlServer::parse(&mut config, "any".to_string()).unwrap();
+
+        for (ip, expected) in [
+            ("127.0.0.1", false),
+            ("127.0.0.2", true),
+            ("127.0.0.7", true),
+            ("127.0.0.11", true),
+            ("127.0.0.12", false),
+            ("127.255.255.254", false),
+        ] {
+            let result = IpResolver::new(ip.parse::<IpAddr>().unwrap());
+            assert_eq!(masked.accepts_result(&result), expected, "{ip}");
+            assert!(any.accepts_result(&result), "{ip}");
+        }
+    }
+
+    #[test]
+    fn dnsbl_terminal() {
+        let mut config = Config::new(
+            r#"
+[spam-filter.dnsbl.server.allow]
+scope = "ip"
+suffix = "allow.example.org"
+tag = "'RBL_ALLOWLISTED'"
+terminal = "allow"
+
+[spam-filter.dnsbl.server.block]
+scope = "ip"
+suffix = "block.example.org"
+tag = "'RBL_BLOCKED'"
+score = "5.0"
+terminal = "block"
+
+[spam-filter.dnsbl.server.tag]
+scope = "ip"
+suffix = "tag.example.org"
+tag = "'RBL_LISTED'"
+"#,
+        )
+        .unwrap();
+        let allow = DnsBlServer::parse(&mut config, "allow".to_string()).unwrap();
+        let block = DnsBlServer::parse(&mut config, "block".to_string()).unwrap();
+        let tag = DnsBlServer::parse(&mut config, "tag".to_string()).unwrap();
+        assert_eq!(allow.terminal, Some(DnsBlTerminal::Allow));
+        assert_eq!(block.terminal, Some(DnsBlTerminal::Block));
+        assert_eq!(tag.terminal, None);
+
+        // Tag-only allowlists are terminal even though they do not change the score
+        let mut result = SpamFilterResult::default();
+        let mut hit = DnsBlHit {
+            tags: vec!["RBL_ALLOWLISTED".to_string()],
+            score: 0.0,
+            terminal: None,
+        };
+        hit.set_terminal(allow.terminal);
+        hit.apply(&mut result);
+        assert_eq!(result.dnsbl_terminal, Some(DnsBlTerminal::Allow));
+        assert!(result.has_tag("RBL_ALLOWLISTED"));
+        assert_eq!(result.score, 0.0);
+
+        // The first terminal hit wins
+        let mut hit = DnsBlHit {
+            tags: vec!["RBL_BLOCKED".to_string()],
+            score: 5.0,
+            terminal: None,
+        };
+        hit.set_terminal(block.terminal);
+        hit.apply(&mut result);
+        assert_eq!(result.dnsbl_terminal, Some(DnsBlTerminal::Allow));
+
+        // Unlisted lookups are never terminal
+        let mut result = SpamFilterResult::default();
+        let mut hit = DnsBlHit::default();
+        hit.set_terminal(block.terminal);
+        hit.apply(&mut result);
+        assert_eq!(result.dnsbl_terminal, None);
+
+        let mut hit = DnsBlHit {
+            tags: vec!["RBL_BLOCKED".to_string()],
+            score: 5.0,
+            terminal: None,
+        };
+        hit.set_terminal(block.terminal);
+        hit.apply(&mut result);
+        assert_eq!(result.dnsbl_terminal, Some(DnsBlTerminal::Block));
+        assert_eq!(result.score, 5.0);
+    }
+
+    #[test]
+    fn dnsbl_metrics() {
+        let metrics = DnsBlMetrics::default();
+        let now = 1_700_000_000 - (1_700_000_000 % DNSBL_METRICS_BUCKET);
+
+        for (outcome, elapsed) in [
+            (DnsBlOutcome::Hit, 5),
+            (DnsBlOutcome::NxDomain, 40),
+            (DnsBlOutcome::NxDomain, 80),
+            (DnsBlOutcome::Error, 6000),
+        ] {
+            metrics.record("zen", outcome, Duration::from_millis(elapsed), now + 10);
+        }
+        metrics.record(
+            "zen",
+            DnsBlOutcome::Hit,
+            Duration::from_millis(20),
+            now + DNSBL_METRICS_BUCKET,
+        );
+
+        let snapshot = metrics.snapshot(0);
+        let buckets = snapshot.get("zen").unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].start, now);
+        assert_eq!(
+            (
+                buckets[0].queries,
+                buckets[0].hits,
+                buckets[0].nxdomain,
+                buckets[0].errors
+            ),
+            (4, 1, 2, 1)
+        );
+        assert_eq!(buckets[0].elapsed_max, 6000);
+
+        let mut total = DnsBlBucket::default();
+        for bucket in buckets {
+            total.merge(bucket);
+        }
+        assert_eq!((total.queries, total.hits), (5, 2));
+        assert_eq!(total.latency_percentile(0.5), Some(50));
+        assert_eq!(total.latency_percentile(1.0), Some(6000));
+
+        // Only recent buckets are returned and old ones are discarded
+        assert_eq!(metrics.snapshot(now + DNSBL_METRICS_BUCKET)["zen"].len(), 1);
+        for hour in 2..=DNSBL_METRICS_MAX_BUCKETS as u64 + 1 {
+            metrics.record(
+                "zen",
+                DnsBlOutcome::Hit,
+                Duration::from_millis(1),
+                now + hour * DNSBL_METRICS_BUCKET,
+            );
+        }
+        let snapshot = metrics.snapshot(0);
+        assert_eq!(snapshot["zen"].len(), DNSBL_METRICS_MAX_BUCKETS);
+        assert_eq!(snapshot["zen"][0].start, now + 2 * DNSBL_METRICS_BUCKET);
+    }
 }
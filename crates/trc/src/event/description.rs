@@ -641,6 +641,8 @@ impl DeliveryEvent {
             DeliveryEvent::ImplicitTlsError => "Implicit TLS error",
             DeliveryEvent::ConcurrencyLimitExceeded => "Concurrency limit exceeded",
             DeliveryEvent::RateLimitExceeded => "Rate limit exceeded",
+            DeliveryEvent::MessageTooLarge => "Message too large for remote host",
+            DeliveryEvent::FallbackRoute => "Escalated to fallback route",
             DeliveryEvent::DoubleBounce => "Discarding message after double bounce",
             DeliveryEvent::DsnSuccess => "DSN success notification",
             DeliveryEvent::DsnTempFail => "DSN temporary failure notification",
@@ -694,6 +696,12 @@ impl DeliveryEvent {
                 "The concurrency limit was exceeded for the remote host"
             }
             DeliveryEvent::RateLimitExceeded => "The rate limit was exceeded for the remote host",
+            DeliveryEvent::MessageTooLarge => {
+                "The message exceeds the maximum size advertised by the remote host"
+            }
+            DeliveryEvent::FallbackRoute => {
+                "The recipient was escalated to the fallback route after repeated failures"
+            }
             DeliveryEvent::DoubleBounce => "The message was discarded after a double bounce",
             DeliveryEvent::DsnSuccess => "A success delivery status notification was created",
             DeliveryEvent::DsnTempFail => {
@@ -452,6 +452,8 @@ impl EventType {
                 | DeliveryEvent::DoubleBounce => Level::Info,
                 DeliveryEvent::ConcurrencyLimitExceeded
                 | DeliveryEvent::RateLimitExceeded
+                | DeliveryEvent::MessageTooLarge
+                | DeliveryEvent::FallbackRoute
                 | DeliveryEvent::MissingOutboundHostname => Level::Warn,
                 DeliveryEvent::DsnSuccess
                 | DeliveryEvent::DsnTempFail
@@ -625,6 +625,8 @@ impl EventType {
                 | DeliveryEvent::ImplicitTlsError
                 | DeliveryEvent::ConcurrencyLimitExceeded
                 | DeliveryEvent::RateLimitExceeded
+                | DeliveryEvent::MessageTooLarge
+                | DeliveryEvent::FallbackRoute
                 | DeliveryEvent::DoubleBounce
                 | DeliveryEvent::DsnSuccess
                 | DeliveryEvent::DsnTempFail
@@ -469,6 +469,8 @@ pub enum DeliveryEvent {
     ImplicitTlsError,
     ConcurrencyLimitExceeded,
     RateLimitExceeded,
+    MessageTooLarge,
+    FallbackRoute,
     DoubleBounce,
     DsnSuccess,
     DsnTempFail,
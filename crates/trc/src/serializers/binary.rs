@@ -897,6 +897,8 @@ impl EventType {
             EventType::TaskQueue(TaskQueueEvent::TaskFailed) => 587,
             EventType::Spam(SpamEvent::TrainStarted) => 588,
             EventType::Spam(SpamEvent::ModelLoaded) => 589,
+            EventType::Delivery(DeliveryEvent::MessageTooLarge) => 590,
+            EventType::Delivery(DeliveryEvent::FallbackRoute) => 591,
         }
     }
 
@@ -1532,6 +1534,8 @@ impl EventType {
             587 => Some(EventType::TaskQueue(TaskQueueEvent::TaskFailed)),
             588 => Some(EventType::Spam(SpamEvent::TrainStarted)),
             589 => Some(EventType::Spam(SpamEvent::ModelLoaded)),
+            590 => Some(EventType::Delivery(DeliveryEvent::MessageTooLarge)),
+            591 => Some(EventType::Delivery(DeliveryEvent::FallbackRoute)),
             _ => None,
         }
     }
@@ -56,6 +56,7 @@ pub enum MailboxField {
 pub enum SieveField {
     Name,
     Ids,
+    Versions,
     Archive,
 }
 
@@ -139,6 +140,7 @@ impl From<SieveField> for u8 {
         match value {
             SieveField::Name => 13,
             SieveField::Ids => 84,
+            SieveField::Versions => 85,
             SieveField::Archive => ARCHIVE_FIELD,
         }
     }
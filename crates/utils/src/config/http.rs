@@ -7,7 +7,7 @@
 use crate::config::{Config, utils::AsKey};
 use base64::{Engine, engine::general_purpose};
 use reqwest::{
-    Client,
+    Certificate, Client, ClientBuilder, Proxy,
     header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, USER_AGENT},
 };
 use std::{str::FromStr, time::Duration};
@@ -17,6 +17,21 @@ pub fn build_http_client(
     prefix: impl AsKey,
     content_type: Option<&str>,
 ) -> Option<Client> {
+    let prefix = prefix.as_key();
+    match http_client_builder(config, &prefix, content_type)?.build() {
+        Ok(client) => Some(client),
+        Err(err) => {
+            config.new_build_error(&prefix, format!("Failed to build HTTP client: {err}"));
+            None
+        }
+    }
+}
+
+pub fn http_client_builder(
+    config: &mut Config,
+    prefix: impl AsKey,
+    content_type: Option<&str>,
+) -> Option<ClientBuilder> {
     let mut headers = parse_http_headers(config, prefix.clone());
     headers.insert(USER_AGENT, "Stalwart/1.0.0".parse().unwrap());
 
@@ -25,7 +40,7 @@ pub fn build_http_client(
     }
 
     let prefix = prefix.as_key();
-    match Client::builder()
+    let mut builder = Client::builder()
         .connect_timeout(
             config
                 .property_or_default::<Duration>((&prefix, "timeout"), "30s")
@@ -36,15 +51,45 @@ pub fn build_http_client(
                 .property_or_default::<bool>((&prefix, "tls.allow-invalid-certs"), "false")
                 .unwrap_or(false),
         )
-        .default_headers(headers)
-        .build()
-    {
-        Ok(client) => Some(client),
-        Err(err) => {
-            config.new_build_error(&prefix, format!("Failed to build HTTP client: {err}"));
-            None
+        .default_headers(headers);
+
+    if let Some(proxy) = config.value((&prefix, "proxy")) {
+        match Proxy::all(proxy) {
+            Ok(proxy) => {
+                builder = builder.proxy(proxy);
+            }
+            Err(err) => {
+                config.new_parse_error((&prefix, "proxy"), format!("Invalid proxy URL: {err}"));
+                return None;
+            }
         }
     }
+
+    if let Some(pem) = config.value((&prefix, "tls.ca-certificate")) {
+        match Certificate::from_pem_bundle(pem.as_bytes()) {
+            Ok(certs) if !certs.is_empty() => {
+                for cert in certs {
+                    builder = builder.add_root_certificate(cert);
+                }
+            }
+            Ok(_) => {
+                config.new_parse_error(
+                    (&prefix, "tls.ca-certificate"),
+                    "No certificates found in PEM bundle",
+                );
+                return None;
+            }
+            Err(err) => {
+                config.new_parse_error(
+                    (&prefix, "tls.ca-certificate"),
+                    format!("Invalid CA certificate: {err}"),
+                );
+                return None;
+            }
+        }
+    }
+
+    Some(builder)
 }
 
 pub fn parse_http_headers(config: &mut Config, prefix: impl AsKey) -> HeaderMap {
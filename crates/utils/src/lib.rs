@@ -13,6 +13,7 @@ pub mod config;
 pub mod glob;
 pub mod map;
 pub mod snowflake;
+pub mod suffixlist;
 pub mod template;
 pub mod topological;
 pub mod url_params;
@@ -98,6 +98,23 @@ pub async fn test(mut imap_john: &mut ImapConnection, _imap_check: &mut ImapConn
     // Jane shares her Inbox to John, expect a Shared Folders item in John's list
     imap_jane.send("SETACL INBOX jdoe@example.com lr").await;
     imap_jane.assert_read(Type::Tagged, ResponseType::Ok).await;
+
+    // The advertised shared namespace should enumerate the shared folders
+    imap_john.send("NAMESPACE").await;
+    imap_john
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_equals(
+            "* NAMESPACE ((\"\" \"/\")) ((\"Shared Folders/jane.smith@example.com\" \"/\")) NIL",
+        );
+    imap_john
+        .send("LIST \"\" \"Shared Folders/jane.smith@example.com/*\"")
+        .await;
+    imap_john
+        .assert_read(Type::Tagged, ResponseType::Ok)
+        .await
+        .assert_equals("* LIST () \"/\" \"Shared Folders/jane.smith@example.com/INBOX\"");
+
     imap_john.send("LIST \"\" \"*\"").await;
     imap_john
         .assert_read(Type::Tagged, ResponseType::Ok)
@@ -131,12 +148,14 @@ pub async fn test(mut imap_john: &mut ImapConnection, _imap_check: &mut ImapConn
         .await
         .assert_contains("Shared Folders/jane.smith@example.com/INBOX");
 
-    // Namespace should now return the Shared Folders namespace
+    // Namespace should now return Jane's shared folders
     imap_john.send("NAMESPACE").await;
     imap_john
         .assert_read(Type::Tagged, ResponseType::Ok)
         .await
-        .assert_equals("* NAMESPACE ((\"\" \"/\")) ((\"Shared Folders\" \"/\")) NIL");
+        .assert_equals(
+            "* NAMESPACE ((\"\" \"/\")) ((\"Shared Folders/jane.smith@example.com\" \"/\")) NIL",
+        );
 
     // List John's right on Jane's Inbox
     imap_john
@@ -9,22 +9,31 @@ use crate::{
         JMAPTest,
         mail::{
             delivery::SmtpConnection,
-            submission::{MockMessage, assert_message_delivery, spawn_mock_smtp_server},
+            submission::{
+                MockMessage, assert_message_delivery, expect_nothing, spawn_mock_smtp_server,
+            },
         },
     },
     smtp::DnsCache,
 };
+use email::sieve::{SeenIdHash, ingest::SieveScriptIngest};
+use jmap::sieve::rollback::SieveScriptRollback;
 use jmap_client::{
     Error,
     core::set::{SetError, SetErrorType},
     email, mailbox,
     sieve::query::{Comparator, Filter},
 };
+use jmap_proto::request::method::MethodObject;
+use serde_json::json;
 use std::{
     fs,
     path::PathBuf,
+    str::FromStr,
     time::{Duration, Instant},
 };
+use store::{dispatch::lookup::KeyValue, write::now};
+use types::id::Id;
 
 pub async fn test(params: &mut JMAPTest) {
     println!("Running Sieve tests...");
@@ -47,6 +56,144 @@ pub async fn test(params: &mut JMAPTest) {
         }))
     ));
 
+    // Compile errors include a structured diagnostic
+    let blob_id = client
+        .upload(None, get_script("validate_error"), None)
+        .await
+        .unwrap()
+        .take_blob_id();
+    let response = account
+        .jmap_create(
+            MethodObject::SieveScript,
+            [json!({
+                "name": "broken",
+                "blobId": blob_id,
+            })],
+            Vec::<(&str, &str)>::new(),
+        )
+        .await;
+    let not_created = response.not_created(0);
+    assert_eq!(not_created["type"], "invalidScript");
+    let script_error = &not_created["scriptError"];
+    assert_eq!(script_error["line"], 1, "{not_created}");
+    assert!(
+        script_error["column"].as_u64().unwrap() > 0,
+        "{not_created}"
+    );
+    assert!(!script_error["message"].as_str().unwrap().is_empty());
+
+    // Include cycles are rejected
+    let mut include_ids = Vec::new();
+    for (name, script) in [
+        ("include_b", "keep;"),
+        (
+            "include_a",
+            "require \"include\"; include :personal \"include_b\";",
+        ),
+    ] {
+        let blob_id = client
+            .upload(None, script.as_bytes().to_vec(), None)
+            .await
+            .unwrap()
+            .take_blob_id();
+        include_ids.push(
+            account
+                .jmap_create(
+                    MethodObject::SieveScript,
+                    [json!({
+                        "name": name,
+                        "blobId": blob_id,
+                    })],
+                    Vec::<(&str, &str)>::new(),
+                )
+                .await
+                .created(0)["id"]
+                .as_str()
+                .unwrap()
+                .to_string(),
+        );
+    }
+    let blob_id = client
+        .upload(
+            None,
+            b"require \"include\"; include :personal \"include_a\";".to_vec(),
+            None,
+        )
+        .await
+        .unwrap()
+        .take_blob_id();
+    let response = account
+        .jmap_update(
+            MethodObject::SieveScript,
+            [(&include_ids[0], json!({ "blobId": blob_id }))],
+            Vec::<(&str, &str)>::new(),
+        )
+        .await;
+    assert_eq!(
+        response.not_updated(&include_ids[0])["type"],
+        "invalidScript"
+    );
+
+    // Includes in comments and multi-line strings are not followed
+    let blob_id = client
+        .upload(
+            None,
+            concat!(
+                "require [\"include\", \"vacation\"];\r\n",
+                "# include :personal \"include_a\";\r\n",
+                "vacation text:\r\ninclude :personal \"include_a\";\r\n.\r\n;\r\n",
+            )
+            .as_bytes()
+            .to_vec(),
+            None,
+        )
+        .await
+        .unwrap()
+        .take_blob_id();
+    account
+        .jmap_update(
+            MethodObject::SieveScript,
+            [(&include_ids[0], json!({ "blobId": blob_id }))],
+            Vec::<(&str, &str)>::new(),
+        )
+        .await
+        .updated(&include_ids[0]);
+
+    // Includes nested deeper than the runtime allows are rejected
+    for (name, include, is_valid) in [
+        ("include_c", "include_a", true),
+        ("include_d", "include_c", true),
+        ("include_e", "include_d", false),
+    ] {
+        let blob_id = client
+            .upload(
+                None,
+                format!("require \"include\"; include :personal \"{include}\";").into_bytes(),
+                None,
+            )
+            .await
+            .unwrap()
+            .take_blob_id();
+        let response = account
+            .jmap_create(
+                MethodObject::SieveScript,
+                [json!({
+                    "name": name,
+                    "blobId": blob_id,
+                })],
+                Vec::<(&str, &str)>::new(),
+            )
+            .await;
+        if is_valid {
+            include_ids.push(response.created(0)["id"].as_str().unwrap().to_string());
+        } else {
+            assert_eq!(response.not_created(0)["type"], "invalidScript");
+        }
+    }
+    for id in &include_ids {
+        client.sieve_script_destroy(id).await.unwrap();
+    }
+
     // Create 5 Sieve scripts, all deactivated.
     let mut script_ids = Vec::new();
     for i in 0..5 {
@@ -109,6 +256,71 @@ pub async fn test(params: &mut JMAPTest) {
         vec![script_ids.first().unwrap().to_string()]
     );
 
+    // Replace the active script and roll back to the previous version
+    let script_id = script_ids.first().unwrap();
+    for i in [10, 11] {
+        let blob_id = client
+            .upload(
+                None,
+                format!("require \"fileinto\"; fileinto \"{i}\";").into_bytes(),
+                None,
+            )
+            .await
+            .unwrap()
+            .take_blob_id();
+        account
+            .jmap_update(
+                MethodObject::SieveScript,
+                [(script_id, json!({ "blobId": blob_id }))],
+                Vec::<(&str, &str)>::new(),
+            )
+            .await
+            .updated(script_id);
+    }
+    let document_id = Id::from_str(script_id).unwrap().document_id();
+    let access_token = server
+        .get_access_token(account.id().document_id())
+        .await
+        .unwrap();
+    assert!(
+        server
+            .sieve_script_rollback(account.id().document_id(), document_id, 0, &access_token)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !server
+            .sieve_script_rollback(account.id().document_id(), document_id, 5, &access_token)
+            .await
+            .unwrap()
+    );
+    let script = client
+        .sieve_script_get(script_id, None::<Vec<_>>)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(client.download(script.blob_id().unwrap()).await.unwrap()).unwrap(),
+        "require \"fileinto\"; fileinto \"10\";"
+    );
+
+    // The source property excludes the compiled script
+    let response = account
+        .jmap_get(MethodObject::SieveScript, ["source"], [script_id])
+        .await;
+    assert_eq!(
+        response.list()[0]["source"],
+        "require \"fileinto\"; fileinto \"10\";"
+    );
+    assert_eq!(
+        client
+            .sieve_script_query(Filter::is_active(true).into(), [Comparator::name()].into())
+            .await
+            .unwrap()
+            .ids(),
+        vec![script_id.to_string()]
+    );
+
     // Destroying an active script should not work
     assert!(matches!(
         client
@@ -296,6 +508,39 @@ pub async fn test(params: &mut JMAPTest) {
     )
     .await;
 
+    // Duplicate ids are never retained for longer than the configured retention
+    let retention = server.core.sieve.duplicate_retention.as_secs() as i64;
+    let id_hash = SeenIdHash::new(u32::MAX, 0, "retention");
+    let timestamp = now() as i64;
+    for (now, expected) in [
+        (timestamp, false),
+        (timestamp + retention - 1, true),
+        (timestamp + retention, false),
+    ] {
+        assert_eq!(
+            server
+                .sieve_duplicate_id(&id_hash, u64::MAX, false, now)
+                .await
+                .unwrap(),
+            expected,
+            "{now}"
+        );
+    }
+
+    // Ids stored without a timestamp by previous versions are considered current
+    let id_hash = SeenIdHash::new(u32::MAX, 0, "legacy");
+    server
+        .in_memory_store()
+        .key_set(KeyValue::new(id_hash.key(), vec![]).expires(60))
+        .await
+        .unwrap();
+    assert!(
+        server
+            .sieve_duplicate_id(&id_hash, 60, false, timestamp)
+            .await
+            .unwrap()
+    );
+
     // Run include tests
     client
         .sieve_script_create("test_include_this", get_script("test_include_this"), false)
@@ -406,6 +651,135 @@ pub async fn test(params: &mut JMAPTest) {
         "Redirected message was stored."
     );
 
+    // Vacation responses are not sent again after rolling back a script
+    let script_id = client
+        .sieve_script_create(
+            "test_vacation_rollback",
+            b"require \"vacation\"; vacation \"I am away\"; discard;".to_vec(),
+            true,
+        )
+        .await
+        .unwrap()
+        .take_id();
+    for (script, text) in [
+        (None, Some("@I am away")),
+        (
+            Some("vacation \"I am still away\";"),
+            Some("@I am still away"),
+        ),
+        (None, None),
+    ] {
+        if let Some(script) = script {
+            let blob_id = client
+                .upload(
+                    None,
+                    format!("require \"vacation\"; {script} discard;").into_bytes(),
+                    None,
+                )
+                .await
+                .unwrap()
+                .take_blob_id();
+            account
+                .jmap_update(
+                    MethodObject::SieveScript,
+                    [(&script_id, json!({ "blobId": blob_id }))],
+                    Vec::<(&str, &str)>::new(),
+                )
+                .await
+                .updated(&script_id);
+        } else if text.is_none() {
+            assert!(
+                server
+                    .sieve_script_rollback(
+                        account.id().document_id(),
+                        Id::from_str(&script_id).unwrap().document_id(),
+                        0,
+                        &access_token,
+                    )
+                    .await
+                    .unwrap()
+            );
+        }
+
+        lmtp.ingest(
+            "milton@remote.org",
+            &["jdoe@example.com"],
+            concat!(
+                "From: milton@remote.org\r\n",
+                "To: jdoe@example.com\r\n",
+                "Subject: Stapler\r\n",
+                "\r\n",
+                "Have you seen my stapler?"
+            ),
+        )
+        .await;
+        if let Some(text) = text {
+            assert_message_delivery(
+                &mut smtp_rx,
+                MockMessage::new("<jdoe@example.com>", ["<milton@remote.org>"], text),
+            )
+            .await;
+        } else {
+            expect_nothing(&mut smtp_rx).await;
+        }
+    }
+
+    // Changes to included scripts take effect without saving the including script
+    let include_id = client
+        .sieve_script_create(
+            "shared_rules",
+            b"require \"vacation\"; vacation \"Shared rules v1\";".to_vec(),
+            false,
+        )
+        .await
+        .unwrap()
+        .take_id();
+    client
+        .sieve_script_create(
+            "test_include",
+            b"require \"include\"; include :personal \"shared_rules\"; discard;".to_vec(),
+            true,
+        )
+        .await
+        .unwrap();
+    for (version, sender) in [("v1", "peter@remote.org"), ("v2", "samir@remote.org")] {
+        if version == "v2" {
+            let blob_id = client
+                .upload(
+                    None,
+                    b"require \"vacation\"; vacation \"Shared rules v2\";".to_vec(),
+                    None,
+                )
+                .await
+                .unwrap()
+                .take_blob_id();
+            account
+                .jmap_update(
+                    MethodObject::SieveScript,
+                    [(&include_id, json!({ "blobId": blob_id }))],
+                    Vec::<(&str, &str)>::new(),
+                )
+                .await
+                .updated(&include_id);
+        }
+
+        lmtp.ingest(
+            sender,
+            &["jdoe@example.com"],
+            &format!("From: {sender}\r\nTo: jdoe@example.com\r\nSubject: Lunch\r\n\r\nLunch?"),
+        )
+        .await;
+        assert_message_delivery(
+            &mut smtp_rx,
+            MockMessage::new(
+                "<jdoe@example.com>".to_string(),
+                [format!("<{sender}>")],
+                format!("@Shared rules {version}"),
+            ),
+        )
+        .await;
+    }
+
     // Run notify + editheader + notify + fcc tests
     client
         .sieve_script_create("test_notify_fcc", get_script("test_notify_fcc"), true)
@@ -632,6 +632,31 @@ pub async fn assert_message_delivery(
     }
 }
 
+// Collects the messages delivered before the event stream goes quiet, the
+// count must match exactly so that duplicate deliveries are also caught
+pub async fn expect_message_count(
+    event_rx: &mut mpsc::Receiver<MockMessage>,
+    count: usize,
+) -> Vec<MockMessage> {
+    let mut messages = Vec::with_capacity(count);
+    loop {
+        let wait = if messages.len() < count { 3000 } else { 500 };
+        match tokio::time::timeout(Duration::from_millis(wait), event_rx.recv()).await {
+            Ok(Some(message)) => messages.push(message),
+            _ => break,
+        }
+    }
+
+    assert_eq!(
+        messages.len(),
+        count,
+        "Expected {count} messages, got: {:?}",
+        messages
+    );
+
+    messages
+}
+
 pub async fn expect_nothing(event_rx: &mut mpsc::Receiver<MockMessage>) {
     match tokio::time::timeout(Duration::from_millis(500), event_rx.recv()).await {
         Err(_) => {}
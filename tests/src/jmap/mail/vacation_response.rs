@@ -10,14 +10,21 @@ use crate::{
         mail::{
             delivery::SmtpConnection,
             submission::{
-                MockMessage, assert_message_delivery, expect_nothing, spawn_mock_smtp_server,
+                MockMessage, assert_message_delivery, expect_message_count,
+                expect_message_delivery, expect_nothing, spawn_mock_smtp_server,
             },
         },
     },
     smtp::DnsCache,
 };
 use chrono::{TimeDelta, Utc};
-use std::time::Instant;
+use jmap_proto::request::method::MethodObject;
+use mail_parser::MessageParser;
+use serde_json::json;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 pub async fn test(params: &mut JMAPTest) {
     println!("Running Vacation Response tests...");
@@ -55,6 +62,7 @@ pub async fn test(params: &mut JMAPTest) {
         concat!(
             "From: bill@remote.org\r\n",
             "To: jdoe@example.com\r\n",
+            "Message-ID: <tps-report@remote.org>\r\n",
             "Subject: TPS Report\r\n",
             "\r\n",
             "I'm going to need those TPS reports ASAP. ",
@@ -63,12 +71,30 @@ pub async fn test(params: &mut JMAPTest) {
     )
     .await;
 
-    // Await vacation response
-    assert_message_delivery(
-        &mut smtp_rx,
-        MockMessage::new("<jdoe@example.com>", ["<bill@remote.org>"], "@Kokomo"),
-    )
-    .await;
+    // Await vacation response, which should thread with the original message
+    let response = expect_message_delivery(&mut smtp_rx).await;
+    assert_eq!(response.mail_from, "<jdoe@example.com>");
+    assert_eq!(response.rcpt_to, vec!["<bill@remote.org>".to_string()]);
+    let message = MessageParser::new()
+        .parse(response.message.as_bytes())
+        .unwrap();
+    assert_eq!(
+        message.subject(),
+        Some("Off the Florida Keys there's a place called Kokomo")
+    );
+    assert_eq!(
+        message.in_reply_to().as_text(),
+        Some("tps-report@remote.org")
+    );
+    assert_eq!(
+        message
+            .references()
+            .as_text_list()
+            .unwrap_or_default()
+            .last()
+            .map(|id| id.as_ref()),
+        Some("tps-report@remote.org")
+    );
 
     // Further messages from the same recipient should not
     // trigger a vacation response
@@ -104,6 +130,142 @@ pub async fn test(params: &mut JMAPTest) {
 
     expect_nothing(&mut smtp_rx).await;
 
+    // Automatic replies should not trigger a vacation response
+    lmtp.ingest(
+        "milton@remote.org",
+        &["jdoe@example.com"],
+        concat!(
+            "From: milton@remote.org\r\n",
+            "To: jdoe@example.com\r\n",
+            "Auto-Submitted: auto-replied\r\n",
+            "Subject: Re: TPS Report\r\n",
+            "\r\n",
+            "I was told I could keep my stapler while you are away.",
+        ),
+    )
+    .await;
+
+    expect_nothing(&mut smtp_rx).await;
+
+    // Mailing list messages should not trigger a vacation response
+    lmtp.ingest(
+        "initech-bounces@lists.remote.org",
+        &["jdoe@example.com"],
+        concat!(
+            "From: peter@remote.org\r\n",
+            "To: staff@lists.remote.org\r\n",
+            "List-Id: Initech Staff <staff.lists.remote.org>\r\n",
+            "Precedence: list\r\n",
+            "Subject: Hawaiian shirt day\r\n",
+            "\r\n",
+            "Friday is Hawaiian shirt day, everyone is welcome.",
+        ),
+    )
+    .await;
+
+    expect_nothing(&mut smtp_rx).await;
+
+    // Each sender receives a single response, regardless of how many
+    // messages they send
+    for (from, subject) in [
+        ("michael@remote.org", "Printer"),
+        ("samir@remote.org", "Parking lot"),
+        ("michael@remote.org", "Printer, again"),
+    ] {
+        lmtp.ingest(
+            from,
+            &["jdoe@example.com"],
+            &format!(
+                "From: {from}\r\nTo: jdoe@example.com\r\nSubject: {subject}\r\n\r\nPC load letter?"
+            ),
+        )
+        .await;
+    }
+    let mut recipients = expect_message_count(&mut smtp_rx, 2)
+        .await
+        .into_iter()
+        .flat_map(|message| message.rcpt_to)
+        .collect::<Vec<_>>();
+    recipients.sort();
+    assert_eq!(recipients, ["<michael@remote.org>", "<samir@remote.org>"]);
+
+    // Changes to the renotify interval apply to existing vacation responses
+    let old_core = params.server.core.clone();
+    let mut new_core = old_core.as_ref().clone();
+    new_core.sieve.untrusted_runtime.set_default_vacation_expiry(1);
+    params.server.inner.shared_core.store(Arc::new(new_core));
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    lmtp.ingest(
+        "bill@remote.org",
+        &["jdoe@example.com"],
+        concat!(
+            "From: bill@remote.org\r\n",
+            "To: jdoe@example.com\r\n",
+            "Subject: TPS Report -- second reminder\r\n",
+            "\r\n",
+            "Did you get the memo about the new cover sheets?",
+        ),
+    )
+    .await;
+    assert_message_delivery(
+        &mut smtp_rx,
+        MockMessage::new("<jdoe@example.com>", ["<bill@remote.org>"], "@Kokomo"),
+    )
+    .await;
+    params.server.inner.shared_core.store(old_core);
+
+    // Reply at most once per mailing list when enabled
+    let response = account
+        .jmap_update(
+            MethodObject::VacationResponse,
+            [("singleton", json!({ "replyOncePerList": true }))],
+            Vec::<(&str, &str)>::new(),
+        )
+        .await;
+    response.updated("singleton");
+    let response = account
+        .jmap_get(
+            MethodObject::VacationResponse,
+            ["replyOncePerList"],
+            Vec::<&str>::new(),
+        )
+        .await;
+    assert_eq!(response.list()[0]["replyOncePerList"], true);
+    for (from, list_id) in [
+        ("staff+peter@lists.remote.org", "staff.lists.remote.org"),
+        ("staff+lumbergh@lists.remote.org", "staff.lists.remote.org"),
+        ("ball+nina@lists.remote.org", "ball.lists.remote.org"),
+        ("staff+tom@lists.remote.org", "staff.lists.remote.org"),
+    ] {
+        lmtp.ingest(
+            from,
+            &["jdoe@example.com"],
+            &format!(
+                concat!(
+                    "From: {from}\r\nTo: jdoe@example.com\r\n",
+                    "List-Id: <{list_id}>\r\nPrecedence: list\r\n",
+                    "Subject: Meeting\r\n\r\nYeah, I'm gonna need you to come in on Saturday."
+                ),
+                from = from,
+                list_id = list_id
+            ),
+        )
+        .await;
+    }
+    let mut recipients = expect_message_count(&mut smtp_rx, 2)
+        .await
+        .into_iter()
+        .flat_map(|message| message.rcpt_to)
+        .collect::<Vec<_>>();
+    recipients.sort();
+    assert_eq!(
+        recipients,
+        [
+            "<ball+nina@lists.remote.org>",
+            "<staff+peter@lists.remote.org>"
+        ]
+    );
+
     // Vacation responses should honor the configured date ranges
     client
         .vacation_response_set_dates(
@@ -1208,6 +1208,19 @@ impl ManagementApi {
         })
     }
 
+    pub async fn post_raw<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        body: impl Into<String>,
+    ) -> Result<Response<T>, String> {
+        self.request_raw(Method::POST, query, Some(body.into()))
+            .await
+            .map(|result| {
+                serde_json::from_str::<Response<T>>(&result)
+                    .unwrap_or_else(|err| panic!("{err}: {result}"))
+            })
+    }
+
     pub async fn patch<T: DeserializeOwned>(
         &self,
         query: &str,
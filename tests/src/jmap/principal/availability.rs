@@ -6,8 +6,10 @@
 
 use crate::jmap::{IntoJmapSet, JMAPTest, JmapUtils, calendar::event::*};
 use calcard::jscalendar::JSCalendarProperty;
+use common::config::groupware::FreeBusyVisibility;
 use jmap_proto::request::method::MethodObject;
 use serde_json::json;
+use std::sync::Arc;
 use types::id::Id;
 
 pub async fn test(params: &mut JMAPTest) {
@@ -158,6 +160,55 @@ pub async fn test(params: &mut JMAPTest) {
       }
     ]));
 
+    // The default visibility applies to other accounts, owners always see the full details
+    let old_core = params.server.core.clone();
+    for visibility in [
+        FreeBusyVisibility::None,
+        FreeBusyVisibility::BusyOnly,
+        FreeBusyVisibility::Full,
+    ] {
+        let mut new_core = old_core.as_ref().clone();
+        new_core.groupware.free_busy_default_visibility = visibility;
+        params.server.inner.shared_core.store(Arc::new(new_core));
+
+        for (account, is_owner) in [(jane, false), (john, true)] {
+            let response = account
+                .jmap_method_calls(json!([[
+                    "Principal/getAvailability",
+                    {
+                        "id": &john_id,
+                        "utcStart": "2006-01-01T00:00:00Z",
+                        "utcEnd": "2006-01-08T00:00:00Z",
+                        "eventProperties": ["id"],
+                    },
+                    "0"
+                ]]))
+                .await;
+            let periods = response.list();
+            let visibility = if is_owner {
+                FreeBusyVisibility::Full
+            } else {
+                visibility
+            };
+
+            match visibility {
+                FreeBusyVisibility::None => assert!(periods.is_empty()),
+                FreeBusyVisibility::BusyOnly => {
+                    assert!(!periods.is_empty());
+                    assert!(
+                        periods.iter().all(|period| period["event"].is_null()
+                            && period["busyStatus"] == "confirmed")
+                    );
+                }
+                FreeBusyVisibility::Full => {
+                    assert!(!periods.is_empty());
+                    assert!(periods.iter().all(|period| period["event"].is_object()));
+                }
+            }
+        }
+    }
+    params.server.inner.shared_core.store(old_core);
+
     // Update availability to none
     john.jmap_update(
         MethodObject::Calendar,
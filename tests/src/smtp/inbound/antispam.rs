@@ -13,7 +13,7 @@ use ahash::{AHashMap, AHashSet};
 use common::{
     Core, Server,
     auth::AccessToken,
-    config::spamfilter::SpamFilterAction,
+    config::spamfilter::{DnsBlTerminal, Element, IpResolver, SpamFilterAction},
     enterprise::{
         SpamFilterLlmConfig,
         llm::{
@@ -53,6 +53,7 @@ use spam_filter::{
 };
 use std::{
     fs,
+    net::{Ipv4Addr, Ipv6Addr},
     path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
@@ -171,6 +172,14 @@ allow-invalid-certs = true
 "surbl-hashbl" = {"bit.ly", "drive.google.com", "lnkiy.in"}
 "#;
 
+const SINGLE_CODE_ZONES: &[&str] = &[
+    ".bl.spameatingmonkey.net",
+    ".b.barracudacentral.org",
+    ".bl.blocklist.de",
+    ".bip.virusfree.cz",
+    ".bl.spamcop.net",
+];
+
 #[tokio::test(flavor = "multi_thread")]
 async fn antispam() {
     // Enable logging
@@ -215,7 +224,7 @@ async fn antispam() {
     core.enterprise.as_mut().unwrap().spam_filter_llm =
         SpamFilterLlmConfig::parse(&mut config, &ai_apis);
     crate::AssertConfig::assert_no_errors(config);
-    let server = TestSMTP::from_core(core).server;
+    let TestSMTP { server, dnsbl, .. } = TestSMTP::from_core(core);
 
     // Add mock DNS entries
     for (domain, ip) in [
@@ -255,11 +264,39 @@ async fn antispam() {
             vec![ip.parse().unwrap()],
             Instant::now() + Duration::from_secs(100),
         );
-        server.dnsbl_add(
-            domain,
-            vec![ip.parse().unwrap()],
-            Instant::now() + Duration::from_secs(100),
-        );
+        dnsbl.add(domain, vec![ip.parse().unwrap()]);
+    }
+    // Add mock DNSBL responses for the test IP range, listed IPs return
+    // 127.0.0.<last octet> except on blocklists publishing a single return
+    // code, which only list 20.11.0.2
+    for config in server
+        .core
+        .spam
+        .dnsbl
+        .servers
+        .iter()
+        .filter(|config| config.scope == Element::Ip)
+    {
+        for octet in 0..=255u8 {
+            let Some(zone) = server
+                .eval_if::<String, _>(
+                    &config.zone,
+                    &IpResolver::new(Ipv4Addr::new(20, 11, 0, octet).into()),
+                    0,
+                )
+                .await
+            else {
+                continue;
+            };
+
+            if octet == 2
+                || !SINGLE_CODE_ZONES
+                    .iter()
+                    .any(|suffix| zone.ends_with(suffix))
+            {
+                dnsbl.add(&zone, vec![Ipv4Addr::new(127, 0, 0, octet)]);
+            }
+        }
     }
     for mx in [
         "domain.org",
@@ -857,3 +894,257 @@ impl ParseConfigValue for Policy {
         }
     }
 }
+
+const DNSBL_GRACE_CONFIG: &str = r#"
+[spam-filter.dnsbl.server.grace]
+scope = "ip"
+suffix = "bl.example.org"
+tag = "'RBL_LISTED'"
+terminal = "block"
+grace-period.duration = "1h"
+grace-period.min-checks = 2
+grace-period.tag = "RBL_NEW_LISTING"
+"#;
+
+#[tokio::test]
+async fn antispam_dnsbl_grace_period() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_antispam_dnsbl_grace_test", DNSBL_GRACE_CONFIG).await;
+    let server = local.server.clone();
+    let remote_ip = Ipv4Addr::new(20, 11, 0, 2);
+    let zone = server
+        .eval_if::<String, _>(
+            &server.core.spam.dnsbl.servers[0].zone,
+            &IpResolver::new(remote_ip.into()),
+            0,
+        )
+        .await
+        .unwrap();
+    local.dnsbl.add(&zone, vec![Ipv4Addr::new(127, 0, 0, 2)]);
+
+    let message = MessageParser::new()
+        .parse(b"Subject: test\r\n\r\ntest\r\n".as_slice())
+        .unwrap();
+    let mut session = Session::test(server.clone());
+    session.data.remote_ip_str = remote_ip.to_string();
+    session.data.remote_ip = remote_ip.into();
+    let checks = || {
+        server
+            .inner
+            .cache
+            .dns_rbl_listings
+            .get(zone.as_str())
+            .map(|listing| listing.checks)
+    };
+
+    // New listings are soft-tagged until they are old enough and seen often enough,
+    // answers served from the cache do not count as checks
+    for (expire_cache, expected_checks) in [(false, 1), (false, 1), (true, 2)] {
+        if expire_cache {
+            server.inner.cache.dns_rbl.remove(zone.as_str());
+        }
+        let mut spam_ctx =
+            server.spam_filter_init(session.build_spam_input(&message, &[], None, None, None));
+        server.spam_filter_analyze_ip(&mut spam_ctx).await;
+        assert!(spam_ctx.result.has_tag("RBL_NEW_LISTING"));
+        assert!(!spam_ctx.result.has_tag("RBL_LISTED"));
+        assert_eq!(spam_ctx.result.dnsbl_terminal, None);
+        assert_eq!(checks(), Some(expected_checks));
+    }
+
+    // Once the grace period is over the listing is a hard block
+    let mut listing = server
+        .inner
+        .cache
+        .dns_rbl_listings
+        .get(zone.as_str())
+        .unwrap();
+    listing.first_seen -= 7200;
+    server
+        .inner
+        .cache
+        .dns_rbl_listings
+        .insert(zone.clone(), listing);
+
+    let mut spam_ctx =
+        server.spam_filter_init(session.build_spam_input(&message, &[], None, None, None));
+    server.spam_filter_analyze_ip(&mut spam_ctx).await;
+    assert!(spam_ctx.result.has_tag("RBL_LISTED"));
+    assert!(!spam_ctx.result.has_tag("RBL_NEW_LISTING"));
+    assert_eq!(spam_ctx.result.dnsbl_terminal, Some(DnsBlTerminal::Block));
+
+    // Delisting forgets the listing, so a new listing starts another grace period
+    local.dnsbl.remove(&zone);
+    server.inner.cache.dns_rbl.remove(zone.as_str());
+    let mut spam_ctx =
+        server.spam_filter_init(session.build_spam_input(&message, &[], None, None, None));
+    server.spam_filter_analyze_ip(&mut spam_ctx).await;
+    assert!(!spam_ctx.result.has_tag("RBL_LISTED"));
+    assert!(!spam_ctx.result.has_tag("RBL_NEW_LISTING"));
+    assert_eq!(checks(), None);
+}
+
+const DNSBL_REASON_CONFIG: &str = r#"
+[spam-filter.dnsbl.server.reason]
+scope = "ip"
+suffix = "bl.example.org"
+reason = true
+tag = [{if = "reason != ''", then = "'RBL_WITH_REASON'"},
+       {else = "'RBL_WITHOUT_REASON'"}]
+"#;
+
+#[tokio::test]
+async fn antispam_dnsbl_reason() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_antispam_dnsbl_reason_test", DNSBL_REASON_CONFIG).await;
+    let server = local.server.clone();
+    let remote_ip = Ipv4Addr::new(20, 11, 0, 2);
+    let zone = server
+        .eval_if::<String, _>(
+            &server.core.spam.dnsbl.servers[0].zone,
+            &IpResolver::new(remote_ip.into()),
+            0,
+        )
+        .await
+        .unwrap();
+    local.dnsbl.add(&zone, vec![Ipv4Addr::new(127, 0, 0, 2)]);
+
+    let message = MessageParser::new()
+        .parse(b"Subject: test\r\n\r\ntest\r\n".as_slice())
+        .unwrap();
+    let mut session = Session::test(server.clone());
+    session.data.remote_ip_str = remote_ip.to_string();
+    session.data.remote_ip = remote_ip.into();
+
+    // The listing reason is cached with the result and exposed to the tags expression
+    let mut spam_ctx =
+        server.spam_filter_init(session.build_spam_input(&message, &[], None, None, None));
+    server.spam_filter_analyze_ip(&mut spam_ctx).await;
+    assert!(spam_ctx.result.has_tag("RBL_WITH_REASON"));
+    assert!(!spam_ctx.result.has_tag("RBL_WITHOUT_REASON"));
+    assert_eq!(
+        server
+            .inner
+            .cache
+            .dns_rbl
+            .get(zone.as_str())
+            .flatten()
+            .and_then(|result| result.reason.clone()),
+        Some(format!("Listed in {zone}"))
+    );
+}
+
+const DNSBL_ALLOW_CONFIG: &str = r#"
+[spam-filter.dnsbl.server.spammy]
+scope = "ip"
+suffix = "spam.example.org"
+tag = "'RBL_SPAMMY'"
+score = "10.0"
+
+[spam-filter.dnsbl.server.allow]
+scope = "ip"
+suffix = "allow.example.org"
+tag = "'RBL_ALLOWLISTED'"
+terminal = "allow"
+
+[spam-filter.list.scores]
+"RBL_SPAMMY" = "reject"
+"#;
+
+#[tokio::test]
+async fn antispam_dnsbl_allow() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_antispam_dnsbl_allow_test", DNSBL_ALLOW_CONFIG).await;
+    let server = local.server.clone();
+    let remote_ip = Ipv4Addr::new(20, 11, 0, 2);
+    for config in &server.core.spam.dnsbl.servers {
+        let zone = server
+            .eval_if::<String, _>(&config.zone, &IpResolver::new(remote_ip.into()), 0)
+            .await
+            .unwrap();
+        local.dnsbl.add(&zone, vec![Ipv4Addr::new(127, 0, 0, 2)]);
+    }
+
+    let message = MessageParser::new()
+        .parse(b"Subject: test\r\n\r\ntest\r\n".as_slice())
+        .unwrap();
+    let mut session = Session::test(server.clone());
+    session.data.remote_ip_str = remote_ip.to_string();
+    session.data.remote_ip = remote_ip.into();
+
+    // An allowlisted sender is accepted as ham even though it is also listed as spam
+    let mut spam_ctx =
+        server.spam_filter_init(session.build_spam_input(&message, &[], None, None, None));
+    server.spam_filter_analyze_ip(&mut spam_ctx).await;
+    assert!(spam_ctx.result.has_tag("RBL_SPAMMY"));
+    assert!(spam_ctx.result.has_tag("RBL_ALLOWLISTED"));
+    match server.spam_filter_terminal(&mut spam_ctx).await {
+        Some(SpamFilterAction::Allow(result)) => {
+            assert_eq!(result.score, 0.0);
+            assert!(result.results.iter().all(|is_spam| !is_spam));
+            assert!(
+                result.headers.contains("X-Spam-Score: ham, score=0.00"),
+                "{}",
+                result.headers
+            );
+        }
+        other => panic!("Expected an allowed message, got {other:?}"),
+    }
+}
+
+const DNSBL_IPV6_CONFIG: &str = r#"
+[spam-filter.dnsbl.server.v6]
+scope = "ip"
+suffix = "v6.example.org"
+tag = "'RBL_V6_LISTED'"
+"#;
+
+#[tokio::test]
+async fn antispam_dnsbl_ipv6() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_antispam_dnsbl_ipv6_test", DNSBL_IPV6_CONFIG).await;
+    let server = local.server.clone();
+    let listed_ip: Ipv6Addr = "2001:db8::1".parse().unwrap();
+    let unlisted_ip: Ipv6Addr = "2001:db8::2".parse().unwrap();
+
+    // IPv6 addresses are queried by their reversed nibbles
+    let zone = server
+        .eval_if::<String, _>(
+            &server.core.spam.dnsbl.servers[0].zone,
+            &IpResolver::new(listed_ip.into()),
+            0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        zone,
+        "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.v6.example.org"
+    );
+    local.dnsbl.add(&zone, vec![Ipv4Addr::new(127, 0, 0, 2)]);
+
+    let message = MessageParser::new()
+        .parse(b"Subject: test\r\n\r\ntest\r\n".as_slice())
+        .unwrap();
+    for (remote_ip, is_listed) in [(listed_ip, true), (unlisted_ip, false)] {
+        let mut session = Session::test(server.clone());
+        session.data.remote_ip_str = remote_ip.to_string();
+        session.data.remote_ip = remote_ip.into();
+
+        let mut spam_ctx =
+            server.spam_filter_init(session.build_spam_input(&message, &[], None, None, None));
+        server.spam_filter_analyze_ip(&mut spam_ctx).await;
+        assert_eq!(
+            spam_ctx.result.has_tag("RBL_V6_LISTED"),
+            is_listed,
+            "{remote_ip}"
+        );
+    }
+}
@@ -6,3 +6,4 @@
 
 pub mod queue;
 pub mod report;
+pub mod sieve;
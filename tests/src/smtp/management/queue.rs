@@ -246,6 +246,11 @@ async fn manage_queue() {
                     "{:?}",
                     rcpt.status
                 );
+                let last_response = rcpt.last_response.as_ref().unwrap();
+                assert_eq!(last_response.hostname, "mx1.foobar.org");
+                assert_eq!(last_response.code, 451);
+                assert_eq!(last_response.enhanced_code, "4.5.3");
+                assert_eq!(last_response.message, "Try again later.");
             }
         }
 
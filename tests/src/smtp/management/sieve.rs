@@ -0,0 +1,309 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use common::config::server::ServerProtocol;
+use reqwest::Method;
+use serde_json::{Value, json};
+
+use crate::{
+    jmap::{ManagementApi, Response},
+    smtp::TestSMTP,
+};
+
+const CONFIG: &str = r#"
+[storage]
+directory = "local"
+
+[directory."local"]
+type = "memory"
+
+[[directory."local".principals]]
+name = "admin"
+type = "admin"
+description = "Superuser"
+secret = "secret"
+class = "admin"
+
+[sieve.trusted.scripts.envelope]
+contents = '''
+require ["envelope", "editheader"];
+
+if envelope :is "from" "jane@example.org" {
+    addheader "X-From" "jane";
+}
+if envelope :is "to" "bill@example.org" {
+    addheader "X-To" "bill";
+}
+if envelope :is "to" "mike@example.org" {
+    addheader "X-To" "mike";
+}
+'''
+
+[sieve.trusted.scripts.actions]
+contents = '''
+require ["fileinto", "imap4flags"];
+
+fileinto :flags "\\Seen" "Archive";
+keep :flags ["\\Flagged"];
+'''
+
+[sieve.trusted.scripts.message]
+contents = '''
+require ["editheader", "reject"];
+
+if header :contains "subject" "invoice" {
+    reject "No invoices please";
+} elsif body :contains "lottery" {
+    discard;
+}
+'''
+
+[sieve.trusted.scripts.now]
+contents = '''
+require ["variables", "editheader", "vnd.stalwart.expressions"];
+
+let "ts" "env.now";
+addheader "X-Now" "${ts}";
+'''
+
+[sieve.trusted.scripts.tag]
+contents = '''
+require ["editheader"];
+
+addheader "X-Spam" "yes";
+'''
+
+[sieve.trusted.scripts.junk]
+contents = '''
+require ["fileinto"];
+
+if header :is "X-Spam" "yes" {
+    fileinto "Junk";
+}
+'''
+
+[sieve.trusted.scripts.restricted]
+contents = '''
+keep;
+'''
+test-access = ["jane"]
+
+[sieve.trusted.limits]
+test-cpu = 100
+"#;
+
+#[tokio::test]
+#[serial_test::serial]
+async fn manage_sieve() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Start management interface
+    let local = TestSMTP::new(
+        "smtp_manage_sieve",
+        [
+            CONFIG,
+            "\n[sieve.trusted.scripts.spin]\ncontents = '''\n",
+            "require [\"variables\", \"editheader\"];\n",
+            &"set \"i\" \"${i}.\";\n".repeat(500),
+            "addheader \"X-Done\" \"yes\";\n'''\n",
+        ]
+        .concat(),
+    )
+    .await;
+    let _rx = local.start(&[ServerProtocol::Http]).await;
+    let api = ManagementApi::default();
+
+    // Repeated envelope parameters are accumulated
+    let response = api
+        .request::<Value>(
+            Method::POST,
+            concat!(
+                "/api/sieve/envelope?env_from=john@example.org&env_from2=Jane@Example.org",
+                "&env_to=bill@example.org&env_to2=mike@example.org"
+            ),
+        )
+        .await
+        .unwrap()
+        .unwrap_data();
+    assert_eq!(
+        response,
+        json!({
+            "action": "accept",
+            "modifications": [
+                {"action": "addHeader", "name": "X-From", "value": "jane"},
+                {"action": "addHeader", "name": "X-To", "value": "bill"},
+                {"action": "addHeader", "name": "X-To", "value": "mike"}
+            ],
+            "actions": []
+        })
+    );
+
+    // Final actions are reported
+    let response = api
+        .request::<Value>(Method::POST, "/api/sieve/actions")
+        .await
+        .unwrap()
+        .unwrap_data();
+    assert_eq!(
+        response,
+        json!({
+            "action": "accept",
+            "modifications": [],
+            "actions": [
+                {"type": "fileInto", "mailbox": "Archive", "flags": ["\\Seen"]},
+                {"type": "keep", "flags": ["\\Flagged"]}
+            ]
+        })
+    );
+
+    // Execution traces are included on request
+    let response = api
+        .request::<Value>(Method::POST, "/api/sieve/actions?trace=1")
+        .await
+        .unwrap()
+        .unwrap_data();
+    assert_eq!(
+        response["trace"],
+        json!([
+            {
+                "type": "action",
+                "action": {"type": "fileInto", "mailbox": "Archive", "flags": ["\\Seen"]}
+            },
+            {
+                "type": "action",
+                "action": {"type": "keep", "flags": ["\\Flagged"]}
+            }
+        ])
+    );
+
+    // Native tests are not traced, only the actions they lead to
+    let response = api
+        .post_raw::<Value>(
+            "/api/sieve/message?trace=1",
+            "Subject: Hello\r\n\r\nYou won the lottery!\r\n",
+        )
+        .await
+        .unwrap()
+        .unwrap_data();
+    assert_eq!(
+        response["trace"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["type"].clone())
+            .collect::<Vec<_>>(),
+        vec![json!("action")],
+        "{response}"
+    );
+
+    // Header and body tests are evaluated against the posted message
+    for (message, expected) in [
+        (
+            "Subject: Your invoice\r\n\r\nPlease pay.\r\n",
+            json!({"action": "reject", "reason": "503 5.5.3 No invoices please\r\n"}),
+        ),
+        (
+            "Subject: Hello\r\n\r\nYou won the lottery!\r\n",
+            json!({"action": "discard"}),
+        ),
+        (
+            "Subject: Hello\r\n\r\nHow are you?\r\n",
+            json!({"action": "accept"}),
+        ),
+    ] {
+        let response = api
+            .post_raw::<Value>("/api/sieve/message", message)
+            .await
+            .unwrap()
+            .unwrap_data();
+        assert_eq!(response["action"], expected["action"], "{response}");
+        if let Some(reason) = expected.get("reason") {
+            assert_eq!(&response["reason"], reason, "{response}");
+        }
+    }
+
+    // Scripts can be chained, later scripts see the changes made by earlier ones
+    let response = api
+        .request::<Value>(Method::POST, "/api/sieve/tag/junk")
+        .await
+        .unwrap()
+        .unwrap_data();
+    assert_eq!(
+        response,
+        json!({
+            "action": "accept",
+            "modifications": [
+                {"action": "addHeader", "name": "X-Spam", "value": "yes"}
+            ],
+            "actions": [
+                {"type": "fileInto", "mailbox": "Junk", "flags": []}
+            ]
+        })
+    );
+
+    // Chains stop as soon as a script rejects or discards the message
+    let response = api
+        .post_raw::<Value>(
+            "/api/sieve/message/actions",
+            "Subject: Your invoice\r\n\r\nPlease pay.\r\n",
+        )
+        .await
+        .unwrap()
+        .unwrap_data();
+    assert_eq!(response["action"], "reject", "{response}");
+    assert!(
+        response["actions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|action| action["type"] != "fileInto"),
+        "{response}"
+    );
+
+    // The current time can be overridden
+    let response = api
+        .request::<Value>(Method::POST, "/api/sieve/now?now=1700000000")
+        .await
+        .unwrap()
+        .unwrap_data();
+    assert_eq!(
+        response["modifications"],
+        json!([{"action": "addHeader", "name": "X-Now", "value": "1700000000"}])
+    );
+    match api
+        .request::<Value>(Method::POST, "/api/sieve/now?now=yesterday")
+        .await
+        .unwrap()
+    {
+        Response::RequestError(err) => assert_eq!(err.status, 400),
+        _ => panic!("Expected a bad request error"),
+    }
+
+    // Scripts exceeding the instruction budget are stopped
+    let response = api
+        .request::<Value>(Method::POST, "/api/sieve/spin?trace=1")
+        .await
+        .unwrap()
+        .unwrap_data();
+    assert_eq!(response["modifications"], json!([]), "{response}");
+    assert_eq!(
+        response["trace"].as_array().unwrap().last().unwrap()["type"],
+        "error",
+        "{response}"
+    );
+
+    // Unknown scripts and scripts the caller is not allowed to run are not found
+    for script in ["unknown", "restricted", "actions/restricted"] {
+        assert!(
+            api.request::<Value>(Method::POST, &format!("/api/sieve/{script}"))
+                .await
+                .unwrap()
+                .try_unwrap_data()
+                .is_none()
+        );
+    }
+}
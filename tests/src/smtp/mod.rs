@@ -5,9 +5,12 @@
  */
 
 use std::{
+    future::Future,
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
     path::PathBuf,
+    pin::Pin,
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use common::{
@@ -15,17 +18,18 @@ use common::{
     config::{
         server::{Listeners, ServerProtocol},
         smtp::resolver::Tlsa,
-        spamfilter::IpResolver,
+        spamfilter::{DnsBlLookup, DnsBlResult, DnsBlZoneLookup, IpResolver},
     },
     ipc::{QueueEvent, ReportingEvent},
     manager::boot::{IpcReceivers, build_ipc},
 };
 
+use ahash::AHashMap;
 use http::HttpSessionManager;
 use mail_auth::{MX, Txt, common::resolver::IntoFqdn};
 use session::{DummyIo, TestSession};
 use smtp::core::{Session, SmtpSessionManager};
-use store::{BlobStore, Store, Stores};
+use store::{BlobStore, Store, Stores, parking_lot::Mutex};
 use tokio::sync::{mpsc, watch};
 use utils::config::Config;
 
@@ -98,8 +102,60 @@ pub struct ReportReceiver {
     pub report_rx: mpsc::Receiver<ReportingEvent>,
 }
 
+/// Serves DNSBL queries without touching the network, zones not added
+/// with `MockDnsBl::add` are not listed and listed zones have the reason
+/// `Listed in <zone>`.
+#[derive(Default)]
+pub struct MockDnsBl {
+    zones: Mutex<AHashMap<String, Vec<Ipv4Addr>>>,
+}
+
+impl MockDnsBl {
+    pub fn add(&self, zone: &str, ips: Vec<Ipv4Addr>) {
+        self.zones.lock().insert(zone.to_string(), ips);
+    }
+
+    pub fn remove(&self, zone: &str) {
+        self.zones.lock().remove(zone);
+    }
+}
+
+impl DnsBlZoneLookup for MockDnsBl {
+    fn lookup_zone<'x>(
+        &'x self,
+        zone: &'x str,
+    ) -> Pin<Box<dyn Future<Output = mail_auth::Result<DnsBlLookup>> + Send + 'x>> {
+        let result = if let Some(ips) = self.zones.lock().get(zone) {
+            Ok(DnsBlLookup::Listed {
+                ips: ips.iter().copied().map(IpAddr::V4).collect(),
+                expires: Instant::now() + Duration::from_secs(100),
+            })
+        } else if zone.contains("_dns_error.") {
+            Err(mail_auth::Error::DnsError("".to_string()))
+        } else {
+            Ok(DnsBlLookup::NotListed { negative_ttl: None })
+        };
+
+        Box::pin(async move { result })
+    }
+
+    fn lookup_txt<'x>(
+        &'x self,
+        zone: &'x str,
+    ) -> Pin<Box<dyn Future<Output = mail_auth::Result<String>> + Send + 'x>> {
+        let result = if self.zones.lock().contains_key(zone) {
+            Ok(format!("Listed in {zone}"))
+        } else {
+            Err(mail_auth::Error::DnsError("".to_string()))
+        };
+
+        Box::pin(async move { result })
+    }
+}
+
 pub struct TestSMTP {
     pub server: Server,
+    pub dnsbl: Arc<MockDnsBl>,
     pub temp_dir: Option<TempDir>,
     pub queue_receiver: QueueReceiver,
     pub report_receiver: ReportReceiver,
@@ -187,13 +243,16 @@ impl TestSMTP {
         )
     }
 
-    fn from_core_and_tempdir(core: Core, data: Data, temp_dir: Option<TempDir>) -> Self {
+    fn from_core_and_tempdir(core: Core, mut data: Data, temp_dir: Option<TempDir>) -> Self {
         let store = core.storage.data.clone();
         let blob_store = core.storage.blob.clone();
         let shared_core = core.into_shared();
         let (ipc, mut ipc_rxs) = build_ipc(false);
+        let dnsbl = Arc::new(MockDnsBl::default());
+        data.dnsbl_lookup = Some(dnsbl.clone());
 
         TestSMTP {
+            dnsbl,
             queue_receiver: QueueReceiver {
                 store,
                 blob_store,
@@ -348,14 +407,17 @@ impl DnsCache for Server {
     fn dnsbl_add(&self, name: &str, value: Vec<Ipv4Addr>, valid_until: std::time::Instant) {
         self.inner.cache.dns_rbl.insert_with_expiry(
             name.to_string(),
-            Some(Arc::new(IpResolver::new(
-                value
-                    .iter()
-                    .copied()
-                    .next()
-                    .unwrap_or(Ipv4Addr::BROADCAST)
-                    .into(),
-            ))),
+            Some(Arc::new(DnsBlResult {
+                ips: if !value.is_empty() {
+                    value
+                        .into_iter()
+                        .map(|ip| IpResolver::new(ip.into()))
+                        .collect()
+                } else {
+                    vec![IpResolver::new(Ipv4Addr::BROADCAST.into())]
+                },
+                reason: None,
+            })),
             valid_until,
         );
     }
@@ -6,7 +6,7 @@
 
 use std::time::{Duration, Instant};
 
-use common::config::server::ServerProtocol;
+use common::{config::server::ServerProtocol, ipc::QueueStateEvent};
 use mail_auth::MX;
 use smtp_proto::{MAIL_REQUIRETLS, MAIL_RET_HDRS, MAIL_SMTPUTF8, RCPT_NOTIFY_NEVER};
 
@@ -76,6 +76,7 @@ async fn extensions() {
         Instant::now() + Duration::from_secs(10),
     );
 
+    let mut queue_state = core.subscribe_queue_state();
     let mut session = local.new_session();
     session.data.remote_ip_str = "10.0.0.1".into();
     session.eval_session_params().await;
@@ -104,6 +105,17 @@ async fn extensions() {
         .assert_contains("Final-Recipient: rfc822;bill@foobar.org")
         .assert_contains("Action: delivered");
     local.queue_receiver.read_event().await.assert_done();
+    let queue_id = match queue_state.try_recv().unwrap() {
+        QueueStateEvent::Queued { queue_id } => queue_id,
+        event => panic!("Unexpected queue state event: {event:?}"),
+    };
+    assert_eq!(
+        queue_state.try_recv().unwrap(),
+        QueueStateEvent::Delivered {
+            queue_id,
+            recipient: "bill@foobar.org".to_string()
+        }
+    );
     remote
         .queue_receiver
         .expect_message()
@@ -112,7 +124,7 @@ async fn extensions() {
         .await
         .assert_contains("using TLSv1.3 with cipher");
 
-    // Test SIZE extension
+    // Test SIZE extension, the advertised size is learned from the first delivery
     session
         .send_message("john@test.org", &["bill@foobar.org"], "test:arc", "250")
         .await;
@@ -127,13 +139,110 @@ async fn extensions() {
         .await
         .read_lines(&local.queue_receiver)
         .await
-        .assert_contains("<bill@foobar.org> (host 'mx.foobar.org' rejected command 'MAIL FROM:")
+        .assert_contains("<bill@foobar.org> (host 'mx.foobar.org' rejected transaction")
+        .assert_contains("exceeds the maximum of 1500 bytes")
         .assert_contains("Action: failed")
         .assert_contains("Diagnostic-Code: smtp;552")
         .assert_contains("Status: 5.3.4");
     local.queue_receiver.read_event().await.assert_done();
     remote.queue_receiver.assert_no_events();
 
+    // Without a cached size, oversized messages are rejected after EHLO
+    core.inner.cache.remote_max_size.clear();
+    session
+        .send_message("john@test.org", &["bill@foobar.org"], "test:arc", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    local
+        .queue_receiver
+        .expect_message()
+        .await
+        .read_lines(&local.queue_receiver)
+        .await
+        .assert_contains("<bill@foobar.org> (host 'mx.foobar.org' rejected transaction")
+        .assert_contains("Action: failed")
+        .assert_contains("Diagnostic-Code: smtp;552")
+        .assert_contains("Status: 5.3.4");
+    local.queue_receiver.read_event().await.assert_done();
+    remote.queue_receiver.assert_no_events();
+
+    // Hosts advertising a smaller size are skipped in favour of the next host
+    core.mx_add(
+        "foobar.org",
+        vec![
+            MX {
+                exchanges: vec!["mx.foobar.org".to_string()],
+                preference: 10,
+            },
+            MX {
+                exchanges: vec!["mx2.foobar.org".to_string()],
+                preference: 20,
+            },
+        ],
+        Instant::now() + Duration::from_secs(10),
+    );
+    core.ipv4_add(
+        "mx2.foobar.org",
+        vec!["127.0.0.1".parse().unwrap()],
+        Instant::now() + Duration::from_secs(10),
+    );
+    core.inner.cache.remote_max_size.insert(
+        "mx.foobar.org".to_string(),
+        100,
+        Duration::from_secs(60),
+    );
+    session
+        .send_message("john@test.org", &["bill@foobar.org"], "test:no_dkim", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    local.queue_receiver.read_event().await.assert_done();
+    remote
+        .queue_receiver
+        .expect_message()
+        .await
+        .read_lines(&remote.queue_receiver)
+        .await
+        .assert_contains("using TLSv1.3 with cipher");
+
+    // Messages fail permanently once every host advertised a smaller size
+    session
+        .send_message("john@test.org", &["bill@foobar.org"], "test:arc", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+    local
+        .queue_receiver
+        .expect_message()
+        .await
+        .read_lines(&local.queue_receiver)
+        .await
+        .assert_contains("<bill@foobar.org> (host 'mx2.foobar.org' rejected transaction")
+        .assert_contains("exceeds the maximum of 1500 bytes")
+        .assert_contains("Action: failed")
+        .assert_contains("Status: 5.3.4");
+    local.queue_receiver.read_event().await.assert_done();
+    remote.queue_receiver.assert_no_events();
+    core.inner.cache.remote_max_size.clear();
+    core.mx_add(
+        "foobar.org",
+        vec![MX {
+            exchanges: vec!["mx.foobar.org".to_string()],
+            preference: 10,
+        }],
+        Instant::now() + Duration::from_secs(10),
+    );
+
     // Test DSN, SMTPUTF8 and REQUIRETLS extensions
     session
         .send_message(
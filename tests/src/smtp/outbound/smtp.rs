@@ -19,7 +19,7 @@ use crate::smtp::{
     queue::QueuedEvents,
     session::{TestSession, VerifyResponse},
 };
-use smtp::queue::spool::{QUEUE_REFRESH, SmtpSpool};
+use smtp::queue::spool::SmtpSpool;
 
 const LOCAL: &str = r#"
 [session.rcpt]
@@ -165,7 +165,13 @@ async fn smtp_delivery() {
     loop {
         match local.queue_receiver.try_read_event().await {
             Some(QueueEvent::Refresh | QueueEvent::WorkerDone { .. }) => {}
-            Some(QueueEvent::Paused(_)) | Some(QueueEvent::ReloadSettings) => unreachable!(),
+            Some(QueueEvent::Paused(_))
+            | Some(QueueEvent::ReloadSettings)
+            | Some(QueueEvent::FlushOnHold { .. })
+            | Some(QueueEvent::LimiterStats(_))
+            | Some(QueueEvent::Drain { .. })
+            | Some(QueueEvent::HoldUntil { .. })
+            | Some(QueueEvent::Retry(_)) => unreachable!(),
             None | Some(QueueEvent::Stop) => {
                 break;
             }
@@ -174,7 +180,7 @@ async fn smtp_delivery() {
         let mut events = core.all_queued_messages().await;
         if events.messages.is_empty() {
             let now = now();
-            if events.next_refresh < now + QUEUE_REFRESH {
+            if events.next_refresh < now + core.core.smtp.queue.max_idle.as_secs() {
                 tokio::time::sleep(Duration::from_secs(events.next_refresh - now)).await;
                 events = core.all_queued_messages().await;
             } else {
@@ -6,14 +6,18 @@
 
 use std::time::{Duration, Instant};
 
-use common::{config::server::ServerProtocol, core::BuildServer, ipc::QueueEvent};
+use common::{
+    config::{server::ServerProtocol, smtp::queue::QueueName},
+    core::BuildServer,
+    ipc::{QueueEvent, QueueLimiterStats},
+};
 use mail_auth::MX;
 
 use crate::{
     smtp::{DnsCache, TestSMTP, session::TestSession},
     store::cleanup::store_assert_is_empty,
 };
-use smtp::queue::manager::Queue;
+use smtp::queue::manager::{Queue, QueueStats};
 
 const LOCAL: &str = r#"
 [spam-filter]
@@ -159,3 +163,41 @@ async fn concurrent_queue() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn queue_limiter_stats() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_queue_limiter_stats", LOCAL).await;
+    let (inner, rxs) = local.inner_with_rxs();
+    let mut queue = Queue::new(inner, rxs.queue_rx.unwrap());
+    assert!(queue.limiter_stats().is_empty());
+
+    // Saturated queues are reported first
+    for (name, in_flight, max_in_flight) in [("local", 1, 4), ("remote", 4, 4)] {
+        queue.stats.insert(
+            QueueName::new(name).unwrap(),
+            QueueStats {
+                in_flight,
+                max_in_flight,
+                last_warning: Instant::now(),
+            },
+        );
+    }
+    assert_eq!(
+        queue.limiter_stats(),
+        vec![
+            QueueLimiterStats {
+                queue_name: "remote".to_string(),
+                in_flight: 4,
+                max_in_flight: 4,
+            },
+            QueueLimiterStats {
+                queue_name: "local".to_string(),
+                in_flight: 1,
+                max_in_flight: 4,
+            }
+        ]
+    );
+}
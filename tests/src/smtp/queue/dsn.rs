@@ -4,8 +4,16 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use crate::smtp::{QueueReceiver, TestSMTP, inbound::sign::SIGNATURES};
-use common::config::smtp::queue::{QueueExpiry, QueueName};
+use crate::smtp::{
+    QueueReceiver, TestSMTP,
+    inbound::{TestMessage, sign::SIGNATURES},
+};
+use common::{
+    Server,
+    config::smtp::queue::{
+        DsnRecipient, DsnStatus, DsnText, DsnTextBuilder, QueueExpiry, QueueName,
+    },
+};
 use smtp::queue::{
     Error, ErrorDetails, HostResponse, Message, MessageWrapper, Recipient, Schedule, Status,
     UnexpectedResponse, dsn::SendDsn,
@@ -13,8 +21,10 @@ use smtp::queue::{
 use smtp_proto::{RCPT_NOTIFY_DELAY, RCPT_NOTIFY_FAILURE, RCPT_NOTIFY_SUCCESS, Response};
 use std::{
     fs,
+    future::Future,
     net::{IpAddr, Ipv4Addr},
     path::PathBuf,
+    pin::Pin,
     time::SystemTime,
 };
 use store::write::now;
@@ -171,6 +181,153 @@ async fn generate_dsn() {
     assert_eq!(queue.len(), 4);
 }
 
+#[tokio::test]
+async fn dsn_delay_not_requested() {
+    // Enable logging
+    crate::enable_logging();
+
+    let mut local = TestSMTP::new(
+        "smtp_dsn_delay_not_requested_test",
+        r#"
+[queue.schedule.delayed]
+retry = ["1h"]
+notify = ["1s", "1h"]
+expire = "1d"
+queue-name = "default"
+
+[queue.strategy]
+schedule = "'delayed'"
+"#,
+    )
+    .await;
+    let server = local.build_smtp();
+    let qr = &mut local.queue_receiver;
+
+    // Due delay notifications are rescheduled even when the recipient did not request them
+    let mut message = MessageWrapper {
+        queue_id: 0,
+        span_id: 0,
+        is_multi_queue: false,
+        queue_name: QueueName::default(),
+        message: test_message(
+            "sender@foobar.org",
+            vec![Recipient {
+                address: "jane@example.org".into(),
+                status: Status::TemporaryFailure(ErrorDetails {
+                    entity: "mx.example.org".into(),
+                    details: Error::ConnectionError("Connection timeout".into()),
+                }),
+                flags: RCPT_NOTIFY_FAILURE,
+                orcpt: None,
+                retry: Schedule::later(3600),
+                notify: Schedule::now(),
+                expires: QueueExpiry::Ttl(86400),
+                queue: QueueName::default(),
+            }],
+        ),
+    };
+    server.send_dsn(&mut message).await;
+    qr.assert_no_events();
+
+    let notify = &message.message.recipients[0].notify;
+    assert_eq!(notify.inner, 1);
+    assert!([3599, 3600].contains(&(notify.due - now())));
+}
+
+struct BrandedDsnText;
+
+impl DsnTextBuilder for BrandedDsnText {
+    fn build<'x>(
+        &'x self,
+        _server: &'x Server,
+        return_path: &'x str,
+        recipients: &'x [DsnRecipient<'x>],
+    ) -> Pin<Box<dyn Future<Output = DsnText> + Send + 'x>> {
+        Box::pin(async move {
+            let mut body = format!("Dear {return_path}, Acme Hosting could not deliver:\r\n");
+            for rcpt in recipients {
+                if rcpt.status == DsnStatus::Failed {
+                    body.push_str(&format!("  * {}\r\n", rcpt.address));
+                }
+            }
+            body.push_str("Contact https://support.acme.example\r\n");
+
+            DsnText {
+                subject: "Acme Hosting: undeliverable message".to_string(),
+                body,
+            }
+        })
+    }
+}
+
+#[tokio::test]
+async fn dsn_text_custom_builder() {
+    // Enable logging
+    crate::enable_logging();
+
+    let mut local = TestSMTP::new(
+        "smtp_dsn_custom_builder_test",
+        CONFIG.to_string() + SIGNATURES,
+    )
+    .await;
+    let core = local.build_smtp();
+    let qr = &mut local.queue_receiver;
+    core.set_dsn_text_builder(BrandedDsnText);
+
+    let mut message = MessageWrapper {
+        queue_id: 0,
+        span_id: 0,
+        is_multi_queue: false,
+        queue_name: QueueName::default(),
+        message: test_message("sender@foobar.org", vec![failed_recipient()]),
+    };
+    message.message.recipients[0].flags = RCPT_NOTIFY_FAILURE;
+    core.send_dsn(&mut message).await;
+
+    qr.expect_message()
+        .await
+        .read_lines(qr)
+        .await
+        .assert_contains("Subject: Acme Hosting: undeliverable message")
+        .assert_contains("Dear sender@foobar.org, Acme Hosting could not deliver:")
+        .assert_contains("  * foobar@example.org")
+        .assert_contains("Contact https://support.acme.example")
+        .assert_contains("Final-Recipient: rfc822;foobar@example.org")
+        .assert_contains("Action: failed");
+}
+
+fn failed_recipient() -> Recipient {
+    Recipient {
+        address: "foobar@example.org".into(),
+        status: Status::PermanentFailure(ErrorDetails {
+            entity: "example.org".into(),
+            details: Error::DnsError("Domain not found".into()),
+        }),
+        flags: 0,
+        orcpt: None,
+        retry: Schedule::now(),
+        notify: Schedule::now(),
+        expires: QueueExpiry::Ttl(10),
+        queue: QueueName::default(),
+    }
+}
+
+fn test_message(return_path: &str, recipients: Vec<Recipient>) -> Message {
+    Message {
+        size: 0,
+        created: now(),
+        return_path: return_path.into(),
+        recipients,
+        flags: 0,
+        env_id: None,
+        priority: 0,
+        blob_hash: BlobHash::default(),
+        quota_keys: Default::default(),
+        received_from_ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        received_via_port: 0,
+    }
+}
+
 impl QueueReceiver {
     async fn compare_dsn(&self, message: Message, test: &str) {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -0,0 +1,107 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::{Duration, Instant};
+
+use common::config::server::ServerProtocol;
+use mail_auth::MX;
+use smtp::queue::RCPT_FALLBACK_ROUTE;
+use store::write::now;
+
+use crate::smtp::{DnsCache, TestSMTP, inbound::TestQueueEvent, session::TestSession};
+
+const LOCAL: &str = r#"
+[session.rcpt]
+relay = true
+
+[queue.fallback]
+route = "fallback"
+attempts = 2
+
+[queue.route.fallback]
+type = "relay"
+address = fallback.foobar.org
+port = 9925
+protocol = 'smtp'
+concurrency = 5
+
+[queue.route.fallback.tls]
+implicit = false
+allow-invalid-certs = true
+
+"#;
+
+const REMOTE: &str = r#"
+[session.rcpt]
+relay = true
+
+[session.ehlo]
+reject-non-fqdn = false
+"#;
+
+#[tokio::test]
+#[serial_test::serial]
+async fn queue_fallback_route() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Start test server
+    let mut remote = TestSMTP::new("smtp_queue_fallback_remote", REMOTE).await;
+    let _rx = remote.start(&[ServerProtocol::Smtp]).await;
+    let mut local = TestSMTP::new("smtp_queue_fallback_local", LOCAL).await;
+
+    // The primary route always fails temporarily
+    let core = local.build_smtp();
+    core.mx_add(
+        "foobar.org",
+        vec![MX {
+            exchanges: vec!["_dns_error.foobar.org".to_string()],
+            preference: 10,
+        }],
+        Instant::now() + Duration::from_secs(10),
+    );
+    core.ipv4_add(
+        "fallback.foobar.org",
+        vec!["127.0.0.1".parse().unwrap()],
+        Instant::now() + Duration::from_secs(10),
+    );
+
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.test.org").await;
+    session
+        .send_message("john@test.org", &["bill@foobar.org"], "test:no_dkim", "250")
+        .await;
+    local
+        .queue_receiver
+        .expect_message_then_deliver()
+        .await
+        .try_deliver(core.clone());
+
+    // Retries use the primary route until the fallback attempts are reached
+    for attempt in 1..=2 {
+        let mut retry = local.queue_receiver.expect_message().await;
+        let rcpt = &retry.message.recipients[0];
+        assert_eq!(rcpt.retry.inner, attempt);
+        assert_eq!(rcpt.flags & RCPT_FALLBACK_ROUTE, 0);
+        remote.queue_receiver.assert_no_events();
+
+        let prev_due = rcpt.retry.due;
+        let queue_id = retry.queue_id;
+        retry.message.recipients[0].retry.due = now();
+        retry.save_changes(&core, prev_due.into()).await;
+        local
+            .queue_receiver
+            .delivery_attempt(queue_id)
+            .await
+            .try_deliver(core.clone());
+    }
+
+    // The third attempt is routed through the fallback relay
+    local.queue_receiver.read_event().await.assert_done();
+    remote.queue_receiver.expect_message().await;
+}
@@ -5,18 +5,30 @@
  */
 
 use crate::smtp::{
-    TestSMTP,
+    DnsCache, TestSMTP,
     queue::{QueuedEvents, build_rcpt},
+    session::TestSession,
 };
-use common::config::smtp::queue::QueueName;
+use common::{
+    config::{
+        server::ServerProtocol,
+        smtp::queue::{QueueExpiry, QueueName},
+    },
+    ipc::QueueEvent,
+};
+use mail_auth::MX;
 use smtp::queue::{
-    Error, ErrorDetails, Message, MessageWrapper, Recipient, Status, spool::SmtpSpool,
+    Error, ErrorDetails, Message, MessageWrapper, Recipient, Status,
+    manager::{NextEvent, Queue, QueueStats, SpawnQueue},
+    spool::{QueueForecast, RecipientSummary, SmtpSpool},
 };
 use std::{
     net::{IpAddr, Ipv4Addr},
-    time::Duration,
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
 };
 use store::write::now;
+use tokio::sync::{mpsc, oneshot};
 
 const CONFIG: &str = r#"
 [session.ehlo]
@@ -26,6 +38,52 @@ reject-non-fqdn = false
 relay = true
 "#;
 
+const IDLE_CONFIG: &str = r#"
+[session.rcpt]
+relay = true
+
+[spam-filter]
+enable = false
+
+[queue.limits]
+max-idle = "1h"
+"#;
+
+const IN_MEMORY_CONFIG: &str = r#"
+[session.rcpt]
+relay = true
+
+[spam-filter]
+enable = false
+
+[queue.limits]
+max-in-memory = 2
+"#;
+
+const TENANT_CONFIG: &str = r#"
+[queue.limits]
+max-per-tenant = 2
+"#;
+
+const DRAIN_CONFIG: &str = r#"
+[session.rcpt]
+relay = true
+
+[spam-filter]
+enable = false
+
+[queue.shutdown]
+drain-timeout = "5s"
+"#;
+
+const HEALTH_CONFIG: &str = r#"
+[session.rcpt]
+relay = true
+
+[spam-filter]
+enable = false
+"#;
+
 #[tokio::test]
 async fn queue_due() {
     // Enable logging
@@ -72,6 +130,590 @@ async fn queue_due() {
     qr.assert_queue_is_empty().await;
 }
 
+#[tokio::test]
+async fn queue_status() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_queue_status_test", CONFIG).await;
+    let core = local.build_smtp();
+
+    let mut message = new_message(10);
+    message.message.recipients.push(build_rcpt("a", 1, 4, 5));
+    message.message.recipients.push(build_rcpt("b", 2, 6, 7));
+    message.message.rcpt_mut("b").status = Status::TemporaryFailure(ErrorDetails {
+        entity: "localhost".into(),
+        details: Error::ConcurrencyLimited,
+    });
+    let next_event = message.message.next_event(None).unwrap().due();
+    message.save_changes(&core, 0.into()).await;
+
+    let mut message = new_message(20);
+    message.message.recipients.push(build_rcpt("c", 1, 4, 5));
+    message.message.rcpt_mut("c").status = Status::PermanentFailure(ErrorDetails {
+        entity: "localhost".into(),
+        details: Error::ConcurrencyLimited,
+    });
+    message.save_changes(&core, 0.into()).await;
+
+    let mut message = new_message(30);
+    message.message.recipients.push(build_rcpt("d", 1, 4, 5));
+    message.save_changes(&core, 0.into()).await;
+
+    // Unknown and duplicate ids are ignored
+    let status = core.queue_status(&[20, 10, 15, 20]).await.unwrap();
+    assert_eq!(status.len(), 2);
+    assert_eq!(status[0].queue_id, 10);
+    assert_eq!(status[0].status, Status::TemporaryFailure(()));
+    assert_eq!(status[0].next_event, Some(next_event));
+    assert_eq!(
+        status[0].recipients,
+        RecipientSummary {
+            scheduled: 1,
+            temporary_failure: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(status[1].queue_id, 20);
+    assert_eq!(status[1].status, Status::PermanentFailure(()));
+    assert_eq!(status[1].next_event, None);
+    assert_eq!(status[1].recipients.permanent_failure, 1);
+    assert!(core.queue_status(&[]).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn queue_hold_until() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_queue_hold_test", CONFIG).await;
+    let core = local.build_smtp();
+
+    let mut message = new_message(0);
+    message
+        .message
+        .recipients
+        .push(build_rcpt("a", 0, 1, 86400));
+    message.save_changes(&core, 0.into()).await;
+
+    // Held recipients are deferred until the release time
+    let until = now() + 3600;
+    assert!(core.hold_until(0, until).await);
+    assert!(!core.hold_until(1, until).await);
+    let message = core.read_message(0, QueueName::default()).await.unwrap();
+    let rcpt = message.message.rcpt("a");
+    assert_eq!(rcpt.retry.due, until);
+    assert_eq!(rcpt.notify.due, until);
+    assert!(rcpt.is_on_hold());
+    assert_eq!(
+        core.queue_status(&[0]).await.unwrap()[0].next_event,
+        Some(until)
+    );
+
+    // Flushing on hold messages leaves held recipients until their release time
+    assert_eq!(core.flush_on_hold(None).await, 0);
+    let message = core.read_message(0, QueueName::default()).await.unwrap();
+    assert_eq!(message.message.rcpt("a").retry.due, until);
+    assert!(message.message.rcpt("a").is_on_hold());
+}
+
+#[tokio::test]
+async fn queue_retry_now() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_queue_retry_now_test", CONFIG).await;
+    let core = local.build_smtp();
+
+    // Held by an operator
+    let mut message = new_message(0);
+    message
+        .message
+        .recipients
+        .push(build_rcpt("a", 0, 1, 86400));
+    message.save_changes(&core, 0.into()).await;
+    assert!(core.hold_until(0, now() + 3600).await);
+
+    // Scheduled for a later retry
+    let mut message = new_message(1);
+    message
+        .message
+        .recipients
+        .push(build_rcpt("b", 3600, 7200, 86400));
+    message.save_changes(&core, 1.into()).await;
+
+    // Both are due immediately and held recipients are released
+    for (queue_id, rcpt) in [(0, "a"), (1, "b")] {
+        assert!(core.retry_now(queue_id).await);
+        let message = core
+            .read_message(queue_id, QueueName::default())
+            .await
+            .unwrap();
+        assert!(message.message.rcpt(rcpt).retry.due <= now());
+        assert!(!message.message.rcpt(rcpt).is_on_hold());
+    }
+    assert_eq!(core.flush_on_hold(None).await, 0);
+    assert!(!core.retry_now(2).await);
+}
+
+#[tokio::test]
+async fn queue_flush_on_hold_domain() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_queue_flush_test", CONFIG).await;
+    let core = local.build_smtp();
+    let until = now() + 3600;
+
+    // Held back by a concurrency limiter
+    let mut message = new_message(0);
+    let mut rcpt = build_rcpt("a@foobar.org", 3600, 7200, 86400);
+    rcpt.status = Status::TemporaryFailure(ErrorDetails {
+        entity: "localhost".into(),
+        details: Error::ConcurrencyLimited,
+    });
+    message.message.recipients.push(rcpt);
+    message.save_changes(&core, 0.into()).await;
+
+    // Held back by a rate limiter
+    let mut message = new_message(1);
+    let mut rcpt = build_rcpt("b@example.org", 3600, 7200, 86400);
+    rcpt.status = Status::TemporaryFailure(ErrorDetails {
+        entity: "localhost".into(),
+        details: Error::RateLimited,
+    });
+    message.message.recipients.push(rcpt);
+    message.save_changes(&core, 1.into()).await;
+
+    // Scheduled for a later retry but not on hold
+    let mut message = new_message(2);
+    message
+        .message
+        .recipients
+        .push(build_rcpt("c@foobar.org", 3600, 7200, 86400));
+    message.save_changes(&core, 2.into()).await;
+
+    // Held by an operator
+    let mut message = new_message(3);
+    message
+        .message
+        .recipients
+        .push(build_rcpt("d@foobar.org", 0, 1, 86400));
+    message.save_changes(&core, 3.into()).await;
+    assert!(core.hold_until(3, until).await);
+
+    // Only limited recipients of the matching domain are released
+    assert_eq!(core.flush_on_hold(Some("FOOBAR.org")).await, 1);
+    let message = core.read_message(0, QueueName::default()).await.unwrap();
+    assert!(message.message.rcpt("a@foobar.org").retry.due <= now());
+    let message = core.read_message(1, QueueName::default()).await.unwrap();
+    assert!(message.message.rcpt("b@example.org").retry.due > now());
+    let message = core.read_message(2, QueueName::default()).await.unwrap();
+    assert!(message.message.rcpt("c@foobar.org").retry.due > now());
+
+    // Flushing without a filter releases the remaining limited recipients
+    assert_eq!(core.flush_on_hold(Some("foobar.org")).await, 0);
+    assert_eq!(core.flush_on_hold(None).await, 1);
+    let message = core.read_message(1, QueueName::default()).await.unwrap();
+    assert!(message.message.rcpt("b@example.org").retry.due <= now());
+    let message = core.read_message(2, QueueName::default()).await.unwrap();
+    assert!(message.message.rcpt("c@foobar.org").retry.due > now());
+
+    // Recipients held by an operator are never flushed
+    let message = core.read_message(3, QueueName::default()).await.unwrap();
+    assert_eq!(message.message.rcpt("d@foobar.org").retry.due, until);
+    assert!(message.message.rcpt("d@foobar.org").is_on_hold());
+}
+
+#[tokio::test]
+async fn queue_forecast() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_queue_forecast_test", CONFIG).await;
+    let core = local.build_smtp();
+    assert_eq!(
+        core.queue_forecast().await.unwrap(),
+        QueueForecast::default()
+    );
+
+    for (queue_id, retry) in [0, 30, 40, 1800, 43200, 200000].into_iter().enumerate() {
+        let mut message = new_message(queue_id as u64);
+        message.message.recipients.push(build_rcpt(
+            "a@foobar.org",
+            retry,
+            retry + 300000,
+            retry + 600000,
+        ));
+        message.save_changes(&core, 0.into()).await;
+    }
+
+    // Messages are bucketed by their next due event
+    assert_eq!(
+        core.queue_forecast().await.unwrap(),
+        QueueForecast {
+            overdue: 1,
+            next_minute: 2,
+            next_hour: 1,
+            next_day: 1,
+            later: 1,
+        }
+    );
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn queue_idle_wakeup() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Start remote test server
+    let remote = TestSMTP::new("smtp_queue_idle_remote", CONFIG).await;
+    let _rx = remote.start(&[ServerProtocol::Smtp]).await;
+
+    // Use an idle cap far longer than the test
+    let local = TestSMTP::new("smtp_queue_idle_local", IDLE_CONFIG).await;
+    let core = local.build_smtp();
+    core.mx_add(
+        "foobar.org",
+        vec![MX {
+            exchanges: vec!["mx.foobar.org".to_string()],
+            preference: 10,
+        }],
+        Instant::now() + Duration::from_secs(100),
+    );
+    core.ipv4_add(
+        "mx.foobar.org",
+        vec!["127.0.0.1".parse().unwrap()],
+        Instant::now() + Duration::from_secs(100),
+    );
+
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.test.org").await;
+    local
+        .queue_receiver
+        .queue_rx
+        .spawn(local.server.inner.clone());
+
+    // Wait until the queue manager is idle
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(
+        core.inner
+            .data
+            .queue_health
+            .next_refresh
+            .load(Ordering::Relaxed)
+            >= now() + 3000,
+        "queue manager is not idle"
+    );
+
+    // A newly queued message should interrupt the idle wait
+    session
+        .send_message("john@test.org", &["bill@foobar.org"], "test:no_dkim", "250")
+        .await;
+    let started = Instant::now();
+    while remote
+        .queue_receiver
+        .read_queued_messages()
+        .await
+        .is_empty()
+    {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "message was not delivered while the queue was idle"
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn queue_max_in_memory() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Start remote test server
+    let remote = TestSMTP::new("smtp_queue_in_memory_remote", CONFIG).await;
+    let _rx = remote.start(&[ServerProtocol::Smtp]).await;
+
+    let local = TestSMTP::new("smtp_queue_in_memory_local", IN_MEMORY_CONFIG).await;
+    let core = local.build_smtp();
+    for queue_id in 0..3 {
+        let mut message = new_message(queue_id);
+        message
+            .message
+            .recipients
+            .push(build_rcpt("a@foobar.org", 0, 3600, 86400));
+        message.save_changes(&core, 0.into()).await;
+    }
+
+    // Events over the limit are left in the spool without scheduling a refresh
+    let mut queue = Queue::new(core.inner.clone(), mpsc::channel(1).1);
+    let events = core.next_event(&mut queue).await;
+    assert_eq!(events.messages.len(), 2);
+    assert!(events.has_backlog);
+    assert!(events.next_refresh > now() + 60);
+
+    // Messages being delivered count towards the limit
+    queue.stats.insert(
+        QueueName::default(),
+        QueueStats {
+            in_flight: 2,
+            max_in_flight: 10,
+            last_warning: Instant::now(),
+        },
+    );
+    let events = core.next_event(&mut queue).await;
+    assert!(events.messages.is_empty());
+    assert!(events.has_backlog);
+
+    // The remaining event is read once a delivery completes
+    queue
+        .stats
+        .get_mut(&QueueName::default())
+        .unwrap()
+        .in_flight = 1;
+    let events = core.next_event(&mut queue).await;
+    assert_eq!(events.messages.len(), 1);
+    assert!(!events.has_backlog);
+
+    // Completed deliveries wake up the queue manager to process the backlog
+    let local = TestSMTP::new("smtp_queue_in_memory_spawn", IN_MEMORY_CONFIG).await;
+    let core = local.build_smtp();
+    core.mx_add(
+        "foobar.org",
+        vec![MX {
+            exchanges: vec!["mx.foobar.org".to_string()],
+            preference: 10,
+        }],
+        Instant::now() + Duration::from_secs(100),
+    );
+    core.ipv4_add(
+        "mx.foobar.org",
+        vec!["127.0.0.1".parse().unwrap()],
+        Instant::now() + Duration::from_secs(100),
+    );
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.test.org").await;
+    for _ in 0..5 {
+        session
+            .send_message("john@test.org", &["bill@foobar.org"], "test:no_dkim", "250")
+            .await;
+    }
+    local
+        .queue_receiver
+        .queue_rx
+        .spawn(local.server.inner.clone());
+    let started = Instant::now();
+    while remote.queue_receiver.read_queued_messages().await.len() < 5 {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "backlog was not delivered"
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test]
+async fn queue_max_per_tenant() {
+    // Enable logging
+    crate::enable_logging();
+
+    let local = TestSMTP::new("smtp_queue_tenant_test", TENANT_CONFIG).await;
+    let core = local.build_smtp();
+    let return_paths = [
+        "",
+        "",
+        "",
+        "a@bulk.org",
+        "b@bulk.org",
+        "c@bulk.org",
+        "d@other.org",
+    ];
+    for (queue_id, return_path) in return_paths.into_iter().enumerate() {
+        let mut message = new_message(queue_id as u64);
+        message.message.return_path = return_path.into();
+        message
+            .message
+            .recipients
+            .push(build_rcpt("a@foobar.org", 0, 3600, 86400));
+        message.save_changes(&core, 0.into()).await;
+    }
+
+    // Null sender messages are not capped as a single tenant
+    let mut queue = Queue::new(core.inner.clone(), mpsc::channel(1).1);
+    let events = core.next_event(&mut queue).await;
+    let mut queue_ids = events
+        .messages
+        .iter()
+        .map(|event| event.queue_id)
+        .collect::<Vec<_>>();
+    assert_eq!(queue_ids.len(), 6);
+    assert!(events.has_backlog);
+    assert!(events.next_refresh > now() + 60);
+
+    // One event per tenant is scheduled before the second event of a tenant
+    assert_eq!(
+        queue_ids[..5]
+            .iter()
+            .filter(|queue_id| (3..=5).contains(*queue_id))
+            .count(),
+        1
+    );
+    queue_ids.sort_unstable();
+    assert_eq!(queue_ids[..3], [0, 1, 2]);
+    assert_eq!(queue_ids[5], 6);
+
+    // Events left over the cap are read on the next cycle
+    let events = core.next_event(&mut queue).await;
+    assert_eq!(events.messages.len(), 1);
+    assert!((3..=5).contains(&events.messages[0].queue_id));
+    assert!(!events.has_backlog);
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn queue_drain() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Start remote test server
+    let remote = TestSMTP::new("smtp_queue_drain_remote", CONFIG).await;
+    let _rx = remote.start(&[ServerProtocol::Smtp]).await;
+
+    let local = TestSMTP::new("smtp_queue_drain_local", DRAIN_CONFIG).await;
+    let core = local.build_smtp();
+    core.mx_add(
+        "foobar.org",
+        vec![MX {
+            exchanges: vec!["mx.foobar.org".to_string()],
+            preference: 10,
+        }],
+        Instant::now() + Duration::from_secs(100),
+    );
+    core.ipv4_add(
+        "mx.foobar.org",
+        vec!["127.0.0.1".parse().unwrap()],
+        Instant::now() + Duration::from_secs(100),
+    );
+
+    // Queue some messages before the queue manager starts
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.test.org").await;
+    for _ in 0..3 {
+        session
+            .send_message("john@test.org", &["bill@foobar.org"], "test:no_dkim", "250")
+            .await;
+    }
+
+    // Due messages are delivered before the drain completes
+    let (tx, rx) = oneshot::channel();
+    local
+        .server
+        .inner
+        .ipc
+        .queue_tx
+        .send(QueueEvent::Drain {
+            deadline: Instant::now() + Duration::from_secs(5),
+            tx,
+        })
+        .await
+        .unwrap();
+    let started = Instant::now();
+    local
+        .queue_receiver
+        .queue_rx
+        .spawn(local.server.inner.clone());
+    rx.await.expect("queue manager did not signal the drain");
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(remote.queue_receiver.read_queued_messages().await.len(), 3);
+
+    // The queue manager exits once drained
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(local.server.inner.ipc.queue_tx.is_closed());
+}
+
+#[tokio::test]
+#[serial_test::serial]
+async fn queue_health() {
+    // Enable logging
+    crate::enable_logging();
+
+    // Start remote test server
+    let remote = TestSMTP::new("smtp_queue_health_remote", CONFIG).await;
+    let _rx = remote.start(&[ServerProtocol::Smtp]).await;
+
+    let local = TestSMTP::new("smtp_queue_health_local", HEALTH_CONFIG).await;
+    let core = local.build_smtp();
+    core.mx_add(
+        "foobar.org",
+        vec![MX {
+            exchanges: vec!["mx.foobar.org".to_string()],
+            preference: 10,
+        }],
+        Instant::now() + Duration::from_secs(100),
+    );
+    core.ipv4_add(
+        "mx.foobar.org",
+        vec!["127.0.0.1".parse().unwrap()],
+        Instant::now() + Duration::from_secs(100),
+    );
+
+    // Queue some messages before the queue manager starts
+    let mut session = local.new_session();
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.test.org").await;
+    for _ in 0..3 {
+        session
+            .send_message("john@test.org", &["bill@foobar.org"], "test:no_dkim", "250")
+            .await;
+    }
+    let health = &core.inner.data.queue_health;
+    assert_eq!(health.last_processed.load(Ordering::Relaxed), 0);
+    assert_eq!(health.next_refresh.load(Ordering::Relaxed), 0);
+
+    // Processing due messages is reported
+    let started = now();
+    local
+        .queue_receiver
+        .queue_rx
+        .spawn(local.server.inner.clone());
+    let wait = Instant::now();
+    while remote.queue_receiver.read_queued_messages().await.len() < 3 {
+        assert!(
+            wait.elapsed() < Duration::from_secs(5),
+            "messages were not delivered"
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(health.last_processed.load(Ordering::Relaxed) >= started);
+    assert!(health.next_refresh.load(Ordering::Relaxed) > now());
+    assert_eq!(health.due_messages.load(Ordering::Relaxed), 0);
+    assert_eq!(health.on_hold.load(Ordering::Relaxed), 0);
+    assert_eq!(health.locked.load(Ordering::Relaxed), 0);
+
+    // Pausing the queue postpones the next refresh
+    local
+        .server
+        .inner
+        .ipc
+        .queue_tx
+        .send(QueueEvent::Paused(true))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!core.inner.data.queue_status.load(Ordering::Relaxed));
+    assert!(health.next_refresh.load(Ordering::Relaxed) >= now() + 86000);
+}
+
 #[test]
 fn delivery_events() {
     let mut message = new_message(0).message;
@@ -83,7 +725,7 @@ fn delivery_events() {
 
     for t in 0..2 {
         assert_eq!(
-            message.next_event(None).unwrap(),
+            message.next_event(None).unwrap().due(),
             message.rcpt("a").retry.due
         );
         assert_eq!(
@@ -133,7 +775,7 @@ fn delivery_events() {
         details: Error::ConcurrencyLimited,
     });
     assert_eq!(
-        message.next_event(None).unwrap(),
+        message.next_event(None).unwrap().due(),
         message.rcpt("b").retry.due
     );
     assert_eq!(
@@ -146,7 +788,7 @@ fn delivery_events() {
         details: Error::ConcurrencyLimited,
     });
     assert_eq!(
-        message.next_event(None).unwrap(),
+        message.next_event(None).unwrap().due(),
         message.rcpt("c").retry.due
     );
     assert_eq!(
@@ -161,6 +803,90 @@ fn delivery_events() {
     assert!(message.next_event(None).is_none());
 }
 
+#[test]
+fn dedup_recipients() {
+    let mut message = new_message(0).message;
+
+    let mut rcpt = build_rcpt("john@example.org", 5, 2, 9);
+    rcpt.flags = 1;
+    message.recipients.push(rcpt);
+    message
+        .recipients
+        .push(build_rcpt("jane@example.org", 1, 2, 3));
+    let mut rcpt = build_rcpt("John@Example.org", 3, 4, 6);
+    rcpt.flags = 2;
+    message.recipients.push(rcpt);
+    let mut rcpt = build_rcpt("john@example.org", 1, 1, 1);
+    rcpt.status = Status::PermanentFailure(ErrorDetails {
+        entity: "localhost".into(),
+        details: Error::ConcurrencyLimited,
+    });
+    message.recipients.push(rcpt);
+
+    let expected_retry = message.recipients[2].retry.due;
+    let expected_notify = message.recipients[0].notify.due;
+
+    message.dedup_recipients();
+
+    assert_eq!(message.recipients.len(), 3);
+    let rcpt = &message.recipients[0];
+    assert_eq!(rcpt.address(), "john@example.org");
+    assert_eq!(rcpt.retry.due, expected_retry);
+    assert_eq!(rcpt.notify.due, expected_notify);
+    assert_eq!(rcpt.expires, QueueExpiry::Ttl(6));
+    assert_eq!(rcpt.flags, 3);
+    assert_eq!(message.recipients[1].address(), "jane@example.org");
+    assert!(matches!(
+        message.recipients[2].status,
+        Status::PermanentFailure(_)
+    ));
+}
+
+#[test]
+fn next_event_reason() {
+    let mut message = new_message(0).message;
+    message.created = now();
+
+    // Delay notification due before the next retry
+    message.recipients.push(build_rcpt("a", 10, 2, 20));
+    assert_eq!(
+        message.next_event(None),
+        Some(NextEvent::Notify(message.rcpt("a").notify.due))
+    );
+
+    // Retries take precedence when due at the same time
+    message.recipients.push(build_rcpt("b", 2, 5, 20));
+    message.rcpt_mut("b").retry.due = message.rcpt("a").notify.due;
+    assert_eq!(
+        message.next_event(None),
+        Some(NextEvent::Retry(message.rcpt("b").retry.due))
+    );
+
+    // Notifications are still scheduled once retries stopped for other recipients
+    message.rcpt_mut("b").status = Status::PermanentFailure(ErrorDetails {
+        entity: "localhost".into(),
+        details: Error::ConcurrencyLimited,
+    });
+    assert_eq!(
+        message.next_event(None),
+        Some(NextEvent::Notify(message.rcpt("a").notify.due))
+    );
+    assert_eq!(
+        message.next_delivery_event(None),
+        Some(message.rcpt("a").retry.due)
+    );
+
+    // Expiration before any retry or notification
+    message.rcpt_mut("a").notify.due = u64::MAX;
+    message.rcpt_mut("a").expires = QueueExpiry::Ttl(5);
+    assert_eq!(
+        message.next_event(None),
+        Some(NextEvent::Expire(
+            message.rcpt("a").expiration_time(message.created).unwrap()
+        ))
+    );
+}
+
 pub fn new_message(queue_id: u64) -> MessageWrapper {
     MessageWrapper {
         queue_id,
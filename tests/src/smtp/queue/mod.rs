@@ -17,6 +17,7 @@ use tokio::sync::mpsc;
 
 pub mod concurrent;
 pub mod dsn;
+pub mod fallback;
 pub mod manager;
 pub mod retry;
 pub mod virtualq;
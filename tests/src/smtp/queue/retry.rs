@@ -14,11 +14,12 @@ use crate::smtp::{
 };
 use ahash::AHashSet;
 use common::{
-    config::smtp::queue::QueueName,
+    config::smtp::queue::{QueueConfig, QueueExpiry, QueueName},
     ipc::{QueueEvent, QueueEventStatus},
 };
-use smtp::queue::spool::{QUEUE_REFRESH, SmtpSpool};
+use smtp::queue::spool::SmtpSpool;
 use store::write::now;
+use utils::config::Config;
 
 const CONFIG: &str = r#"
 [session.ehlo]
@@ -44,8 +45,15 @@ expire = "6s"
 #max-attempts = 3
 queue-name = "default"
 
+[queue.schedule.sender-capped]
+retry = ["1s"]
+notify = ["1d"]
+max-attempts = 2
+queue-name = "default"
+
 [queue.strategy]
 schedule = [{if = "sender_domain == 'test.org'", then = "'sender-test'"},
+           {if = "sender_domain == 'capped.org'", then = "'sender-capped'"},
            {else = "'sender-default'"}]
 "#;
 
@@ -115,14 +123,20 @@ async fn queue_retry() {
                     _ => panic!("unexpected status {queue_id}: {status:?}"),
                 }
             }
-            Some(QueueEvent::Refresh) | Some(QueueEvent::ReloadSettings) => (),
+            Some(QueueEvent::Refresh)
+            | Some(QueueEvent::ReloadSettings)
+            | Some(QueueEvent::FlushOnHold { .. })
+            | Some(QueueEvent::LimiterStats(_))
+            | Some(QueueEvent::Drain { .. })
+            | Some(QueueEvent::HoldUntil { .. })
+            | Some(QueueEvent::Retry(_)) => (),
             None | Some(QueueEvent::Stop) | Some(QueueEvent::Paused(_)) => break,
         }
 
         let now = now();
         let mut events = core.all_queued_messages().await;
         if events.messages.is_empty() {
-            if events.next_refresh < now + QUEUE_REFRESH {
+            if events.next_refresh < now + core.core.smtp.queue.max_idle.as_secs() {
                 tokio::time::sleep(Duration::from_secs(events.next_refresh - now)).await;
                 events = core.all_queued_messages().await;
             } else if in_fight.is_empty() {
@@ -232,3 +246,124 @@ async fn queue_retry() {
         [3599, 3600].contains(&(schedule.message.recipients.first().unwrap().notify.due - now()))
     );
 }
+
+#[test]
+fn domain_retry_schedule() {
+    let mut config = Config::new(
+        r#"
+[queue.domain-schedule.slow]
+domains = ["*.partner.org", "fragile.net"]
+retry = ["30m", "2h"]
+
+[queue.domain-schedule.slower]
+domains = ["*.mx.partner.org"]
+retry = ["4h"]
+"#,
+    )
+    .unwrap();
+    let queue = QueueConfig::parse(&mut config);
+
+    assert_eq!(
+        queue.domain_retry_schedule("fragile.net"),
+        Some(&[1800, 7200][..])
+    );
+    assert_eq!(
+        queue.domain_retry_schedule("eu.partner.org"),
+        Some(&[1800, 7200][..])
+    );
+    assert_eq!(
+        queue.domain_retry_schedule("a.mx.partner.org"),
+        Some(&[14400][..])
+    );
+    assert_eq!(queue.domain_retry_schedule("partner.org"), None);
+    assert_eq!(queue.domain_retry_schedule("notfragile.net"), None);
+    assert_eq!(queue.domain_retry_schedule("example.org"), None);
+}
+
+#[tokio::test]
+async fn queue_retry_max_attempts() {
+    // Enable logging
+    crate::enable_logging();
+
+    let mut local = TestSMTP::new("smtp_queue_retry_max_attempts_test", CONFIG).await;
+    let core = local.build_smtp();
+    let mut session = local.new_session();
+    let qr = &mut local.queue_receiver;
+
+    session.data.remote_ip_str = "10.0.0.1".into();
+    session.eval_session_params().await;
+    session.ehlo("mx.capped.org").await;
+    session
+        .send_message(
+            "john@capped.org",
+            &["jane@_dns_error.org"],
+            "test:no_dkim",
+            "250",
+        )
+        .await;
+
+    // The first attempt fails temporarily and is rescheduled
+    let attempt = qr.expect_message_then_deliver().await;
+    let queue_id = attempt.queue_id;
+    attempt.try_deliver(core.clone());
+    qr.read_event().await.assert_refresh_or_done();
+    let message = core
+        .read_message(queue_id, QueueName::default())
+        .await
+        .unwrap();
+    assert!(matches!(
+        message.message.recipients[0].status,
+        smtp::queue::Status::TemporaryFailure(_)
+    ));
+
+    // The second attempt reaches the cap and bounces without waiting for another retry
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    qr.delivery_attempt(queue_id)
+        .await
+        .try_deliver(core.clone());
+    let dsn = qr.expect_message().await;
+    assert_eq!(dsn.message.return_path.as_ref(), "");
+    dsn.read_lines(qr)
+        .await
+        .assert_contains("Final-Recipient: rfc822;jane@_dns_error.org")
+        .assert_contains("Action: failed");
+    qr.read_event().await.assert_done();
+    assert!(
+        core.read_message(queue_id, QueueName::default())
+            .await
+            .is_none()
+    );
+}
+
+#[test]
+fn queue_schedule_expiry() {
+    let mut config = Config::new(
+        r#"
+[queue.schedule.ttl]
+retry = ["1m"]
+expire = "2d"
+
+[queue.schedule.attempts]
+retry = ["1m"]
+max-attempts = 5
+
+[queue.schedule.both]
+retry = ["1m"]
+expire = "2d"
+max-attempts = 5
+"#,
+    )
+    .unwrap();
+    let queue = QueueConfig::parse(&mut config);
+
+    assert_eq!(
+        queue.queue_strategy.get("ttl").unwrap().expiry,
+        QueueExpiry::Ttl(2 * 86400)
+    );
+    assert_eq!(
+        queue.queue_strategy.get("attempts").unwrap().expiry,
+        QueueExpiry::Attempts(5)
+    );
+    assert!(!queue.queue_strategy.contains_key("both"));
+    assert!(config.errors.contains_key("queue.schedule.both.expire"));
+}
@@ -7,10 +7,11 @@
 use ahash::AHashMap;
 use calcard::{
     common::{IanaString, PartialDateTime},
-    icalendar::{ICalendar, ICalendarProperty, ICalendarValue},
+    icalendar::{ICalendar, ICalendarProperty, ICalendarStatus, ICalendarValue},
 };
 use groupware::scheduling::{
-    ItipMessage, ItipSummary,
+    InstanceId, ItipAlarmTrigger, ItipEntryValue, ItipError, ItipLimits, ItipMessage,
+    ItipSnapshots, ItipSummary, PartStatCounts, RecurrenceId, RequestStatus,
     event_cancel::itip_cancel,
     event_create::itip_create,
     event_update::itip_update,
@@ -19,6 +20,12 @@ use groupware::scheduling::{
 };
 use std::{collections::hash_map::Entry, path::PathBuf};
 
+const LIMITS: ItipLimits = ItipLimits {
+    max_attendees_per_instance: 10,
+    max_overrides: 5,
+    validate_timezones: false,
+};
+
 struct Test {
     test_name: String,
     command: Command,
@@ -143,11 +150,13 @@ pub fn test() {
                                 &mut ical,
                                 entry.get_mut(),
                                 &[account.to_string()],
+                                LIMITS,
                             ));
                             entry.insert(ical);
                         }
                         Entry::Vacant(entry) => {
-                            last_itip = Some(itip_create(&mut ical, &[account.to_string()]));
+                            last_itip =
+                                Some(itip_create(&mut ical, &[account.to_string()], LIMITS));
                             entry.insert(ical);
                         }
                     }
@@ -248,6 +257,8 @@ pub fn test() {
                                     let result = match itip_snapshot(
                                         &message.message,
                                         &[rcpt.to_string()],
+                                        LIMITS,
+                                        None,
                                         false,
                                     ) {
                                         Ok(itip_snapshots) => {
@@ -261,6 +272,8 @@ pub fn test() {
                                                     let snapshots = itip_snapshot(
                                                         ical,
                                                         &[rcpt.to_string()],
+                                                        LIMITS,
+                                                        None,
                                                         false,
                                                     )
                                                     .expect("Failed to create iTIP snapshot");
@@ -414,6 +427,741 @@ impl ItipMessageExt for ItipMessage<ICalendar> {
     }
 }
 
+#[test]
+fn itip_force_send() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:force-send@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+ORGANIZER:mailto:a@example.com
+ATTENDEE;PARTSTAT=ACCEPTED:mailto:a@example.com
+ATTENDEE;SCHEDULE-FORCE-SEND=REQUEST:mailto:b@example.com
+ATTENDEE:mailto:c@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+    let instance = snapshots.main_instance().unwrap();
+
+    assert!(instance.should_force_send(&snapshots.organizer, "b@example.com"));
+    assert!(!instance.should_force_send(&snapshots.organizer, "a@example.com"));
+    assert!(!instance.should_force_send(&snapshots.organizer, "c@example.com"));
+}
+
+#[test]
+fn itip_request_status() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+METHOD:REPLY
+BEGIN:VEVENT
+UID:request-status@example.com
+DTSTAMP:20250101T000000Z
+ORGANIZER:mailto:a@example.com
+ATTENDEE;PARTSTAT=ACCEPTED:mailto:b@example.com
+REQUEST-STATUS:2.0;Success
+REQUEST-STATUS:5.1;Service unavailable;mailto:c@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+    let request_status = &snapshots.main_instance().unwrap().request_status;
+
+    assert_eq!(
+        request_status,
+        &[
+            RequestStatus {
+                code: "2.0".to_string(),
+                description: "Success".to_string(),
+                extra: None,
+            },
+            RequestStatus {
+                code: "5.1".to_string(),
+                description: "Service unavailable".to_string(),
+                extra: Some("mailto:c@example.com".to_string()),
+            },
+        ]
+    );
+    assert!(request_status[0].is_success());
+    assert!(request_status[1].is_failure());
+    assert_eq!(request_status[1].class(), Some(5));
+}
+
+#[test]
+fn itip_unresolved_attendees() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:unresolved@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+ORGANIZER:mailto:a@example.com
+ATTENDEE;PARTSTAT=ACCEPTED:mailto:a@example.com
+ATTENDEE;CN=Jane Doe:urn:uuid:0c7b5d4e-2ef4-4ad3-9fd5-0f13e7f2fe0a
+ATTENDEE;CN=John Doe:urn:uuid:2f5a1a3e-8a6b-4c52-9d8e-6a1f3e2f7c11
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let account_emails = ["a@example.com".to_string()];
+    let aliases = AHashMap::from_iter([("jane doe".to_string(), "jane@example.com".to_string())]);
+
+    // Without aliases both attendees are unresolved
+    let snapshots = itip_snapshot(&ical, &account_emails, LIMITS, None, false).unwrap();
+    let instance = snapshots.main_instance().unwrap();
+    assert_eq!(instance.attendees.len(), 1);
+    assert_eq!(
+        instance
+            .unresolved_attendees
+            .iter()
+            .map(|attendee| attendee.name)
+            .collect::<Vec<_>>(),
+        [Some("Jane Doe"), Some("John Doe")]
+    );
+
+    // Aliases resolve attendees by their common name
+    let snapshots = itip_snapshot(&ical, &account_emails, LIMITS, Some(&aliases), false).unwrap();
+    let instance = snapshots.main_instance().unwrap();
+    assert!(instance.attendee_by_email("jane@example.com").is_some());
+    assert_eq!(instance.unresolved_attendees.len(), 1);
+    assert_eq!(instance.unresolved_attendees[0].name, Some("John Doe"));
+}
+
+#[test]
+fn itip_cancelled_instance() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:cancelled@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+RRULE:FREQ=DAILY;COUNT=3
+STATUS:CONFIRMED
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+BEGIN:VEVENT
+UID:cancelled@example.com
+DTSTAMP:20250101T000000Z
+RECURRENCE-ID:20250103T100000Z
+DTSTART:20250103T100000Z
+STATUS:CANCELLED
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+
+    let main = snapshots.main_instance().unwrap();
+    assert!(!main.is_cancelled());
+    assert!(matches!(main.status, Some(ICalendarStatus::Confirmed)));
+    let instance = snapshots
+        .components
+        .iter()
+        .find_map(|(instance_id, snapshot)| {
+            matches!(instance_id, InstanceId::Recurrence(_)).then_some(snapshot)
+        })
+        .unwrap();
+    assert!(instance.is_cancelled());
+}
+
+#[test]
+fn itip_partstat_summary() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:partstat@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+ORGANIZER:mailto:a@example.com
+ATTENDEE;PARTSTAT=ACCEPTED:mailto:a@example.com
+ATTENDEE;PARTSTAT=ACCEPTED:mailto:b@example.com
+ATTENDEE;PARTSTAT=DECLINED:mailto:c@example.com
+ATTENDEE;PARTSTAT=TENTATIVE:mailto:d@example.com
+ATTENDEE;PARTSTAT=NEEDS-ACTION:mailto:e@example.com
+ATTENDEE:mailto:f@example.com
+ATTENDEE;PARTSTAT=DELEGATED:mailto:g@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+
+    assert_eq!(
+        snapshots.main_instance().unwrap().partstat_summary(),
+        PartStatCounts {
+            accepted: 2,
+            declined: 1,
+            tentative: 1,
+            needs_action: 2,
+            other: 1,
+        }
+    );
+}
+
+#[test]
+fn itip_organizer_delegate() {
+    let ical = |organizer: &str| {
+        ICalendar::parse(&format!(
+            "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:delegate@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+RRULE:FREQ=DAILY;COUNT=3
+ORGANIZER:mailto:assistant@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+BEGIN:VEVENT
+UID:delegate@example.com
+DTSTAMP:20250101T000000Z
+RECURRENCE-ID:20250103T100000Z
+DTSTART:20250103T110000Z
+{organizer}
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+END:VCALENDAR
+"
+        ))
+        .unwrap()
+    };
+    let account_emails = ["b@example.com".to_string()];
+
+    // The same organizer expressed through the delegate's SENT-BY
+    let ical_ = ical("ORGANIZER;SENT-BY=\"mailto:assistant@example.com\":mailto:boss@example.com");
+    let snapshots = itip_snapshot(&ical_, &account_emails, LIMITS, None, false).unwrap();
+    assert_eq!(snapshots.organizer.email.email, "boss@example.com");
+
+    // Genuinely different organizers are still rejected
+    let ical_ = ical("ORGANIZER:mailto:other@example.com");
+    assert!(matches!(
+        itip_snapshot(&ical_, &account_emails, LIMITS, None, false),
+        Err(ItipError::MultipleOrganizer { .. })
+    ));
+}
+
+#[test]
+fn itip_conflicts_with() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:conflicts@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+DTEND:20250102T110000Z
+RRULE:FREQ=DAILY;COUNT=3
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+BEGIN:VEVENT
+UID:conflicts@example.com
+DTSTAMP:20250101T000000Z
+RECURRENCE-ID:20250103T100000Z
+DTSTART:20250103T150000Z
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let main_start = 1735812000;
+    let override_start = 1735916400;
+
+    // Only instances with local attendees are checked
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+    assert!(snapshots.conflicts_with(&[(0, i64::MAX)]).is_empty());
+
+    let snapshots =
+        itip_snapshot(&ical, &["b@example.com".to_string()], LIMITS, None, false).unwrap();
+    assert_eq!(
+        snapshots.conflicts_with(&[(main_start + 1800, main_start + 1900)]),
+        vec![InstanceId::Main]
+    );
+
+    // The override inherits the duration of the main instance
+    let conflicts = snapshots.conflicts_with(&[(override_start + 1800, override_start + 3600)]);
+    assert_eq!(conflicts.len(), 1);
+    assert!(matches!(conflicts[0], InstanceId::Recurrence(_)));
+    assert!(
+        snapshots
+            .conflicts_with(&[
+                (main_start - 3600, main_start),
+                (override_start + 3600, override_start + 7200)
+            ])
+            .is_empty()
+    );
+    assert_eq!(snapshots.conflicts_with(&[(0, i64::MAX)]).len(), 2);
+}
+
+#[test]
+fn itip_effective_end() {
+    for (name, dates, expected_end) in [
+        (
+            "VEVENT",
+            "DTSTART:20250102T100000Z\nDURATION:PT30M\n",
+            Some(1735813800),
+        ),
+        (
+            "VEVENT",
+            "DTSTART:20250102T100000Z\nDTEND:20250102T120000Z\n",
+            Some(1735819200),
+        ),
+        (
+            "VTODO",
+            "DTSTART:20250102T100000Z\nDUE:20250102T110000Z\n",
+            Some(1735815600),
+        ),
+        (
+            "VTODO",
+            "DTSTART:20250102T100000Z\nDURATION:PT1H\n",
+            Some(1735815600),
+        ),
+        ("VEVENT", "DTSTART:20250102T100000Z\n", None),
+    ] {
+        let ical = ICalendar::parse(&format!(
+            "BEGIN:VCALENDAR\nVERSION:2.0\nBEGIN:{name}\nUID:end@example.com\n\
+             DTSTAMP:20250101T000000Z\n{dates}ORGANIZER:mailto:a@example.com\n\
+             ATTENDEE:mailto:b@example.com\nEND:{name}\nEND:VCALENDAR\n"
+        ))
+        .unwrap();
+        let snapshots =
+            itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+        assert_eq!(
+            snapshots.main_instance().unwrap().end,
+            expected_end,
+            "{name} {dates}"
+        );
+    }
+}
+
+#[test]
+fn itip_attendee_is_organizer() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR\nVERSION:2.0\nBEGIN:VEVENT\nUID:self@example.com\n\
+         DTSTAMP:20250101T000000Z\nDTSTART:20250102T100000Z\n\
+         ORGANIZER:mailto:a@example.com\nATTENDEE:mailto:a@example.com\n\
+         ATTENDEE:mailto:b@example.com\nEND:VEVENT\nEND:VCALENDAR\n",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["b@example.com".to_string()], LIMITS, None, false).unwrap();
+    let main = snapshots.main_instance().unwrap();
+
+    assert!(
+        main.attendee_by_email("a@example.com")
+            .unwrap()
+            .is_organizer
+    );
+    assert!(
+        !main
+            .attendee_by_email("b@example.com")
+            .unwrap()
+            .is_organizer
+    );
+}
+
+#[test]
+fn itip_organizer_sent_by() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:sent-by@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+ORGANIZER;SENT-BY=\"mailto:assistant@example.com\":mailto:boss@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots = itip_snapshot(
+        &ical,
+        &["assistant@example.com".to_string()],
+        LIMITS,
+        None,
+        false,
+    )
+    .unwrap();
+    let sent_by = snapshots.organizer.sent_by.as_ref().unwrap();
+
+    assert_eq!(sent_by.email, "assistant@example.com");
+    assert!(sent_by.is_local);
+    assert!(!snapshots.organizer.email.is_local);
+}
+
+#[test]
+fn itip_instance_starts() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:instance-starts@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+RRULE:FREQ=DAILY;COUNT=5
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+BEGIN:VEVENT
+UID:instance-starts@example.com
+DTSTAMP:20250101T000000Z
+RECURRENCE-ID:20250103T100000Z
+DTSTART:20250103T150000Z
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+    let mut starts = snapshots
+        .instance_starts()
+        .map(|(instance_id, start)| (matches!(instance_id, InstanceId::Main), start))
+        .collect::<Vec<_>>();
+    starts.sort_unstable();
+
+    assert_eq!(starts, [(false, 1735916400), (true, 1735812000)]);
+}
+
+#[test]
+fn itip_affected_instances() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:affected-instances@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+RRULE:FREQ=DAILY;COUNT=5
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+    let dates = |rid: RecurrenceId, max_instances: usize| {
+        snapshots
+            .affected_instances(&ical, &rid, max_instances, i64::MAX)
+            .into_iter()
+            .map(|instance_id| match instance_id {
+                InstanceId::Recurrence(rid) => rid.date,
+                InstanceId::Main => unreachable!(),
+            })
+            .collect::<Vec<_>>()
+    };
+    let rid = RecurrenceId {
+        entry_id: 0,
+        date: 1735898400, // 20250103T100000Z
+        this_and_future: false,
+    };
+
+    assert_eq!(dates(rid, 100), [1735898400]);
+    assert_eq!(
+        dates(
+            RecurrenceId {
+                this_and_future: true,
+                ..rid
+            },
+            100
+        ),
+        [1735898400, 1735984800, 1736071200, 1736157600]
+    );
+    assert_eq!(
+        dates(
+            RecurrenceId {
+                this_and_future: true,
+                ..rid
+            },
+            2
+        ),
+        [1735898400, 1735984800]
+    );
+}
+
+#[test]
+fn itip_recurrence_set() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:recurrence-set@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+RRULE:FREQ=DAILY;COUNT=4
+EXDATE:20250103T100000Z
+RDATE:20250110T100000Z
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+
+    let set = snapshots.recurrence_set(&ical, 100, i64::MAX);
+    assert_eq!(
+        set.iter().collect::<Vec<_>>(),
+        [1735812000, 1735984800, 1736071200, 1736503200]
+    );
+    assert!(!set.contains(1735898400));
+
+    let set = snapshots.recurrence_set(&ical, 2, i64::MAX);
+    assert_eq!(set.iter().collect::<Vec<_>>(), [1735812000, 1735984800]);
+}
+
+#[test]
+fn itip_recurrence_horizon() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:recurrence-horizon@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:21000101T000000Z
+RRULE:FREQ=MINUTELY
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+
+    // Instances starting more than ten minutes after the first one are not expanded
+    let first = 4102444800; // 21000101T000000Z
+    let horizon = 600;
+    let expected = (0..=10).map(|i| first + i * 60).collect::<Vec<_>>();
+
+    let set = snapshots.recurrence_set(&ical, 100, horizon);
+    assert_eq!(set.iter().collect::<Vec<_>>(), expected);
+
+    let dates = snapshots
+        .affected_instances(
+            &ical,
+            &RecurrenceId {
+                entry_id: 0,
+                date: first,
+                this_and_future: true,
+            },
+            100,
+            horizon,
+        )
+        .into_iter()
+        .map(|instance_id| match instance_id {
+            InstanceId::Recurrence(rid) => rid.date,
+            InstanceId::Main => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(dates, expected);
+}
+
+#[test]
+fn itip_alarms() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:alarms@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+DTEND:20250102T110000Z
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+BEGIN:VALARM
+ACTION:DISPLAY
+DESCRIPTION:Reminder
+TRIGGER:-PT15M
+END:VALARM
+BEGIN:VALARM
+ACTION:EMAIL
+SUMMARY:Follow up
+DESCRIPTION:Follow up
+TRIGGER;RELATED=END:PT5M
+END:VALARM
+BEGIN:VALARM
+ACTION:AUDIO
+TRIGGER;VALUE=DATE-TIME:20250102T090000Z
+END:VALARM
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+    let alarms = &snapshots.main_instance().unwrap().alarms;
+
+    assert_eq!(alarms.len(), 3);
+    assert_eq!(
+        alarms
+            .iter()
+            .map(|alarm| alarm.action.unwrap())
+            .collect::<Vec<_>>(),
+        ["DISPLAY", "EMAIL", "AUDIO"]
+    );
+    assert!(matches!(
+        &alarms[0].trigger,
+        ItipAlarmTrigger::Relative { duration, related_end: false } if duration.as_seconds() == -900
+    ));
+    assert!(matches!(
+        &alarms[1].trigger,
+        ItipAlarmTrigger::Relative { duration, related_end: true } if duration.as_seconds() == 300
+    ));
+    assert!(matches!(
+        &alarms[2].trigger,
+        ItipAlarmTrigger::Absolute(dt) if dt.timestamp == 1735808400
+    ));
+}
+
+#[test]
+fn itip_contact_attach() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:contact-attach@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+CONTACT:Dial-in +1-555-0100
+ATTACH:https://example.com/agenda.pdf
+ATTACH;ENCODING=BASE64;VALUE=BINARY:SGVsbG8=
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+    let entries = &snapshots.main_instance().unwrap().entries;
+
+    assert!(entries.iter().any(|entry| {
+        entry.name == &ICalendarProperty::Contact
+            && entry.value == ItipEntryValue::Text("Dial-in +1-555-0100")
+    }));
+    assert_eq!(
+        entries
+            .iter()
+            .filter(|entry| entry.name == &ICalendarProperty::Attach)
+            .map(|entry| &entry.value)
+            .collect::<Vec<_>>(),
+        [&ItipEntryValue::Uri("https://example.com/agenda.pdf")]
+    );
+}
+
+#[test]
+fn itip_snapshot_from_ics() {
+    let mut ical = None;
+    let snapshots = ItipSnapshots::from_ics(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:from-ics@example.com
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+END:VCALENDAR
+",
+        &mut ical,
+        &["a@example.com".to_string()],
+        LIMITS,
+    )
+    .unwrap();
+    assert_eq!(snapshots.uid, "from-ics@example.com");
+    assert_eq!(snapshots.organizer.email.email, "a@example.com");
+
+    let mut ical = None;
+    assert!(matches!(
+        ItipSnapshots::from_ics(
+            "this is not an icalendar object",
+            &mut ical,
+            &["a@example.com".to_string()],
+            LIMITS,
+        ),
+        Err(ItipError::ICalendarParseError)
+    ));
+}
+
+#[test]
+fn itip_timezone_conflicts() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VTIMEZONE
+TZID:America/New_York
+BEGIN:STANDARD
+DTSTART:19700101T000000
+TZOFFSETFROM:+0000
+TZOFFSETTO:+0000
+TZNAME:BROKEN
+END:STANDARD
+END:VTIMEZONE
+BEGIN:VEVENT
+UID:tz-conflict@example.com
+DTSTAMP:20250101T000000Z
+DTSTART;TZID=America/New_York:20250102T100000
+ORGANIZER:mailto:a@example.com
+ATTENDEE:mailto:b@example.com
+END:VEVENT
+END:VCALENDAR
+",
+    )
+    .unwrap();
+
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], LIMITS, None, false).unwrap();
+    assert!(snapshots.main_instance().unwrap().tz_warnings.is_empty());
+
+    let limits = ItipLimits {
+        validate_timezones: true,
+        ..LIMITS
+    };
+    let snapshots =
+        itip_snapshot(&ical, &["a@example.com".to_string()], limits, None, false).unwrap();
+    let warnings = &snapshots.main_instance().unwrap().tz_warnings;
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert_eq!(warnings[0].name, &ICalendarProperty::Dtstart);
+    assert_eq!(warnings[0].tz_id, "America/New_York");
+    assert_eq!(warnings[0].iana_timestamp - warnings[0].timestamp, 5 * 3600);
+}
+
 fn normalize_ical(mut ical: ICalendar, map: &mut AHashMap<PartialDateTime, usize>) -> String {
     let mut comps = ical
         .components
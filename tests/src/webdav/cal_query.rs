@@ -7,11 +7,16 @@
 use super::WebDavTest;
 use ahash::AHashSet;
 use calcard::{common::timezone::Tz, icalendar::ICalendar};
+use common::config::groupware::FreeBusyVisibility;
 use groupware::{
     DavResourceName,
-    calendar::{CalendarEventData, alarm::ExpandAlarm, expand::CalendarEventExpansion},
+    calendar::{
+        CalendarEventData, alarm::ExpandAlarm, dates::horizon_expansions,
+        expand::CalendarEventExpansion,
+    },
 };
 use hyper::StatusCode;
+use std::sync::Arc;
 use store::write::serialize::rkyv_unarchive;
 use types::TimeRange;
 
@@ -172,6 +177,42 @@ pub async fn test(test: &WebDavTest) {
         remove_dtstamp(REPORT_11_RESPONSE)
     );
 
+    // Other accounts get what the default free/busy visibility discloses
+    let jane_client = test.client("jane");
+    client
+        .request(
+            "ACL",
+            &cal_path,
+            ACL_FREEBUSY.replace(
+                "$HREF",
+                &format!("{}/jane/", DavResourceName::Principal.base_path()),
+            ),
+        )
+        .await
+        .with_status(StatusCode::OK);
+    let old_core = test.server.core.clone();
+    for (visibility, expected) in [
+        (FreeBusyVisibility::None, REPORT_10_RESPONSE_NONE),
+        (FreeBusyVisibility::BusyOnly, REPORT_10_RESPONSE_BUSY_ONLY),
+        (FreeBusyVisibility::Full, REPORT_10_RESPONSE),
+    ] {
+        let mut new_core = old_core.as_ref().clone();
+        new_core.groupware.free_busy_default_visibility = visibility;
+        test.server.inner.shared_core.store(Arc::new(new_core));
+        let response = jane_client
+            .request("REPORT", &cal_path, REPORT_10)
+            .await
+            .with_status(StatusCode::OK)
+            .body
+            .unwrap();
+        assert_eq!(
+            remove_dtstamp(&response.replace("\r\n ", "")),
+            remove_dtstamp(expected),
+            "failed for {visibility:?}"
+        );
+    }
+    test.server.inner.shared_core.store(old_core);
+
     client.delete_default_containers().await;
     test.assert_is_empty().await;
 }
@@ -199,6 +240,82 @@ fn ical_roundtrip_expansion() {
     }
 }
 
+#[test]
+fn ical_expansion_horizon() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:horizon\r\nDTSTART:20200101T000000Z\r\nDURATION:PT1H\r\nRRULE:FREQ=YEARLY\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+    )
+    .unwrap();
+
+    // Both the instance count and the horizon limit the expansion
+    let horizon = 3 * 366 * 86400;
+    let now = store::write::now() as i64;
+    for (max_expansions, max_horizon) in [(100, horizon), (2, i64::MAX)] {
+        let event_data = CalendarEventData::new(
+            ical.clone(),
+            Tz::UTC,
+            max_expansions,
+            max_horizon,
+            &mut None,
+        );
+        let (start, duration) = event_data.event_range().unwrap();
+        assert_eq!(start, 1577836800);
+        if max_horizon == i64::MAX {
+            assert_eq!(duration, 366 * 86400 + 3600);
+        } else {
+            assert!(start + duration as i64 <= now + horizon + 3600);
+        }
+    }
+}
+
+#[test]
+fn ical_expansion_horizon_timezone() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR\r\nBEGIN:VTIMEZONE\r\nTZID:Europe/Berlin\r\nBEGIN:STANDARD\r\nDTSTART:19701025T030000\r\nRRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\r\nTZOFFSETFROM:+0200\r\nTZOFFSETTO:+0100\r\nEND:STANDARD\r\nBEGIN:DAYLIGHT\r\nDTSTART:19700329T020000\r\nRRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\nTZOFFSETFROM:+0100\r\nTZOFFSETTO:+0200\r\nEND:DAYLIGHT\r\nEND:VTIMEZONE\r\nBEGIN:VEVENT\r\nUID:horizon-tz\r\nDTSTART;TZID=Europe/Berlin:20200101T000000\r\nDURATION:PT1H\r\nRRULE:FREQ=YEARLY\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+    )
+    .unwrap();
+
+    // Timezone rules do not move the start of the expansion back to 1970
+    let horizon = 3 * 366 * 86400;
+    let now = store::write::now() as i64;
+    let max_years = ((now + horizon + 86400 - 1577836800) / (365 * 86400)) as usize + 2;
+    assert!(horizon_expansions(&ical, 1000, horizon) <= max_years);
+    assert_eq!(horizon_expansions(&ical, 1000, i64::MAX), 1000);
+}
+
+#[test]
+fn ical_expansion_horizon_secondly() {
+    let ical = ICalendar::parse(
+        "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:horizon-secondly\r\nDTSTART:21000101T000000Z\r\nDURATION:PT1S\r\nRRULE:FREQ=SECONDLY\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+    )
+    .unwrap();
+
+    // Rules recurring every second stop at the horizon when stored
+    let first = 4102444800; // 21000101T000000Z
+    let event_data = CalendarEventData::new(ical, Tz::UTC, 1000, 30, &mut None);
+    let (start, duration) = event_data.event_range().unwrap();
+    assert_eq!(start, first);
+    assert_eq!(duration, 31);
+
+    // and when expanded with a shorter horizon
+    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&event_data).unwrap();
+    let archive = rkyv_unarchive::<CalendarEventData>(&bytes).unwrap();
+    let starts = archive
+        .expand(
+            Tz::UTC,
+            TimeRange {
+                start: i64::MIN,
+                end: i64::MAX,
+            },
+            10,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|expansion| expansion.start)
+        .collect::<Vec<_>>();
+    assert_eq!(starts, (first..=first + 10).collect::<Vec<_>>());
+}
+
 fn roundtrip_expansion(ics: &str, ignore_errors: bool) {
     let ical = if let Ok(ical) = ICalendar::parse(ics) {
         ical
@@ -255,7 +372,7 @@ fn roundtrip_expansion(ics: &str, ignore_errors: bool) {
         .collect::<Vec<_>>();
 
     // Verify min/max UTC timestamps
-    let event_data = CalendarEventData::new(ical, Tz::UTC, 100, &mut None);
+    let event_data = CalendarEventData::new(ical, Tz::UTC, 100, i64::MAX, &mut None);
     let from_time = event_data.base_time_utc as i64 + event_data.base_offset;
     let to_time = from_time + event_data.duration as i64;
 
@@ -286,6 +403,7 @@ fn roundtrip_expansion(ics: &str, ignore_errors: bool) {
                 start: i64::MIN,
                 end: i64::MAX,
             },
+            i64::MAX,
         )
         .unwrap();
 
@@ -753,6 +871,40 @@ END:VFREEBUSY
 END:VCALENDAR
 "#;
 
+const REPORT_10_RESPONSE_NONE: &str = r#"BEGIN:VCALENDAR
+VERSION:2.0
+PRODID:-//Stalwart Labs LLC//Stalwart Server//EN
+BEGIN:VFREEBUSY
+DTSTART:20060104T140000Z
+DTEND:20060105T220000Z
+END:VFREEBUSY
+END:VCALENDAR
+"#;
+
+const REPORT_10_RESPONSE_BUSY_ONLY: &str = r#"BEGIN:VCALENDAR
+VERSION:2.0
+PRODID:-//Stalwart Labs LLC//Stalwart Server//EN
+BEGIN:VFREEBUSY
+DTSTART:20060104T140000Z
+DTEND:20060105T220000Z
+FREEBUSY;FBTYPE=BUSY:20060104T150000Z/20060104T160000Z,20060104T190000Z/20060104T200000Z,20060105T100000Z/20060105T120000Z,20060105T170000Z/20060105T180000Z
+END:VFREEBUSY
+END:VCALENDAR
+"#;
+
+const ACL_FREEBUSY: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+   <D:acl xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+     <D:ace>
+       <D:principal>
+         <D:href>$HREF</D:href>
+       </D:principal>
+       <D:grant>
+         <D:privilege><D:read/></D:privilege>
+         <D:privilege><C:read-free-busy/></D:privilege>
+       </D:grant>
+     </D:ace>
+   </D:acl>"#;
+
 const REPORT_11: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
    <C:free-busy-query xmlns:C="urn:ietf:params:xml:ns:caldav">
      <C:time-range start="20060101T000000Z"
@@ -6,6 +6,7 @@
 
 use super::WebDavTest;
 use crate::{
+    directory::internal::TestInternalDirectory,
     jmap::mail::mailbox::destroy_all_mailboxes_for_account,
     webdav::{DummyWebDavClient, prop::ALL_DAV_PROPERTIES},
 };
@@ -21,8 +22,10 @@ use dav_proto::schema::property::{CalDavProperty, DavProperty, WebDavProperty};
 use email::cache::MessageCacheFetch;
 use groupware::{
     cache::GroupwareCache,
+    calendar::itip::{ItipIngest, ItipIngestError},
     scheduling::{
-        ArchivedItipSummary, ItipField, ItipParticipant, ItipSummary, ItipTime, ItipValue,
+        ArchivedItipSummary, ItipError, ItipField, ItipParticipant, ItipSummary, ItipTime,
+        ItipValue,
     },
 };
 use hyper::StatusCode;
@@ -528,9 +531,85 @@ pub async fn test(test: &WebDavTest) {
         destroy_all_mailboxes_for_account(client.account_id).await;
     }
 
+    // Inbound iTIP addressed to an alias of the account
+    itip_local_aliases(test).await;
+
     test.assert_is_empty().await;
 }
 
+async fn itip_local_aliases(test: &WebDavTest) {
+    let store = test.server.store();
+    let pat_id = store
+        .create_test_user(
+            "pat",
+            "secret",
+            "Pat Doe",
+            &["pat@example.com", "sales@example.com"],
+        )
+        .await;
+    let lee_id = store
+        .create_test_user("lee", "secret", "Lee Doe", &["lee@example.com"])
+        .await;
+    let itip = "BEGIN:VCALENDAR
+VERSION:2.0
+METHOD:REQUEST
+BEGIN:VEVENT
+UID:local-aliases@remote.org
+DTSTAMP:20250101T000000Z
+DTSTART:20250102T100000Z
+SEQUENCE:0
+SUMMARY:Quarterly review
+ORGANIZER:mailto:organizer@remote.org
+ATTENDEE:MAILTO:Sales@Example.com
+END:VEVENT
+END:VCALENDAR
+";
+
+    // The alias resolves to Pat's account, regardless of the URI scheme case
+    let access_token = test.server.get_access_token(pat_id).await.unwrap();
+    assert!(matches!(
+        test.server
+            .itip_ingest(
+                &access_token,
+                &access_token.as_resource_token(),
+                "organizer@remote.org",
+                "sales@example.com",
+                itip,
+            )
+            .await,
+        Ok(_)
+    ));
+    let events = test
+        .server
+        .fetch_dav_resources(&access_token, pat_id, SyncCollection::Calendar)
+        .await
+        .unwrap();
+    assert_eq!(events.resources.len(), 2);
+
+    // Lee is on the same domain but the alias belongs to someone else
+    let access_token = test.server.get_access_token(lee_id).await.unwrap();
+    assert!(matches!(
+        test.server
+            .itip_ingest(
+                &access_token,
+                &access_token.as_resource_token(),
+                "organizer@remote.org",
+                "lee@example.com",
+                itip,
+            )
+            .await,
+        Err(ItipIngestError::Message(ItipError::NotOrganizerNorAttendee))
+    ));
+
+    let pat_client = DummyWebDavClient::new(pat_id, "pat", "secret", "pat@example.com");
+    assert_eq!(fetch_and_remove_itips(&pat_client).await.len(), 1);
+    pat_client
+        .request("DELETE", "/dav/cal/pat/default", "")
+        .await
+        .with_status(StatusCode::NO_CONTENT);
+    destroy_all_mailboxes_for_account(pat_id).await;
+}
+
 async fn fetch_and_remove_itips(client: &DummyWebDavClient) -> Vec<String> {
     let inbox_href = format!("/dav/itip/{}/inbox/", client.name);
     let response = client
@@ -657,10 +657,17 @@ pub async fn test(test: &WebDavTest, assisted_discovery: bool) {
                 .into_propfind_response(None);
             let props = response.properties(path);
             for prop in chunky_props {
+                let max_size = if matches!(prop, DavProperty::DeadProperty(_)) {
+                    test.server.core.groupware.dead_property_size.unwrap()
+                } else {
+                    test.server.core.groupware.live_property_size
+                };
                 props
                     .get(prop)
                     .with_status(StatusCode::INSUFFICIENT_STORAGE)
-                    .with_description("Property value is too long");
+                    .with_description(&format!(
+                        "Property value exceeds the maximum size of {max_size} bytes"
+                    ));
             }
 
             // Test 18: PROPPATCH should fail on invalid calendar property values
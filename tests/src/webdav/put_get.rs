@@ -197,6 +197,45 @@ pub async fn test(test: &WebDavTest) {
             .with_status(StatusCode::NO_CONTENT);
     }
 
+    // File uploads into shared collections count against the owner's quota
+    let old_core = test.server.core.clone();
+    let mut new_core = old_core.as_ref().clone();
+    new_core.groupware.file_upload_quota = true;
+    test.server.inner.shared_core.store(Arc::new(new_core));
+    let content = "x".repeat(2048);
+    for (owner, sharee, expect) in [
+        ("mike", "john", StatusCode::PAYLOAD_TOO_LARGE),
+        ("john", "mike", StatusCode::CREATED),
+    ] {
+        let owner_client = test.client(owner);
+        let sharee_client = test.client(sharee);
+        let path = format!(
+            "{}/{owner}/quota-shared/",
+            DavResourceName::File.base_path()
+        );
+        owner_client
+            .mkcol("MKCOL", &path, [], [])
+            .await
+            .with_status(StatusCode::CREATED);
+        owner_client
+            .acl(
+                &path,
+                &format!("{}/{sharee}/", DavResourceName::Principal.base_path()),
+                ["read", "write"],
+            )
+            .await
+            .with_status(StatusCode::OK);
+        sharee_client
+            .request("PUT", &format!("{path}file"), &content)
+            .await
+            .with_status(expect);
+        owner_client
+            .request("DELETE", &path, "")
+            .await
+            .with_status(StatusCode::NO_CONTENT);
+    }
+    test.server.inner.shared_core.store(old_core);
+
     // PUT precondition enforcement
     let modseq = [
         test.resources("john", Collection::FileNode)
@@ -441,3 +480,50 @@ END:VCALENDAR
     mike_noquota.delete_default_containers().await;
     test.assert_is_empty().await;
 }
+
+#[test]
+fn groupware_size_limits() {
+    let mut config = utils::config::Config::new(
+        r#"
+[calendar]
+max-size = 1000
+
+[contacts]
+max-size = 2000
+
+[groupware.size-limit.premium]
+accounts = ["jane@example.org", "*@premium.org"]
+calendar = 5000
+
+[groupware.size-limit.enterprise]
+accounts = ["*@premium.org"]
+contacts = 8000
+"#,
+    )
+    .unwrap();
+    let groupware = common::config::groupware::GroupwareConfig::parse(&mut config);
+
+    for (account, ical_size, vcard_size) in [
+        ("jane@example.org", 5000, 2000),
+        ("bill@premium.org", 5000, 8000),
+        ("john@example.org", 1000, 2000),
+    ] {
+        assert_eq!(groupware.ical_size_limit(account), ical_size);
+        assert_eq!(groupware.vcard_size_limit(account), vcard_size);
+    }
+}
+
+#[test]
+fn groupware_size_limits_without_sizes() {
+    let mut config = utils::config::Config::new(
+        r#"
+[groupware.size-limit.empty]
+accounts = ["jane@example.org"]
+"#,
+    )
+    .unwrap();
+    let groupware = common::config::groupware::GroupwareConfig::parse(&mut config);
+
+    assert!(groupware.size_limits.is_empty());
+    assert!(config.errors.contains_key("groupware.size-limit.empty"));
+}